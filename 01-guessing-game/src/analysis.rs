@@ -0,0 +1,161 @@
+//! Post-game strategy review: how well each guess split the numbers still possible.
+//!
+//! Halving the candidate interval is the information-theoretic optimum: whatever the
+//! answer, no guess can guarantee ruling out more. Each guess is scored by the share of
+//! that optimal guaranteed elimination it achieves; a guess outside the interval rules
+//! out nothing new and is flagged as wasted.
+
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+use crate::difficulty::GameConfig;
+use crate::game::{Game, Guess};
+use crate::session::{SessionEvent, SessionLine};
+
+/// One guess of the game under review.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuessReview {
+    pub value: u32,
+    pub ordering: Ordering,
+    /// The numbers still possible before this guess.
+    pub candidates: RangeInclusive<u32>,
+    /// How many of them the answer actually ruled out (all but the secret on a win).
+    pub eliminated: u32,
+    /// The guess was already ruled out, so it taught nothing.
+    pub wasted: bool,
+    /// Guaranteed elimination compared with a guess in the middle, from 0 to 1.
+    pub quality: f64,
+}
+
+impl GuessReview {
+    pub fn candidate_count(&self) -> u64 { (self.candidates.end() - self.candidates.start()) as u64 + 1 }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    pub guesses: Vec<GuessReview>,
+    /// Guesses binary search needs in the worst case for the configured range.
+    pub optimal_attempts: u32,
+}
+
+impl Review {
+    pub fn wasted(&self) -> usize { self.guesses.iter().filter(|g| g.wasted).count() }
+
+    /// Mean guess quality, from 0 to 1 (0 when there were no guesses).
+    pub fn efficiency(&self) -> f64 {
+        if self.guesses.is_empty() {
+            return 0.0;
+        }
+        self.guesses.iter().map(|g| g.quality).sum::<f64>() / self.guesses.len() as f64
+    }
+
+    /// Every guess split the interval as evenly as possible.
+    pub fn is_perfect(&self) -> bool { !self.guesses.is_empty() && self.guesses.iter().all(|g| g.quality >= 1.0) }
+}
+
+/// Review a game's guesses in the order they were made.
+pub fn review(config: GameConfig, history: &[Guess]) -> Review {
+    let (mut low, mut high) = (config.min, config.max);
+    let mut guesses = Vec::with_capacity(history.len());
+    for g in history {
+        // In u64: the full 0..=u32::MAX range holds one more number than u32 can count.
+        let n = (high - low) as u64 + 1;
+        let wasted = !(low..=high).contains(&g.value);
+        // The larger side is what remains if the answer is unlucky; the middle keeps it smallest.
+        let worst = if wasted { n } else { (g.value - low).max(high - g.value) as u64 };
+        let best_worst = n / 2;
+        let quality = (n - worst) as f64 / (n - best_worst) as f64;
+
+        let before = low..=high;
+        match g.ordering {
+            Ordering::Less => low = low.max(g.value + 1),
+            Ordering::Greater => high = high.min(g.value - 1),
+            Ordering::Equal => (low, high) = (g.value, g.value),
+        }
+        let eliminated = (n - (high as u64 + 1).saturating_sub(low as u64)) as u32;
+        guesses.push(GuessReview { value: g.value, ordering: g.ordering, candidates: before, eliminated, wasted, quality });
+    }
+    let size = (config.max - config.min) as u64 + 1;
+    Review { guesses, optimal_attempts: u64::BITS - size.leading_zeros() }
+}
+
+/// Rebuild the game played in a session recording from its seed and the numbers typed.
+/// Commands and invalid input are skipped, as they were during play. `None` when the
+/// recording has no start event.
+pub fn game_from_session(lines: &[SessionLine]) -> Option<Game> {
+    let mut game: Option<Game> = None;
+    for line in lines {
+        match &line.event {
+            // A resumed game starts again in the same file; keep playing the same game.
+            SessionEvent::Start { seed, config, .. } if game.is_none() => game = Some(Game::with_config(*seed, *config)),
+            SessionEvent::Input { text } => {
+                if let Some(game) = game.as_mut()
+                    && let Ok(value) = text.trim().parse()
+                {
+                    let _ = game.guess(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Tone;
+
+    fn guesses(secret: u32, values: &[u32]) -> Vec<Guess> {
+        values.iter().map(|&value| Guess { value, ordering: value.cmp(&secret) }).collect()
+    }
+
+    #[test]
+    fn binary_search_is_perfect_and_repeats_are_wasted() {
+        let config = GameConfig::new(1, 100, None).unwrap();
+        let perfect = review(config, &guesses(37, &[50, 25, 37]));
+        assert_eq!(perfect.optimal_attempts, 7);
+        assert!(perfect.is_perfect());
+        assert_eq!(perfect.guesses[0].eliminated, 51);
+        assert_eq!(perfect.guesses[1].candidates, 1..=49);
+        assert_eq!(perfect.guesses[2].eliminated, 23);
+
+        let sloppy = review(config, &guesses(37, &[90, 95, 10, 37]));
+        assert_eq!(sloppy.wasted(), 1);
+        assert!(sloppy.guesses[1].wasted && sloppy.guesses[1].eliminated == 0);
+        assert_eq!(sloppy.guesses[1].quality, 0.0);
+        assert!(sloppy.efficiency() < 0.5 && !sloppy.is_perfect());
+    }
+
+    #[test]
+    fn reviews_the_full_u32_range() {
+        let config = GameConfig::new(0, u32::MAX, None).unwrap();
+        let review = review(config, &guesses(u32::MAX, &[1 << 31, u32::MAX]));
+        assert_eq!(review.optimal_attempts, 33);
+        assert_eq!(review.guesses[0].candidate_count(), 1 << 32);
+        assert_eq!(review.guesses[0].eliminated, (1 << 31) + 1);
+        assert_eq!(review.guesses[0].quality, 1.0);
+        assert_eq!(review.guesses[1].eliminated, (1 << 31) - 2);
+    }
+
+    #[test]
+    fn rebuilds_the_game_from_a_recording() {
+        let config = GameConfig::new(1, 100, None).unwrap();
+        let secret = Game::with_config(4, config).secret();
+        let line = |event| SessionLine { at_ms: 0, event };
+        let start = |resumed| line(SessionEvent::Start { player: "ana".into(), seed: 4, config, resumed });
+        let lines = vec![
+            start(false),
+            line(SessionEvent::Input { text: "abc".into() }),
+            line(SessionEvent::Input { text: ":hint parity".into() }),
+            line(SessionEvent::Input { text: "101".into() }),
+            line(SessionEvent::Output { text: "Too low".into(), tone: Tone::Error }),
+            start(true),
+            line(SessionEvent::Input { text: secret.to_string() }),
+        ];
+        let game = game_from_session(&lines).unwrap();
+        assert_eq!(game.attempts(), 1);
+        assert!(game.is_over());
+        assert!(game_from_session(&lines[1..5]).is_none());
+    }
+}
@@ -0,0 +1,27 @@
+// Reference bot for the machine protocol (src/machine.rs): plain binary search.
+// Try it with `cargo run -- tournament ./target/debug/bisect-bot`, or copy it to start your own.
+
+use std::io::{self, BufRead, Write};
+
+use basic_game::machine::MachineReply;
+use basic_game::protocol::Feedback;
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let (mut low, mut high) = (0u32, 0u32);
+
+    for line in stdin.lock().lines() {
+        let Ok(reply) = serde_json::from_str::<MachineReply>(&line?) else { break };
+        match reply {
+            MachineReply::Start { min, max, .. } => (low, high) = (min, max),
+            MachineReply::Result { guess, result: Feedback::TooLow, .. } => low = guess + 1,
+            MachineReply::Result { guess, result: Feedback::TooHigh, .. } => high = guess - 1,
+            MachineReply::Result { result: Feedback::Correct, .. } => continue,
+            MachineReply::GameOver { .. } | MachineReply::Error { .. } => break,
+        }
+        writeln!(stdout, "{}", low + (high - low) / 2)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
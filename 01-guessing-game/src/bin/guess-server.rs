@@ -0,0 +1,90 @@
+// Multiplayer guessing server: players race over TCP to find a shared secret.
+// Speaks newline-delimited JSON (see src/protocol.rs), so bots can join with e.g. `nc`.
+
+use std::time::Duration;
+use clap::Parser;
+use colored::*;
+
+use basic_game::i18n::{self, Lang};
+use basic_game::server::{serve, ServerConfig};
+use basic_game::{t, tn, Difficulty, GameConfig};
+
+/// Host a multiplayer race: every player hunts the same secret.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:4000")]
+    addr: String,
+    /// Preset range: easy, normal, hard or insane.
+    #[arg(long, default_value_t = Difficulty::Normal)]
+    difficulty: Difficulty,
+    /// Lowest possible secret (overrides the preset).
+    #[arg(long)]
+    min: Option<u32>,
+    /// Highest possible secret (overrides the preset).
+    #[arg(long)]
+    max: Option<u32>,
+    /// Number of rounds before the final ranking.
+    #[arg(long, default_value_t = 3)]
+    rounds: u32,
+    /// Seconds a player may take per guess before losing a turn.
+    #[arg(long, default_value_t = 30)]
+    guess_timeout: u64,
+    /// Players needed before the first round starts.
+    #[arg(long, default_value_t = 2)]
+    min_players: usize,
+    /// Seed for the round secrets (random when omitted).
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Language of the messages: en or es (default: from LC_ALL, LC_MESSAGES or LANG).
+    #[arg(long)]
+    lang: Option<Lang>,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Some(lang) = cli.lang {
+        i18n::set_lang(lang);
+    }
+    let game = match GameConfig::from_preset(cli.difficulty, cli.min, cli.max, None) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", t!("error.config", error = e).red());
+            std::process::exit(2);
+        }
+    };
+    let config = ServerConfig {
+        game,
+        rounds: cli.rounds.max(1),
+        guess_timeout: Duration::from_secs(cli.guess_timeout.max(1)),
+        min_players: cli.min_players.max(1),
+        seed: cli.seed.unwrap_or_else(rand::random),
+    };
+
+    let listener = match tokio::net::TcpListener::bind(&cli.addr).await {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("{}", t!("server.bind_error", addr = cli.addr, error = e).red());
+            std::process::exit(1);
+        }
+    };
+    println!("{}", tn!("server.listening", config.rounds, addr = cli.addr, min = game.min, max = game.max));
+    println!("{}", t!("server.join_help", join = r#"{"type":"join","name":"you"}"#, guess = r#"{"type":"guess","value":50}"#));
+
+    match serve(listener, config).await {
+        Ok(standings) => {
+            println!("{}", t!("server.ranking").green().bold());
+            for (rank, s) in standings.iter().enumerate() {
+                let name = format!("{:<16}", s.name);
+                let (wins, guesses, timeouts) = (tn!("server.wins", s.wins), tn!("server.guesses", s.guesses), tn!("server.timeouts", s.timeouts));
+                println!("{}", t!("server.standing", rank = rank + 1, name = name, wins = wins, guesses = guesses, timeouts = timeouts));
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", t!("server.error", error = e).red());
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Blitz mode clock: an overall time limit plus a deadline for every guess.
+//!
+//! The clock only does arithmetic on `Instant`s passed in by the caller, so it can be
+//! tested without sleeping. The CLI reads stdin on a separate thread and waits on the
+//! clock's deadline, which keeps the countdown moving while the player types.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlitzClock {
+    started: Instant,
+    total: Duration,
+    per_turn: Duration,
+    turn_started: Instant,
+}
+
+impl BlitzClock {
+    /// Start the overall clock and the first turn at `now`.
+    pub fn start(total: Duration, per_turn: Duration, now: Instant) -> Self {
+        Self { started: now, total, per_turn, turn_started: now }
+    }
+
+    /// Start a new turn (after a guess or a missed deadline) with a fresh per-guess time.
+    pub fn next_turn(&mut self, now: Instant) { self.turn_started = now; }
+
+    /// Time left for the whole game.
+    pub fn total_left(&self, now: Instant) -> Duration { (self.started + self.total).saturating_duration_since(now) }
+
+    /// Time left for this guess: the turn deadline, or the overall limit when that comes first.
+    pub fn turn_left(&self, now: Instant) -> Duration {
+        (self.turn_started + self.per_turn).saturating_duration_since(now).min(self.total_left(now))
+    }
+
+    pub fn out_of_time(&self, now: Instant) -> bool { self.total_left(now).is_zero() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turn_deadline_never_outlasts_the_game() {
+        let t0 = Instant::now();
+        let secs = Duration::from_secs;
+        let mut clock = BlitzClock::start(secs(25), secs(10), t0);
+        assert_eq!(clock.turn_left(t0 + secs(4)), secs(6));
+        assert_eq!(clock.turn_left(t0 + secs(12)), Duration::ZERO);
+
+        clock.next_turn(t0 + secs(12));
+        assert_eq!(clock.turn_left(t0 + secs(12)), secs(10));
+        clock.next_turn(t0 + secs(20));
+        assert_eq!(clock.turn_left(t0 + secs(20)), secs(5));
+        assert!(!clock.out_of_time(t0 + secs(24)));
+        assert!(clock.out_of_time(t0 + secs(25)));
+    }
+}
@@ -0,0 +1,312 @@
+//! Bulls and Cows (the digit version of Mastermind).
+//!
+//! The secret is an N-digit code with distinct digits. Each guess is scored with
+//! bulls (right digit, right place) and cows (right digit, wrong place). `KnuthSolver`
+//! plays the game itself with Knuth's minimax strategy: it guesses the code whose worst
+//! possible answer leaves the fewest candidates.
+
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::game::Outcome;
+
+pub const MAX_DIGITS: usize = 10;
+
+/// Longest code `KnuthSolver` handles. Each guess scores every code against every
+/// candidate, so six digits (151,200 codes) would take hours per guess.
+pub const MAX_SOLVER_DIGITS: usize = 5;
+
+/// Above this many codes the solver only considers guesses that are still candidates,
+/// which keeps five-digit games to seconds at the cost of an attempt or so.
+const FULL_SEARCH_LIMIT: usize = 5040;
+
+/// A code of distinct decimal digits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Code {
+    digits: Vec<u8>,
+    /// Bit `d` is set when digit `d` appears in the code.
+    mask: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeError {
+    WrongLength { expected: usize, got: usize },
+    NotADigit(char),
+    DuplicateDigit(u8),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeError::WrongLength { expected, got } => write!(f, "the code has {} digits, you entered {}", expected, got),
+            CodeError::NotADigit(c) => write!(f, "'{}' is not a digit", c),
+            CodeError::DuplicateDigit(d) => write!(f, "digit {} appears more than once", d),
+        }
+    }
+}
+
+impl std::error::Error for CodeError {}
+
+impl Code {
+    /// Parse a guess such as `"0123"`, checking its length and that digits are distinct.
+    pub fn parse(input: &str, len: usize) -> Result<Self, CodeError> {
+        let input = input.trim();
+        let mut digits = Vec::with_capacity(len);
+        for c in input.chars() {
+            let d = c.to_digit(10).ok_or(CodeError::NotADigit(c))? as u8;
+            digits.push(d);
+        }
+        if digits.len() != len {
+            return Err(CodeError::WrongLength { expected: len, got: digits.len() });
+        }
+        Self::from_digits(digits)
+    }
+
+    fn from_digits(digits: Vec<u8>) -> Result<Self, CodeError> {
+        let mut mask = 0u16;
+        for &d in &digits {
+            if mask & (1 << d) != 0 {
+                return Err(CodeError::DuplicateDigit(d));
+            }
+            mask |= 1 << d;
+        }
+        Ok(Self { digits, mask })
+    }
+
+    pub fn digits(&self) -> &[u8] { &self.digits }
+    pub fn len(&self) -> usize { self.digits.len() }
+    pub fn is_empty(&self) -> bool { self.digits.is_empty() }
+
+    /// Score `guess` against `self` as the secret.
+    pub fn score(&self, guess: &Code) -> Score {
+        let bulls = self.digits.iter().zip(&guess.digits).filter(|(a, b)| a == b).count();
+        let common = (self.mask & guess.mask).count_ones() as usize;
+        Score { bulls, cows: common - bulls }
+    }
+
+    /// Every code of `len` distinct digits, in ascending order.
+    pub fn all(len: usize) -> Vec<Code> {
+        fn extend(prefix: &mut Vec<u8>, mask: u16, len: usize, out: &mut Vec<Code>) {
+            if prefix.len() == len {
+                out.push(Code { digits: prefix.clone(), mask });
+                return;
+            }
+            for d in 0..10u8 {
+                if mask & (1 << d) == 0 {
+                    prefix.push(d);
+                    extend(prefix, mask | (1 << d), len, out);
+                    prefix.pop();
+                }
+            }
+        }
+        let mut out = Vec::new();
+        extend(&mut Vec::with_capacity(len), 0, len, &mut out);
+        out
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.digits.iter().try_for_each(|d| write!(f, "{}", d))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Score {
+    pub bulls: usize,
+    pub cows: usize,
+}
+
+/// How one digit of a guess scored, for per-digit feedback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigitHint {
+    Bull,
+    Cow,
+    Miss,
+}
+
+impl Score {
+    fn index(self, len: usize) -> usize { self.bulls * (len + 1) + self.cows }
+}
+
+#[derive(Debug, Clone)]
+pub struct BullsGame {
+    secret: Code,
+    seed: u64,
+    max_attempts: Option<u32>,
+    history: Vec<(Code, Score)>,
+    outcome: Outcome,
+}
+
+impl BullsGame {
+    /// Start a game with an `len`-digit secret derived from `seed`.
+    pub fn new(seed: u64, len: usize, max_attempts: Option<u32>) -> Self {
+        assert!((1..=MAX_DIGITS).contains(&len), "code length must be 1 to {}", MAX_DIGITS);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut pool: Vec<u8> = (0..10).collect();
+        pool.shuffle(&mut rng);
+        pool.truncate(len);
+        let secret = Code::from_digits(pool).expect("shuffled digits are distinct");
+        Self { secret, seed, max_attempts, history: Vec::new(), outcome: Outcome::InProgress }
+    }
+
+    /// Score a guess and record it. Returns `None` once the game is over.
+    pub fn guess(&mut self, code: Code) -> Option<Score> {
+        if self.outcome != Outcome::InProgress || code.len() != self.secret.len() {
+            return None;
+        }
+        let score = self.secret.score(&code);
+        self.history.push((code, score));
+        if score.bulls == self.secret.len() {
+            self.outcome = Outcome::Won { attempts: self.history.len() };
+        } else if self.attempts_left() == Some(0) {
+            self.outcome = Outcome::Lost { attempts: self.history.len() };
+        }
+        Some(score)
+    }
+
+    /// Per-digit breakdown of a guess against the secret.
+    pub fn digit_hints(&self, code: &Code) -> Vec<DigitHint> {
+        code.digits
+            .iter()
+            .zip(&self.secret.digits)
+            .map(|(g, s)| {
+                if g == s {
+                    DigitHint::Bull
+                } else if self.secret.mask & (1 << g) != 0 {
+                    DigitHint::Cow
+                } else {
+                    DigitHint::Miss
+                }
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize { self.secret.len() }
+    pub fn is_empty(&self) -> bool { self.secret.is_empty() }
+    pub fn outcome(&self) -> Outcome { self.outcome }
+    pub fn is_over(&self) -> bool { self.outcome != Outcome::InProgress }
+    pub fn history(&self) -> &[(Code, Score)] { &self.history }
+    pub fn seed(&self) -> u64 { self.seed }
+
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.max_attempts.map(|max| max.saturating_sub(self.history.len() as u32))
+    }
+
+    /// The secret code. Front-ends should only reveal it once the game is over.
+    pub fn secret(&self) -> &Code { &self.secret }
+}
+
+/// Knuth-style minimax solver.
+#[derive(Debug, Clone)]
+pub struct KnuthSolver {
+    len: usize,
+    all: Vec<Code>,
+    candidates: Vec<Code>,
+}
+
+impl KnuthSolver {
+    pub fn new(len: usize) -> Self {
+        assert!((1..=MAX_SOLVER_DIGITS).contains(&len), "the solver handles codes of 1 to {} digits", MAX_SOLVER_DIGITS);
+        let all = Code::all(len);
+        Self { len, candidates: all.clone(), all }
+    }
+
+    /// The guess minimizing the largest group of candidates that could remain.
+    /// Ties prefer codes that are still candidates (they might win outright).
+    /// Long codes only search the candidates (see `FULL_SEARCH_LIMIT`).
+    pub fn next_guess(&self) -> Option<Code> {
+        match self.candidates.len() {
+            0 => return None,
+            1 | 2 => return Some(self.candidates[0].clone()),
+            _ => {}
+        }
+        if self.candidates.len() == self.all.len() {
+            // Before any answer every code is equivalent; skip the expensive search.
+            return Some(self.all[0].clone());
+        }
+
+        let guesses = if self.all.len() > FULL_SEARCH_LIMIT { &self.candidates } else { &self.all };
+        let mut partitions = vec![0usize; (self.len + 1) * (self.len + 1)];
+        let mut best: Option<(usize, bool, &Code)> = None;
+        for guess in guesses {
+            partitions.iter_mut().for_each(|n| *n = 0);
+            for candidate in &self.candidates {
+                partitions[candidate.score(guess).index(self.len)] += 1;
+            }
+            let worst = partitions.iter().copied().max().unwrap_or(0);
+            let is_candidate = self.candidates.contains(guess);
+            let better = match best {
+                None => true,
+                Some((w, c, _)) => worst < w || (worst == w && is_candidate && !c),
+            };
+            if better {
+                best = Some((worst, is_candidate, guess));
+            }
+        }
+        best.map(|(_, _, code)| code.clone())
+    }
+
+    /// Keep only the candidates that would have produced `score` for `guess`.
+    pub fn record(&mut self, guess: &Code, score: Score) {
+        self.candidates.retain(|c| c.score(guess) == score);
+    }
+
+    pub fn candidates(&self) -> &[Code] { &self.candidates }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_codes() {
+        assert_eq!(Code::parse("012", 4), Err(CodeError::WrongLength { expected: 4, got: 3 }));
+        assert_eq!(Code::parse("0113", 4), Err(CodeError::DuplicateDigit(1)));
+        assert_eq!(Code::parse("01a3", 4), Err(CodeError::NotADigit('a')));
+        assert_eq!(Code::parse(" 0123 ", 4).unwrap().to_string(), "0123");
+    }
+
+    #[test]
+    fn scores_bulls_and_cows() {
+        let secret = Code::parse("1234", 4).unwrap();
+        assert_eq!(secret.score(&Code::parse("1243", 4).unwrap()), Score { bulls: 2, cows: 2 });
+        assert_eq!(secret.score(&Code::parse("5678", 4).unwrap()), Score { bulls: 0, cows: 0 });
+        assert_eq!(Code::all(4).len(), 5040);
+    }
+
+    #[test]
+    fn solver_beats_every_three_digit_code() {
+        for secret in Code::all(3).into_iter().step_by(37) {
+            let mut solver = KnuthSolver::new(3);
+            let mut attempts = 0;
+            loop {
+                let guess = solver.next_guess().unwrap();
+                attempts += 1;
+                let score = secret.score(&guess);
+                if score.bulls == 3 {
+                    break;
+                }
+                solver.record(&guess, score);
+            }
+            assert!(attempts <= 7, "{} took {} attempts", secret, attempts);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the solver handles codes of 1 to 5 digits")]
+    fn solver_refuses_codes_it_cannot_search() {
+        KnuthSolver::new(MAX_SOLVER_DIGITS + 1);
+    }
+
+    #[test]
+    fn game_is_seeded_and_reports_digits() {
+        let game = BullsGame::new(11, 4, Some(10));
+        assert_eq!(game.secret(), BullsGame::new(11, 4, None).secret());
+        let hints = game.digit_hints(game.secret());
+        assert!(hints.iter().all(|h| *h == DigitHint::Bull));
+    }
+}
@@ -0,0 +1,135 @@
+//! Daily challenge: one secret per UTC day, the same for every player.
+//!
+//! The seed is derived from the date, so no server is needed to agree on the number.
+//! A small log in the data directory remembers which days were already played.
+
+use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::{Difficulty, GameConfig};
+use crate::game::{Game, Outcome};
+use crate::storage;
+use crate::t;
+
+const FILE_NAME: &str = "daily.json";
+/// Keeps daily seeds apart from small hand-picked `--seed` values.
+const SEED_SALT: u64 = 0x6461_696c_7921_0000;
+
+/// Today's date in UTC.
+pub fn today() -> NaiveDate { Utc::now().date_naive() }
+
+pub fn seed_for(date: NaiveDate) -> u64 { SEED_SALT ^ date.num_days_from_ce() as u64 }
+
+/// Everyone plays the normal preset so results are comparable.
+pub fn config() -> GameConfig { Difficulty::Normal.config() }
+
+/// The daily game for `date`.
+pub fn game_for(date: NaiveDate) -> Game { Game::with_config(seed_for(date), config()) }
+
+/// Wordle-style summary: an arrow per guess pointing towards the secret.
+///
+/// ```text
+/// Daily #2026-10-16 4/10
+/// ⬆️⬇️⬆️✅
+/// ```
+pub fn share_string(date: NaiveDate, game: &Game) -> String {
+    let score = match game.outcome() {
+        Outcome::Won { attempts } => attempts.to_string(),
+        _ => "X".to_string(),
+    };
+    let budget = game.config().max_attempts.map_or("∞".to_string(), |m| m.to_string());
+    let path: String = game
+        .history()
+        .iter()
+        .map(|g| match g.ordering {
+            Ordering::Less => "⬆️",
+            Ordering::Greater => "⬇️",
+            Ordering::Equal => "✅",
+        })
+        .collect();
+    format!("{}\n{}", t!("daily.share", date = date, score = score, budget = budget), path)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyEntry {
+    /// `None` while the game is still in progress (or was abandoned).
+    pub won: Option<bool>,
+    pub attempts: usize,
+}
+
+/// Which days have been played, keyed by ISO date.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyLog {
+    pub days: BTreeMap<NaiveDate, DailyEntry>,
+}
+
+impl DailyLog {
+    pub fn path() -> io::Result<PathBuf> { Ok(storage::data_dir()?.join(FILE_NAME)) }
+
+    pub fn load() -> io::Result<Self> { storage::read_json(&Self::path()?) }
+
+    pub fn has_played(&self, date: NaiveDate) -> bool { self.days.contains_key(&date) }
+
+    /// Mark the day as played as soon as the game starts, so quitting can't buy a retry.
+    pub fn mark_started(date: NaiveDate) -> io::Result<bool> {
+        storage::update_json(&Self::path()?, |log: &mut DailyLog| {
+            if log.has_played(date) {
+                return false;
+            }
+            log.days.insert(date, DailyEntry { won: None, attempts: 0 });
+            true
+        })
+    }
+
+    pub fn mark_finished(date: NaiveDate, game: &Game) -> io::Result<()> {
+        let won = match game.outcome() {
+            Outcome::InProgress => None,
+            Outcome::Won { .. } => Some(true),
+            Outcome::Lost { .. } => Some(false),
+        };
+        let entry = DailyEntry { won, attempts: game.attempts() };
+        storage::update_json(&Self::path()?, |log: &mut DailyLog| {
+            log.days.insert(date, entry);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_day_same_secret() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let next = day.succ_opt().unwrap();
+        assert_eq!(game_for(day).secret(), game_for(day).secret());
+        assert_ne!(seed_for(day), seed_for(next));
+    }
+
+    #[test]
+    fn share_string_shows_the_path() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let mut game = game_for(day);
+        let secret = game.secret();
+        if secret > 1 {
+            game.guess(1).unwrap();
+        }
+        if secret < 100 {
+            game.guess(100).unwrap();
+        }
+        game.guess(secret).unwrap();
+        let share = share_string(day, &game);
+        let attempts = game.attempts();
+        let title = t!("daily.share", date = "2026-10-16", score = attempts, budget = 10);
+        assert!(share.starts_with(&format!("{}\n", title)));
+        assert!(share.ends_with("✅"));
+        if (2..100).contains(&secret) {
+            assert!(share.contains("⬆️⬇️✅"));
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Difficulty presets and the validated game configuration built from them.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::t;
+
+/// Named presets selectable with `--difficulty`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Insane];
+
+    /// The range and attempt budget of this preset.
+    pub fn config(self) -> GameConfig {
+        let (min, max, max_attempts) = match self {
+            Difficulty::Easy => (1, 50, 12),
+            Difficulty::Normal => (1, 100, 10),
+            Difficulty::Hard => (1, 1_000, 12),
+            // ceil(log2(10_000)) = 14: only a perfect binary search is guaranteed to win.
+            Difficulty::Insane => (1, 10_000, 14),
+        };
+        GameConfig { min, max, max_attempts: Some(max_attempts) }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.name()) }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|d| d.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown difficulty '{}' (expected easy, normal, hard or insane)", s))
+    }
+}
+
+/// The range the secret is drawn from and how many attempts the player gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub min: u32,
+    pub max: u32,
+    /// `None` means unlimited attempts.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self { Difficulty::default().config() }
+}
+
+/// Why a set of options does not make a playable game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    MinAboveMax { min: u32, max: u32 },
+    NoAttempts,
+}
+
+/// Shown in the current language, so every binary reports it the same way.
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MinAboveMax { min, max } => f.write_str(&t!("config.min_above_max", min = min, max = max)),
+            ConfigError::NoAttempts => f.write_str(&t!("config.no_attempts")),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl GameConfig {
+    /// Build a config, rejecting empty ranges and zero-attempt budgets.
+    pub fn new(min: u32, max: u32, max_attempts: Option<u32>) -> Result<Self, ConfigError> {
+        if min > max {
+            return Err(ConfigError::MinAboveMax { min, max });
+        }
+        if max_attempts == Some(0) {
+            return Err(ConfigError::NoAttempts);
+        }
+        Ok(Self { min, max, max_attempts })
+    }
+
+    /// Start from a preset and apply any explicit overrides on top of it.
+    pub fn from_preset(
+        difficulty: Difficulty,
+        min: Option<u32>,
+        max: Option<u32>,
+        max_attempts: Option<u32>,
+    ) -> Result<Self, ConfigError> {
+        let preset = difficulty.config();
+        Self::new(min.unwrap_or(preset.min), max.unwrap_or(preset.max), max_attempts.or(preset.max_attempts))
+    }
+
+    pub fn range(&self) -> RangeInclusive<u32> { self.min..=self.max }
+
+    /// The preset this config matches exactly, if any.
+    pub fn preset(&self) -> Option<Difficulty> {
+        Difficulty::ALL.into_iter().find(|d| d.config() == *self)
+    }
+
+    /// Preset name, or `"custom"` for overridden settings. Used to group results.
+    pub fn label(&self) -> &'static str {
+        self.preset().map_or("custom", Difficulty::name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_presets_case_insensitively() {
+        assert_eq!("HARD".parse::<Difficulty>(), Ok(Difficulty::Hard));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn overrides_apply_on_top_of_preset() {
+        let cfg = GameConfig::from_preset(Difficulty::Easy, None, Some(20), None).unwrap();
+        assert_eq!(cfg, GameConfig { min: 1, max: 20, max_attempts: Some(12) });
+        assert_eq!(GameConfig::from_preset(Difficulty::Easy, Some(60), None, None), Err(ConfigError::MinAboveMax { min: 60, max: 50 }));
+        assert_eq!(GameConfig::new(1, 10, Some(0)), Err(ConfigError::NoAttempts));
+        assert_eq!(ConfigError::MinAboveMax { min: 60, max: 50 }.to_string(), "--min (60) must not be greater than --max (50)");
+    }
+}
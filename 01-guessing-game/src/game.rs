@@ -0,0 +1,243 @@
+//! The guessing-game engine: a small state machine with no I/O.
+//!
+//! A `Game` owns the secret number, its configuration (range and attempt budget) and
+//! the history of guesses. The secret is drawn from a seeded RNG, so the same seed
+//! always produces the same game; this is what lets the CLI, bots and tests share one
+//! core and replay games.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::difficulty::GameConfig;
+
+/// One recorded guess together with how it compared to the secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guess {
+    pub value: u32,
+    /// `Less` means the guess was too low, `Greater` too high.
+    pub ordering: Ordering,
+}
+
+/// Where the game currently stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    InProgress,
+    Won { attempts: usize },
+    /// The attempt budget ran out before the secret was found.
+    Lost { attempts: usize },
+}
+
+/// Why a guess was rejected. Rejected guesses are not recorded as attempts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    OutOfRange { min: u32, max: u32 },
+    GameOver,
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuessError::OutOfRange { min, max } => write!(f, "guess must be between {} and {}", min, max),
+            GuessError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    seed: u64,
+    config: GameConfig,
+    secret: u32,
+    history: Vec<Guess>,
+    /// Turns that used up an attempt without a guess (a blitz deadline passing).
+    wasted: usize,
+    outcome: Outcome,
+}
+
+impl Game {
+    /// Start a game with the default (normal) configuration whose secret is derived from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self::with_config(seed, GameConfig::default())
+    }
+
+    /// Start a game with a custom configuration whose secret is derived from `seed`.
+    pub fn with_config(seed: u64, config: GameConfig) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let secret = rng.gen_range(config.range());
+        Self { seed, config, secret, history: Vec::new(), wasted: 0, outcome: Outcome::InProgress }
+    }
+
+    /// Start a game with a seed drawn from the given RNG (e.g. `rand::thread_rng()`).
+    pub fn from_rng<R: Rng>(rng: &mut R, config: GameConfig) -> Self {
+        Self::with_config(rng.r#gen(), config)
+    }
+
+    /// Submit a guess. Out-of-range guesses are rejected without using up an attempt;
+    /// the game is lost when a wrong guess uses up the last attempt.
+    pub fn guess(&mut self, value: u32) -> Result<Ordering, GuessError> {
+        if self.outcome != Outcome::InProgress {
+            return Err(GuessError::GameOver);
+        }
+        if !self.config.range().contains(&value) {
+            return Err(GuessError::OutOfRange { min: self.config.min, max: self.config.max });
+        }
+
+        let ordering = value.cmp(&self.secret);
+        self.history.push(Guess { value, ordering });
+        if ordering == Ordering::Equal {
+            self.outcome = Outcome::Won { attempts: self.attempts() };
+        } else {
+            self.check_budget();
+        }
+        Ok(ordering)
+    }
+
+    /// Use up an attempt without guessing, e.g. when a turn's time runs out. Like a wrong
+    /// guess, this loses the game when it was the last attempt.
+    pub fn waste_attempt(&mut self) -> Result<(), GuessError> {
+        if self.outcome != Outcome::InProgress {
+            return Err(GuessError::GameOver);
+        }
+        self.wasted += 1;
+        self.check_budget();
+        Ok(())
+    }
+
+    /// End the game as lost whatever attempts are left, e.g. when the overall time is up.
+    pub fn resign(&mut self) {
+        if self.outcome == Outcome::InProgress {
+            self.outcome = Outcome::Lost { attempts: self.attempts() };
+        }
+    }
+
+    fn check_budget(&mut self) {
+        if self.attempts_left() == Some(0) {
+            self.outcome = Outcome::Lost { attempts: self.attempts() };
+        }
+    }
+
+    pub fn outcome(&self) -> Outcome { self.outcome }
+    pub fn is_over(&self) -> bool { self.outcome != Outcome::InProgress }
+    pub fn history(&self) -> &[Guess] { &self.history }
+    /// Attempts used so far: guesses plus wasted turns.
+    pub fn attempts(&self) -> usize { self.history.len() + self.wasted }
+    pub fn wasted(&self) -> usize { self.wasted }
+    pub fn seed(&self) -> u64 { self.seed }
+    pub fn config(&self) -> GameConfig { self.config }
+    pub fn range(&self) -> RangeInclusive<u32> { self.config.range() }
+
+    /// The values still consistent with every answer so far: above the highest "too low"
+    /// guess and below the lowest "too high" one. Contains just the secret once won.
+    pub fn candidates(&self) -> RangeInclusive<u32> {
+        let (mut low, mut high) = (self.config.min, self.config.max);
+        for g in &self.history {
+            match g.ordering {
+                Ordering::Less => low = low.max(g.value + 1),
+                Ordering::Greater => high = high.min(g.value - 1),
+                Ordering::Equal => return g.value..=g.value,
+            }
+        }
+        low..=high
+    }
+
+    /// How many values [`candidates`](Self::candidates) holds; 2^32 for the full `u32` range.
+    pub fn candidate_count(&self) -> u64 {
+        let candidates = self.candidates();
+        u64::from(*candidates.end()) - u64::from(*candidates.start()) + 1
+    }
+
+    /// Remaining attempts, or `None` when the budget is unlimited.
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.config.max_attempts.map(|max| max.saturating_sub(self.attempts() as u32))
+    }
+
+    /// The secret number. Front-ends should only reveal it once the game is over.
+    pub fn secret(&self) -> u32 { self.secret }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_secret() {
+        assert_eq!(Game::new(42).secret(), Game::new(42).secret());
+        assert!(Game::new(7).range().contains(&Game::new(7).secret()));
+    }
+
+    #[test]
+    fn guesses_are_recorded_until_won() {
+        let mut game = Game::with_config(1, GameConfig::new(1, 10, None).unwrap());
+        let secret = game.secret();
+        let low = if secret > 1 { secret - 1 } else { secret + 1 };
+        assert_ne!(game.guess(low).unwrap(), Ordering::Equal);
+        assert_eq!(game.guess(secret).unwrap(), Ordering::Equal);
+        assert_eq!(game.outcome(), Outcome::Won { attempts: 2 });
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.guess(secret), Err(GuessError::GameOver));
+    }
+
+    #[test]
+    fn out_of_range_is_not_an_attempt() {
+        let mut game = Game::new(3);
+        assert_eq!(game.guess(0), Err(GuessError::OutOfRange { min: 1, max: 100 }));
+        assert_eq!(game.guess(101), Err(GuessError::OutOfRange { min: 1, max: 100 }));
+        assert_eq!(game.attempts(), 0);
+    }
+
+    #[test]
+    fn running_out_of_attempts_is_a_loss() {
+        let mut game = Game::with_config(9, GameConfig::new(1, 100, Some(2)).unwrap());
+        let wrong = if game.secret() == 1 { 2 } else { 1 };
+        game.guess(wrong).unwrap();
+        assert_eq!(game.attempts_left(), Some(1));
+        game.guess(wrong).unwrap();
+        assert_eq!(game.outcome(), Outcome::Lost { attempts: 2 });
+        assert_eq!(game.guess(game.secret()), Err(GuessError::GameOver));
+    }
+
+    #[test]
+    fn wasted_turns_use_up_attempts() {
+        let mut game = Game::with_config(9, GameConfig::new(1, 100, Some(3)).unwrap());
+        let wrong = if game.secret() == 1 { 2 } else { 1 };
+        game.waste_attempt().unwrap();
+        game.guess(wrong).unwrap();
+        assert_eq!((game.attempts(), game.wasted(), game.attempts_left()), (2, 1, Some(1)));
+        game.waste_attempt().unwrap();
+        assert_eq!(game.outcome(), Outcome::Lost { attempts: 3 });
+        assert_eq!(game.waste_attempt(), Err(GuessError::GameOver));
+
+        let mut game = Game::new(9);
+        game.resign();
+        assert_eq!(game.outcome(), Outcome::Lost { attempts: 0 });
+    }
+
+    #[test]
+    fn candidates_shrink_with_each_answer() {
+        let mut game = Game::with_config(5, GameConfig::new(1, 100, None).unwrap());
+        let secret = game.secret();
+        assert_eq!(game.candidates(), 1..=100);
+        if secret > 1 {
+            game.guess(1).unwrap();
+        }
+        if secret < 100 {
+            game.guess(100).unwrap();
+        }
+        assert_eq!(game.candidates(), 2.min(secret)..=99.max(secret));
+        game.guess(secret).unwrap();
+        assert_eq!(game.candidates(), secret..=secret);
+        assert_eq!(game.candidate_count(), 1);
+    }
+
+    #[test]
+    fn counts_the_candidates_of_the_full_u32_range() {
+        let game = Game::with_config(5, GameConfig::new(0, u32::MAX, None).unwrap());
+        assert_eq!(game.candidate_count(), 1 << 32);
+    }
+}
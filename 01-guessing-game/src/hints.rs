@@ -0,0 +1,237 @@
+//! Hint economy: clues about the secret bought with points from the game's score.
+//!
+//! A game starts with `START_SCORE` points. Every wrong guess costs `MISS_PENALTY` and
+//! every hint its own `cost`; a lost game scores 0. Hints are only sold while the score
+//! can pay for them.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, Outcome};
+
+pub const START_SCORE: u32 = 1000;
+pub const MISS_PENALTY: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hint {
+    /// Is the last guess closer to the secret than the one before?
+    Warmer,
+    Parity,
+    /// Is the secret divisible by this number?
+    Divisible(u32),
+    DigitSum,
+}
+
+impl Hint {
+    /// One of each kind, for listing them (the divisor is only an example).
+    pub const KINDS: [Hint; 4] = [Hint::Warmer, Hint::Parity, Hint::Divisible(3), Hint::DigitSum];
+
+    pub fn cost(self) -> u32 {
+        match self {
+            Hint::Warmer => 50,
+            Hint::Parity => 100,
+            Hint::Divisible(_) => 75,
+            Hint::DigitSum => 150,
+        }
+    }
+
+    /// The command argument that asks for this hint, e.g. `div 3`.
+    pub fn command(self) -> String {
+        match self {
+            Hint::Warmer => "warmer".into(),
+            Hint::Parity => "parity".into(),
+            Hint::Divisible(n) => format!("div {}", n),
+            Hint::DigitSum => "digitsum".into(),
+        }
+    }
+}
+
+/// Why a `:hint` argument could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintParseError {
+    Unknown(String),
+    /// `div` without a whole number of at least 2.
+    BadDivisor,
+    /// Words left over after a complete hint.
+    Trailing(String),
+}
+
+impl fmt::Display for HintParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HintParseError::Unknown(hint) => write!(f, "unknown hint '{}' (expected warmer, parity, div N or digitsum)", hint),
+            HintParseError::BadDivisor => write!(f, "div needs a whole number of at least 2, e.g. `div 3`"),
+            HintParseError::Trailing(word) => write!(f, "unexpected '{}' after the hint", word),
+        }
+    }
+}
+
+impl std::error::Error for HintParseError {}
+
+impl FromStr for Hint {
+    type Err = HintParseError;
+
+    /// Accepts `warmer` (or `colder`), `parity`, `div N` (or `divisible N`) and `digitsum`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let hint = match words.next().map(str::to_ascii_lowercase).as_deref() {
+            Some("warmer" | "colder") => Hint::Warmer,
+            Some("parity") => Hint::Parity,
+            Some("div" | "divisible") => {
+                let n = words.next().and_then(|n| n.parse().ok()).filter(|&n| n >= 2);
+                Hint::Divisible(n.ok_or(HintParseError::BadDivisor)?)
+            }
+            Some("digitsum" | "digits") => Hint::DigitSum,
+            _ => return Err(HintParseError::Unknown(s.trim().to_string())),
+        };
+        match words.next() {
+            Some(extra) => Err(HintParseError::Trailing(extra.to_string())),
+            None => Ok(hint),
+        }
+    }
+}
+
+/// What a hint revealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clue {
+    Warmer,
+    Colder,
+    SameDistance,
+    Even,
+    Odd,
+    Divisible { divisor: u32, divisible: bool },
+    DigitSum(u32),
+}
+
+impl fmt::Display for Clue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Clue::Warmer => write!(f, "warmer: your last guess is closer than the one before"),
+            Clue::Colder => write!(f, "colder: your last guess is further away than the one before"),
+            Clue::SameDistance => write!(f, "neither warmer nor colder: both guesses are as far away"),
+            Clue::Even => write!(f, "the number is even"),
+            Clue::Odd => write!(f, "the number is odd"),
+            Clue::Divisible { divisor, divisible: true } => write!(f, "the number is divisible by {}", divisor),
+            Clue::Divisible { divisor, divisible: false } => write!(f, "the number is not divisible by {}", divisor),
+            Clue::DigitSum(sum) => write!(f, "the digits of the number add up to {}", sum),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintError {
+    /// The hint costs more than the current score.
+    Unaffordable { cost: u32, score: u32 },
+    /// Warmer/colder compares the last two guesses.
+    NeedsTwoGuesses,
+    GameOver,
+}
+
+impl fmt::Display for HintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HintError::Unaffordable { cost, score } => write!(f, "that hint costs {} points and you have {}", cost, score),
+            HintError::NeedsTwoGuesses => write!(f, "warmer/colder needs at least two guesses to compare"),
+            HintError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl std::error::Error for HintError {}
+
+/// The hints bought during one game.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HintBank {
+    used: Vec<Hint>,
+}
+
+impl HintBank {
+    /// Restore the hints of a saved game.
+    pub fn from_used(used: Vec<Hint>) -> Self { Self { used } }
+
+    pub fn used(&self) -> &[Hint] { &self.used }
+
+    pub fn spent(&self) -> u32 { self.used.iter().map(|h| h.cost()).sum() }
+
+    /// Points left now (or the final score once the game is over).
+    pub fn score(&self, game: &Game) -> u32 {
+        if let Outcome::Lost { .. } = game.outcome() {
+            return 0;
+        }
+        let misses = game.history().iter().filter(|g| g.ordering.is_ne()).count() as u32;
+        START_SCORE.saturating_sub(MISS_PENALTY * misses).saturating_sub(self.spent())
+    }
+
+    /// Pay for `hint` and reveal it. Nothing is charged when the hint can't be given.
+    pub fn request(&mut self, hint: Hint, game: &Game) -> Result<Clue, HintError> {
+        if game.is_over() {
+            return Err(HintError::GameOver);
+        }
+        let score = self.score(game);
+        if hint.cost() > score {
+            return Err(HintError::Unaffordable { cost: hint.cost(), score });
+        }
+        let secret = game.secret();
+        let clue = match hint {
+            Hint::Warmer => {
+                let [.., before, last] = game.history() else { return Err(HintError::NeedsTwoGuesses) };
+                match last.value.abs_diff(secret).cmp(&before.value.abs_diff(secret)) {
+                    std::cmp::Ordering::Less => Clue::Warmer,
+                    std::cmp::Ordering::Greater => Clue::Colder,
+                    std::cmp::Ordering::Equal => Clue::SameDistance,
+                }
+            }
+            Hint::Parity if secret.is_multiple_of(2) => Clue::Even,
+            Hint::Parity => Clue::Odd,
+            Hint::Divisible(divisor) => Clue::Divisible { divisor, divisible: secret.is_multiple_of(divisor) },
+            Hint::DigitSum => Clue::DigitSum(secret.to_string().bytes().map(|b| (b - b'0') as u32).sum()),
+        };
+        self.used.push(hint);
+        Ok(clue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::GameConfig;
+
+    #[test]
+    fn parses_hint_commands() {
+        assert_eq!("parity".parse(), Ok(Hint::Parity));
+        assert_eq!("Div 7".parse(), Ok(Hint::Divisible(7)));
+        assert_eq!("div 1".parse::<Hint>(), Err(HintParseError::BadDivisor));
+        assert_eq!("parity please".parse::<Hint>(), Err(HintParseError::Trailing("please".into())));
+        assert_eq!("colour".parse::<Hint>(), Err(HintParseError::Unknown("colour".into())));
+    }
+
+    #[test]
+    fn hints_reveal_the_secret_and_cost_points() {
+        let mut game = Game::with_config(5, GameConfig::new(10, 99, Some(10)).unwrap());
+        let secret = game.secret();
+        let mut bank = HintBank::default();
+        assert_eq!(bank.request(Hint::Warmer, &game), Err(HintError::NeedsTwoGuesses));
+
+        let clue = bank.request(Hint::Parity, &game).unwrap();
+        assert_eq!(clue, if secret.is_multiple_of(2) { Clue::Even } else { Clue::Odd });
+        let sum = secret / 10 + secret % 10;
+        assert_eq!(bank.request(Hint::DigitSum, &game), Ok(Clue::DigitSum(sum)));
+        assert_eq!(bank.score(&game), START_SCORE - 250);
+
+        let far = if secret > 50 { 10 } else { 99 };
+        let near = if secret > 50 { secret - 1 } else { secret + 1 };
+        game.guess(far).unwrap();
+        game.guess(near).unwrap();
+        assert_eq!(bank.request(Hint::Warmer, &game), Ok(Clue::Warmer));
+        assert_eq!(bank.score(&game), START_SCORE - 300 - 2 * MISS_PENALTY);
+
+        for _ in 0..8 {
+            game.guess(far).unwrap();
+        }
+        assert_eq!(bank.score(&game), 0);
+        assert_eq!(bank.request(Hint::Parity, &game), Err(HintError::GameOver));
+    }
+}
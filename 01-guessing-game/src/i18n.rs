@@ -0,0 +1,623 @@
+//! Message catalog: every text the `basic_game` CLI shows, in each shipped language.
+//!
+//! Messages are looked up by key and may contain named placeholders such as `{min}`,
+//! filled in with the `t!` macro. Counted messages have a `.one` and an `.other` form;
+//! `tn!` picks one for the count and binds it to `{n}`:
+//!
+//! ```
+//! use basic_game::{i18n, t, tn};
+//! i18n::set_lang(i18n::Lang::Es);
+//! assert_eq!(t!("play.too_low"), "Muy bajo. ¡Inténtalo de nuevo!");
+//! assert_eq!(tn!("points", 1), "1 punto");
+//! ```
+//!
+//! The language comes from `--lang` or the usual locale variables (`LC_ALL`,
+//! `LC_MESSAGES`, `LANG`), falling back to English. A key missing from a catalog falls
+//! back to English too; the tests make sure that never happens.
+
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lang {
+    En,
+    Es,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::En, Lang::Es];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+        }
+    }
+
+    /// The language of a locale name such as `es_AR.UTF-8`, if it is one we ship.
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let code = locale.split(['_', '.', '-', '@']).next().unwrap_or_default();
+        Lang::ALL.into_iter().find(|l| l.code().eq_ignore_ascii_case(code))
+    }
+
+    /// The language of the first locale variable that is set, or English.
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::from_locale(&value))
+            .unwrap_or(Lang::En)
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::En => EN,
+            Lang::Es => ES,
+        }
+    }
+
+    /// Plural category of `n`. Both shipped languages only distinguish one from many.
+    fn plural(self, n: u64) -> &'static str {
+        match self {
+            Lang::En | Lang::Es if n == 1 => "one",
+            Lang::En | Lang::Es => "other",
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.code()) }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lang::from_locale(s.trim()).ok_or_else(|| format!("unsupported language '{}' (expected en or es)", s))
+    }
+}
+
+static CURRENT: OnceLock<Lang> = OnceLock::new();
+
+/// Choose the language for the rest of the process. Only the first call has an effect.
+pub fn set_lang(lang: Lang) { let _ = CURRENT.set(lang); }
+
+/// The language in use: the one set with `set_lang`, or the one from the environment.
+pub fn lang() -> Lang { *CURRENT.get_or_init(Lang::from_env) }
+
+/// The raw template for `key` in `lang`.
+pub fn lookup(lang: Lang, key: &str) -> Option<&'static str> {
+    lang.catalog().iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// The message for `key` in the current language with its placeholders filled in.
+/// Prefer the `t!` macro.
+pub fn message(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    match lookup(lang(), key).or_else(|| lookup(Lang::En, key)) {
+        Some(template) => fill(template, args),
+        None => key.to_string(),
+    }
+}
+
+/// The `.one` or `.other` form of `key` for the count `n`, with `{n}` bound to it.
+/// Prefer the `tn!` macro.
+pub fn message_n(key: &str, n: u64, args: &[(&str, &dyn Display)]) -> String {
+    let key = format!("{}.{}", key, lang().plural(n));
+    let mut all: Vec<(&str, &dyn Display)> = vec![("n", &n)];
+    all.extend_from_slice(args);
+    message(&key, &all)
+}
+
+/// Replace every `{name}` that has a matching argument. Other braces are left alone.
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(template.len() + 16);
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| args.iter().find(|(name, _)| *name == &after[..close]).map(|(_, v)| (close, v)));
+        match value {
+            Some((close, value)) => {
+                let _ = write!(out, "{}", value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `t!("key", name = value, ...)`: a message from the catalog.
+#[macro_export]
+macro_rules! t {
+    ($key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::message($key, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+    };
+}
+
+/// `tn!("key", count, name = value, ...)`: a counted message, with `{n}` set to `count`.
+#[macro_export]
+macro_rules! tn {
+    ($key:expr, $n:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::message_n($key, $n as u64, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+    };
+}
+
+const EN: &[(&str, &str)] = &[
+    // Shared
+    ("error.config", "Configuration error: {error}"),
+    ("config.min_above_max", "--min ({min}) must not be greater than --max ({max})"),
+    ("config.no_attempts", "--max-attempts must be at least 1"),
+    ("error.read_input", "Error reading input: {error}"),
+    ("error.save_result", "Could not save your result: {error}"),
+    ("error.not_a_number", "Invalid input. Please enter a number."),
+    ("error.out_of_range", "Please enter a number between {min} and {max}."),
+    ("attempts.one", "{n} attempt"),
+    ("attempts.other", "{n} attempts"),
+    ("points.one", "{n} point"),
+    ("points.other", "{n} points"),
+    ("feedback.too_low", "too low"),
+    ("feedback.too_high", "too high"),
+    ("feedback.correct", "correct"),
+    ("name.ask", "What's your name?"),
+    // Classic game
+    ("play.welcome", "Welcome to the number guessing game!"),
+    ("play.unfinished", "You have an unfinished game; run `basic_game resume` to continue it instead."),
+    ("play.intro", "I've chosen a number between {min} and {max}. Can you guess it?"),
+    ("play.budget.one", "You have {n} attempt."),
+    ("play.budget.other", "You have {n} attempts."),
+    ("play.prompt", "Please enter your guess ({min}-{max}):"),
+    ("play.prompt_left.one", "Please enter your guess ({min}-{max}, {n} left):"),
+    ("play.prompt_left.other", "Please enter your guess ({min}-{max}, {n} left):"),
+    ("play.too_low", "Too low. Try again!"),
+    ("play.too_high", "Too high. Try again!"),
+    ("play.won.one", "Congratulations! You guessed the number {secret} in {n} attempt."),
+    ("play.won.other", "Congratulations! You guessed the number {secret} in {n} attempts."),
+    ("play.lost.one", "Out of attempts after {n} guess. The number was {secret}."),
+    ("play.lost.other", "Out of attempts after {n} guesses. The number was {secret}."),
+    ("play.score_intro", "Score: {score} points. Each wrong guess costs {penalty}; type :hint to buy a clue."),
+    ("play.final_score.one", "Final score: {score} of {max} points ({spent} spent on {n} hint)."),
+    ("play.final_score.other", "Final score: {score} of {max} points ({spent} spent on {n} hints)."),
+    // Hints
+    ("hint.unknown_command", "Unknown command ':{command}'. Try :hint."),
+    ("hint.list", "You have {score} points. Hints:"),
+    ("hint.invalid", "Invalid hint: {error}."),
+    ("hint.bought", "Hint (-{cost} points): {clue}. Score: {score}."),
+    ("hint.refused", "No hint: {error}."),
+    ("hint.parse.unknown", "unknown hint '{hint}' (expected warmer, parity, div N or digitsum)"),
+    ("hint.parse.divisor", "div needs a whole number of at least 2, e.g. `div 3`"),
+    ("hint.parse.trailing", "unexpected '{word}' after the hint"),
+    ("hint.error.unaffordable", "that hint costs {cost} points and you have {score}"),
+    ("hint.error.two_guesses", "warmer/colder needs at least two guesses to compare"),
+    ("hint.error.game_over", "the game is already over"),
+    ("clue.warmer", "warmer: your last guess is closer than the one before"),
+    ("clue.colder", "colder: your last guess is further away than the one before"),
+    ("clue.same", "neither warmer nor colder: both guesses are as far away"),
+    ("clue.even", "the number is even"),
+    ("clue.odd", "the number is odd"),
+    ("clue.divisible", "the number is divisible by {divisor}"),
+    ("clue.not_divisible", "the number is not divisible by {divisor}"),
+    ("clue.digit_sum", "the digits of the number add up to {sum}"),
+    // Saving, resuming and recording
+    ("resume.none", "There is no saved game. Start one with `cargo run`!"),
+    ("resume.read_error", "Could not read the saved game: {error}"),
+    ("resume.corrupt", "The saved game is corrupt ({error}); discarding it."),
+    ("resume.welcome", "Welcome back, {player}! The number is still between {min} and {max}."),
+    ("resume.hints", "Hints bought so far: {hints}."),
+    ("save.done", "Game saved. Run `basic_game resume` to continue."),
+    ("save.error", "Could not save the game: {error}"),
+    ("save.clear_error", "Could not remove the saved game: {error}"),
+    ("save.no_handler", "Ctrl+C will not save the game: {error}"),
+    ("session.not_recording", "Not recording this session: {error}"),
+    ("session.stopped", "Stopped recording the session: {error}"),
+    ("replay.speed", "--speed must be a positive number"),
+    ("replay.read_error", "Could not read {file}: {error}"),
+    ("replay.started", "[{player} started a game between {min} and {max}, seed {seed}]"),
+    ("replay.resumed", "[{player} resumed a game between {min} and {max}, seed {seed}]"),
+    ("replay.end.one", "[{outcome} after {n} guess]"),
+    ("replay.end.other", "[{outcome} after {n} guesses]"),
+    ("outcome.won", "won"),
+    ("outcome.lost", "lost"),
+    ("outcome.saved", "saved"),
+    // Strategy review
+    ("analysis.title.one", "Strategy review (halving the range finds any number here in {n} guess):"),
+    ("analysis.title.other", "Strategy review (halving the range finds any number here in at most {n} guesses):"),
+    ("analysis.guess", "  #{index} {guess}: {low}–{high} possible, ruled out {eliminated} ({percent}%), {quality}% of the best split"),
+    ("analysis.wasted", "  #{index} {guess}: wasted, only {low}–{high} was still possible"),
+    ("analysis.summary.one", "Efficiency: {percent}% ({n} wasted guess)"),
+    ("analysis.summary.other", "Efficiency: {percent}% ({n} wasted guesses)"),
+    ("analysis.no_guesses", "No guesses to review."),
+    ("analysis.no_game", "{file} does not contain a game."),
+    // Full-screen interface
+    ("tui.failed", "The full-screen interface failed ({error}); switching to plain mode."),
+    ("tui.attempts_left", "Attempts left:"),
+    ("tui.unlimited", "unlimited"),
+    ("tui.score", "Score:"),
+    ("tui.time", "Time:"),
+    ("tui.history", "History"),
+    ("tui.possible.one", "Still possible: {low}–{high} ({n} number)"),
+    ("tui.possible.other", "Still possible: {low}–{high} ({n} numbers)"),
+    ("tui.input", "Your guess, or :hint (Esc saves and quits)"),
+    ("tui.game_over", "Game over"),
+    ("tui.press_key", "Press any key to exit"),
+    // Daily challenge
+    ("daily.already", "You already played the daily challenge for {date}. Come back tomorrow, or use --practice."),
+    ("daily.read_error", "Could not read the daily log: {error}"),
+    ("daily.welcome", "Welcome to the daily challenge for {date}!"),
+    ("daily.welcome_practice", "Welcome to the daily challenge for {date} (practice)!"),
+    ("daily.intro", "Everyone gets the same number between {min} and {max} today."),
+    ("daily.share", "Daily #{date} {score}/{budget}"),
+    // Blitz mode
+    ("blitz.welcome", "Welcome to blitz mode!"),
+    ("blitz.rules", "You have {total}s in all and {turn}s per guess; a late guess wastes the attempt."),
+    ("blitz.clock", "[{turn}s | {total}s]"),
+    ("blitz.too_slow", "Too slow! That attempt is wasted."),
+    ("blitz.out_of_time", "Time's up! The number was {secret}."),
+    ("blitz.won.one", "Congratulations! You found {secret} in {n} attempt with {left}s to spare."),
+    ("blitz.won.other", "Congratulations! You found {secret} in {n} attempts with {left}s to spare."),
+    ("blitz.wasted.one", "{n} attempt ran out of time."),
+    ("blitz.wasted.other", "{n} attempts ran out of time."),
+    // Reverse mode
+    ("reverse.intro", "Think of a number between {min} and {max} and I'll guess it."),
+    ("reverse.how", "Answer each guess with h (higher), l (lower) or c (correct)."),
+    ("reverse.ask", "Is it {guess}?"),
+    ("reverse.bad_answer", "Please answer h, l or c."),
+    ("reverse.got_it.one", "Got it! Your number is {guess} ({n} guess)."),
+    ("reverse.got_it.other", "Got it! Your number is {guess} ({n} guesses)."),
+    ("reverse.cheated", "You cheated! {reason}."),
+    ("reverse.conflict", "answer #{answer} contradicts answer #{other}"),
+    ("reverse.outside", "answer #{answer} puts the secret outside the allowed range"),
+    ("reverse.said.higher", "higher"),
+    ("reverse.said.lower", "lower"),
+    ("reverse.said.correct", "correct"),
+    // Liar mode
+    ("liar.welcome", "Welcome to the liar's guessing game!"),
+    ("liar.intro.one", "I've chosen a number between {min} and {max}, but I may lie up to {n} time."),
+    ("liar.intro.other", "I've chosen a number between {min} and {max}, but I may lie up to {n} times."),
+    ("liar.hint.one", "Hint: {n} candidate left, try {guess}."),
+    ("liar.hint.other", "Hint: {n} candidates left, try {guess}."),
+    ("liar.won.one", "Congratulations! You found {secret} in {n} attempt despite the lies."),
+    ("liar.won.other", "Congratulations! You found {secret} in {n} attempts despite the lies."),
+    ("liar.lost", "Out of attempts. The number was {secret}."),
+    ("liar.lies.one", "I lied {n} time:"),
+    ("liar.lies.other", "I lied {n} times:"),
+    ("liar.lie", "(LIE)"),
+    // Bulls and Cows
+    ("bulls.welcome", "Welcome to Bulls and Cows!"),
+    ("bulls.intro.one", "I've chosen a {digits}-digit code with no repeated digits. You have {n} attempt."),
+    ("bulls.intro.other", "I've chosen a {digits}-digit code with no repeated digits. You have {n} attempts."),
+    ("bulls.legend", "{green} = right digit, right place; {yellow} = right digit, wrong place."),
+    ("bulls.green", "green"),
+    ("bulls.yellow", "yellow"),
+    ("bulls.solver.one", "Solver guesses {code} ({n} candidate left)"),
+    ("bulls.solver.other", "Solver guesses {code} ({n} candidates left)"),
+    ("bulls.solver_digits", "--solve works with codes of at most {max} digits"),
+    ("bulls.prompt.one", "Please enter your guess ({digits} digits, {n} left):"),
+    ("bulls.prompt.other", "Please enter your guess ({digits} digits, {n} left):"),
+    ("bulls.invalid", "Invalid code: {error}."),
+    ("bulls.won.one", "Cracked it! The code was {code} ({n} attempt)."),
+    ("bulls.won.other", "Cracked it! The code was {code} ({n} attempts)."),
+    ("bulls.lost", "Out of attempts. The code was {code}."),
+    ("bulls.bulls.one", "{n} bull"),
+    ("bulls.bulls.other", "{n} bulls"),
+    ("bulls.cows.one", "{n} cow"),
+    ("bulls.cows.other", "{n} cows"),
+    ("code.wrong_length", "the code has {expected} digits, you entered {got}"),
+    ("code.not_a_digit", "'{char}' is not a digit"),
+    ("code.duplicate", "digit {digit} appears more than once"),
+    // Multiplayer server
+    ("server.accept_error", "Could not accept a connection: {error}"),
+    ("server.bind_error", "Failed to bind {addr}: {error}"),
+    ("server.listening.one", "🎯 Guess server listening on {addr} ({n} round, {min}-{max})"),
+    ("server.listening.other", "🎯 Guess server listening on {addr} ({n} rounds, {min}-{max})"),
+    ("server.join_help", "   Join with: {join} then {guess}"),
+    ("server.error", "Server error: {error}"),
+    ("server.ranking", "Final ranking"),
+    ("server.standing", "  {rank}. {name} {wins}, {guesses} ({timeouts})"),
+    ("server.wins.one", "{n} win"),
+    ("server.wins.other", "{n} wins"),
+    ("server.guesses.one", "{n} guess"),
+    ("server.guesses.other", "{n} guesses"),
+    ("server.timeouts.one", "{n} timeout"),
+    ("server.timeouts.other", "{n} timeouts"),
+    // Tournament
+    ("tournament.no_bots", "no bot commands given"),
+    ("tournament.intro", "Playing {games} games between {min} and {max} per bot (seeds {seed}..), {ms} ms per guess..."),
+    ("tournament.bot", "bot"),
+    ("tournament.games", "games"),
+    ("tournament.wins", "wins"),
+    ("tournament.forfeits", "forfeits"),
+    ("tournament.mean", "mean"),
+    ("tournament.worst", "worst"),
+    ("tournament.forfeited.one", "{bot} forfeited {n} game, first because: {reason}"),
+    ("tournament.forfeited.other", "{bot} forfeited {n} games, first because: {reason}"),
+    // Achievements and profile
+    ("achievement.unlocked", "Achievement unlocked: {name} ({description})!"),
+    ("achievement.first_try.name", "First try"),
+    ("achievement.first_try.description", "win with your very first guess"),
+    ("achievement.last_attempt.name", "Photo finish"),
+    ("achievement.last_attempt.description", "win with your last attempt"),
+    ("achievement.win_streak.name", "On a roll"),
+    ("achievement.win_streak.description", "win {goal} games in a row"),
+    ("achievement.perfect_search.name", "Perfect binary search"),
+    ("achievement.perfect_search.description", "win with every guess splitting the numbers left exactly in half"),
+    ("profile.summary", "Games: {games}, wins: {wins}, current streak: {streak} (best {best})"),
+    ("profile.unlocked", "Unlocked"),
+    ("profile.locked", "Locked"),
+    ("profile.none", "none yet"),
+    ("profile.unlocked_on", "unlocked {date}"),
+    ("profile.read_error", "Could not read the profile: {error}"),
+    ("profile.save_error", "Could not update the profile: {error}"),
+    // Leaderboard
+    ("leaderboard.read_error", "Could not read the leaderboard: {error}"),
+    ("leaderboard.empty", "No games recorded yet. Play one with `cargo run`!"),
+    ("leaderboard.best", "Best scores"),
+    ("leaderboard.players", "Players"),
+    ("leaderboard.name", "name"),
+    ("leaderboard.games", "games"),
+    ("leaderboard.wins", "wins"),
+    ("leaderboard.avg_attempts", "avg attempts"),
+    ("leaderboard.avg_time", "avg time"),
+];
+
+const ES: &[(&str, &str)] = &[
+    // Shared
+    ("error.config", "Error de configuración: {error}"),
+    ("config.min_above_max", "--min ({min}) no puede ser mayor que --max ({max})"),
+    ("config.no_attempts", "--max-attempts debe ser al menos 1"),
+    ("error.read_input", "Error al leer la entrada: {error}"),
+    ("error.save_result", "No se pudo guardar tu resultado: {error}"),
+    ("error.not_a_number", "Entrada inválida. Escribe un número."),
+    ("error.out_of_range", "Escribe un número entre {min} y {max}."),
+    ("attempts.one", "{n} intento"),
+    ("attempts.other", "{n} intentos"),
+    ("points.one", "{n} punto"),
+    ("points.other", "{n} puntos"),
+    ("feedback.too_low", "muy bajo"),
+    ("feedback.too_high", "muy alto"),
+    ("feedback.correct", "correcto"),
+    ("name.ask", "¿Cómo te llamas?"),
+    // Classic game
+    ("play.welcome", "¡Bienvenido al juego de adivinar el número!"),
+    ("play.unfinished", "Tienes una partida sin terminar; ejecuta `basic_game resume` para continuarla."),
+    ("play.intro", "Elegí un número entre {min} y {max}. ¿Puedes adivinarlo?"),
+    ("play.budget.one", "Tienes {n} intento."),
+    ("play.budget.other", "Tienes {n} intentos."),
+    ("play.prompt", "Escribe tu número ({min}-{max}):"),
+    ("play.prompt_left.one", "Escribe tu número ({min}-{max}, queda {n}):"),
+    ("play.prompt_left.other", "Escribe tu número ({min}-{max}, quedan {n}):"),
+    ("play.too_low", "Muy bajo. ¡Inténtalo de nuevo!"),
+    ("play.too_high", "Muy alto. ¡Inténtalo de nuevo!"),
+    ("play.won.one", "¡Felicidades! Adivinaste el número {secret} en {n} intento."),
+    ("play.won.other", "¡Felicidades! Adivinaste el número {secret} en {n} intentos."),
+    ("play.lost.one", "Sin intentos después de {n} número. El número era {secret}."),
+    ("play.lost.other", "Sin intentos después de {n} números. El número era {secret}."),
+    ("play.score_intro", "Puntaje: {score} puntos. Cada error cuesta {penalty}; escribe :hint para comprar una pista."),
+    ("play.final_score.one", "Puntaje final: {score} de {max} puntos ({spent} gastados en {n} pista)."),
+    ("play.final_score.other", "Puntaje final: {score} de {max} puntos ({spent} gastados en {n} pistas)."),
+    // Hints
+    ("hint.unknown_command", "Comando desconocido ':{command}'. Prueba :hint."),
+    ("hint.list", "Tienes {score} puntos. Pistas:"),
+    ("hint.invalid", "Pista inválida: {error}."),
+    ("hint.bought", "Pista (-{cost} puntos): {clue}. Puntaje: {score}."),
+    ("hint.refused", "Sin pista: {error}."),
+    ("hint.parse.unknown", "pista desconocida '{hint}' (se espera warmer, parity, div N o digitsum)"),
+    ("hint.parse.divisor", "div necesita un número entero de al menos 2, p. ej. `div 3`"),
+    ("hint.parse.trailing", "sobra '{word}' después de la pista"),
+    ("hint.error.unaffordable", "esa pista cuesta {cost} puntos y tienes {score}"),
+    ("hint.error.two_guesses", "caliente/frío necesita al menos dos intentos para comparar"),
+    ("hint.error.game_over", "la partida ya terminó"),
+    ("clue.warmer", "caliente: tu último número está más cerca que el anterior"),
+    ("clue.colder", "frío: tu último número está más lejos que el anterior"),
+    ("clue.same", "ni caliente ni frío: los dos números están igual de lejos"),
+    ("clue.even", "el número es par"),
+    ("clue.odd", "el número es impar"),
+    ("clue.divisible", "el número es divisible por {divisor}"),
+    ("clue.not_divisible", "el número no es divisible por {divisor}"),
+    ("clue.digit_sum", "los dígitos del número suman {sum}"),
+    // Saving, resuming and recording
+    ("resume.none", "No hay ninguna partida guardada. ¡Empieza una con `cargo run`!"),
+    ("resume.read_error", "No se pudo leer la partida guardada: {error}"),
+    ("resume.corrupt", "La partida guardada está dañada ({error}); se descarta."),
+    ("resume.welcome", "¡Hola de nuevo, {player}! El número sigue entre {min} y {max}."),
+    ("resume.hints", "Pistas compradas hasta ahora: {hints}."),
+    ("save.done", "Partida guardada. Ejecuta `basic_game resume` para continuar."),
+    ("save.error", "No se pudo guardar la partida: {error}"),
+    ("save.clear_error", "No se pudo borrar la partida guardada: {error}"),
+    ("save.no_handler", "Ctrl+C no guardará la partida: {error}"),
+    ("session.not_recording", "Esta sesión no se grabará: {error}"),
+    ("session.stopped", "Se dejó de grabar la sesión: {error}"),
+    ("replay.speed", "--speed debe ser un número positivo"),
+    ("replay.read_error", "No se pudo leer {file}: {error}"),
+    ("replay.started", "[{player} empezó una partida entre {min} y {max}, semilla {seed}]"),
+    ("replay.resumed", "[{player} retomó una partida entre {min} y {max}, semilla {seed}]"),
+    ("replay.end.one", "[{outcome} tras {n} intento]"),
+    ("replay.end.other", "[{outcome} tras {n} intentos]"),
+    ("outcome.won", "ganada"),
+    ("outcome.lost", "perdida"),
+    ("outcome.saved", "guardada"),
+    // Strategy review
+    ("analysis.title.one", "Análisis de la estrategia (partiendo el rango a la mitad se encuentra cualquier número en {n} intento):"),
+    ("analysis.title.other", "Análisis de la estrategia (partiendo el rango a la mitad se encuentra cualquier número en {n} intentos como máximo):"),
+    ("analysis.guess", "  #{index} {guess}: {low}–{high} posibles, descartó {eliminated} ({percent}%), {quality}% de la mejor división"),
+    ("analysis.wasted", "  #{index} {guess}: desperdiciado, solo {low}–{high} seguía siendo posible"),
+    ("analysis.summary.one", "Eficiencia: {percent}% ({n} intento desperdiciado)"),
+    ("analysis.summary.other", "Eficiencia: {percent}% ({n} intentos desperdiciados)"),
+    ("analysis.no_guesses", "No hay intentos que analizar."),
+    ("analysis.no_game", "{file} no contiene ninguna partida."),
+    // Full-screen interface
+    ("tui.failed", "Falló la interfaz de pantalla completa ({error}); se pasa al modo simple."),
+    ("tui.attempts_left", "Intentos restantes:"),
+    ("tui.unlimited", "ilimitados"),
+    ("tui.score", "Puntaje:"),
+    ("tui.time", "Tiempo:"),
+    ("tui.history", "Historial"),
+    ("tui.possible.one", "Aún posible: {low}–{high} ({n} número)"),
+    ("tui.possible.other", "Aún posible: {low}–{high} ({n} números)"),
+    ("tui.input", "Tu número, o :hint (Esc guarda y sale)"),
+    ("tui.game_over", "Fin de la partida"),
+    ("tui.press_key", "Pulsa cualquier tecla para salir"),
+    // Daily challenge
+    ("daily.already", "Ya jugaste el desafío diario del {date}. Vuelve mañana o usa --practice."),
+    ("daily.read_error", "No se pudo leer el registro diario: {error}"),
+    ("daily.welcome", "¡Bienvenido al desafío diario del {date}!"),
+    ("daily.welcome_practice", "¡Bienvenido al desafío diario del {date} (práctica)!"),
+    ("daily.intro", "Hoy todos buscan el mismo número entre {min} y {max}."),
+    ("daily.share", "Diario #{date} {score}/{budget}"),
+    // Blitz mode
+    ("blitz.welcome", "¡Bienvenido al modo blitz!"),
+    ("blitz.rules", "Tienes {total}s en total y {turn}s por intento; un número tardío pierde el intento."),
+    ("blitz.clock", "[{turn}s | {total}s]"),
+    ("blitz.too_slow", "¡Demasiado lento! Ese intento se pierde."),
+    ("blitz.out_of_time", "¡Se acabó el tiempo! El número era {secret}."),
+    ("blitz.won.one", "¡Felicidades! Encontraste el {secret} en {n} intento y te sobraron {left}s."),
+    ("blitz.won.other", "¡Felicidades! Encontraste el {secret} en {n} intentos y te sobraron {left}s."),
+    ("blitz.wasted.one", "A {n} intento se le acabó el tiempo."),
+    ("blitz.wasted.other", "A {n} intentos se les acabó el tiempo."),
+    // Reverse mode
+    ("reverse.intro", "Piensa un número entre {min} y {max} y yo lo adivinaré."),
+    ("reverse.how", "Responde a cada intento con h (más alto), l (más bajo) o c (correcto)."),
+    ("reverse.ask", "¿Es {guess}?"),
+    ("reverse.bad_answer", "Responde h, l o c."),
+    ("reverse.got_it.one", "¡Lo tengo! Tu número es {guess} ({n} intento)."),
+    ("reverse.got_it.other", "¡Lo tengo! Tu número es {guess} ({n} intentos)."),
+    ("reverse.cheated", "¡Hiciste trampa! {reason}."),
+    ("reverse.conflict", "la respuesta #{answer} contradice la respuesta #{other}"),
+    ("reverse.outside", "la respuesta #{answer} deja el número fuera del rango permitido"),
+    ("reverse.said.higher", "más alto"),
+    ("reverse.said.lower", "más bajo"),
+    ("reverse.said.correct", "correcto"),
+    // Liar mode
+    ("liar.welcome", "¡Bienvenido al juego del mentiroso!"),
+    ("liar.intro.one", "Elegí un número entre {min} y {max}, pero puedo mentir hasta {n} vez."),
+    ("liar.intro.other", "Elegí un número entre {min} y {max}, pero puedo mentir hasta {n} veces."),
+    ("liar.hint.one", "Pista: queda {n} candidato, prueba {guess}."),
+    ("liar.hint.other", "Pista: quedan {n} candidatos, prueba {guess}."),
+    ("liar.won.one", "¡Felicidades! Encontraste el {secret} en {n} intento a pesar de las mentiras."),
+    ("liar.won.other", "¡Felicidades! Encontraste el {secret} en {n} intentos a pesar de las mentiras."),
+    ("liar.lost", "Sin intentos. El número era {secret}."),
+    ("liar.lies.one", "Mentí {n} vez:"),
+    ("liar.lies.other", "Mentí {n} veces:"),
+    ("liar.lie", "(MENTIRA)"),
+    // Bulls and Cows
+    ("bulls.welcome", "¡Bienvenido a Picas y Fijas!"),
+    ("bulls.intro.one", "Elegí un código de {digits} dígitos sin repetir. Tienes {n} intento."),
+    ("bulls.intro.other", "Elegí un código de {digits} dígitos sin repetir. Tienes {n} intentos."),
+    ("bulls.legend", "{green} = dígito correcto en su lugar; {yellow} = dígito correcto en otro lugar."),
+    ("bulls.green", "verde"),
+    ("bulls.yellow", "amarillo"),
+    ("bulls.solver.one", "El solucionador prueba {code} (queda {n} candidato)"),
+    ("bulls.solver.other", "El solucionador prueba {code} (quedan {n} candidatos)"),
+    ("bulls.solver_digits", "--solve solo admite códigos de hasta {max} dígitos"),
+    ("bulls.prompt.one", "Escribe tu código ({digits} dígitos, queda {n}):"),
+    ("bulls.prompt.other", "Escribe tu código ({digits} dígitos, quedan {n}):"),
+    ("bulls.invalid", "Código inválido: {error}."),
+    ("bulls.won.one", "¡Descifrado! El código era {code} ({n} intento)."),
+    ("bulls.won.other", "¡Descifrado! El código era {code} ({n} intentos)."),
+    ("bulls.lost", "Sin intentos. El código era {code}."),
+    ("bulls.bulls.one", "{n} fija"),
+    ("bulls.bulls.other", "{n} fijas"),
+    ("bulls.cows.one", "{n} pica"),
+    ("bulls.cows.other", "{n} picas"),
+    ("code.wrong_length", "el código tiene {expected} dígitos y escribiste {got}"),
+    ("code.not_a_digit", "'{char}' no es un dígito"),
+    ("code.duplicate", "el dígito {digit} aparece más de una vez"),
+    // Multiplayer server
+    ("server.accept_error", "No se pudo aceptar una conexión: {error}"),
+    ("server.bind_error", "No se pudo escuchar en {addr}: {error}"),
+    ("server.listening.one", "🎯 Servidor de adivinanzas escuchando en {addr} ({n} ronda, {min}-{max})"),
+    ("server.listening.other", "🎯 Servidor de adivinanzas escuchando en {addr} ({n} rondas, {min}-{max})"),
+    ("server.join_help", "   Únete con: {join} y luego {guess}"),
+    ("server.error", "Error del servidor: {error}"),
+    ("server.ranking", "Clasificación final"),
+    ("server.standing", "  {rank}. {name} {wins}, {guesses} ({timeouts})"),
+    ("server.wins.one", "{n} victoria"),
+    ("server.wins.other", "{n} victorias"),
+    ("server.guesses.one", "{n} intento"),
+    ("server.guesses.other", "{n} intentos"),
+    ("server.timeouts.one", "{n} turno perdido"),
+    ("server.timeouts.other", "{n} turnos perdidos"),
+    // Tournament
+    ("tournament.no_bots", "no se indicó ningún bot"),
+    ("tournament.intro", "Jugando {games} partidas entre {min} y {max} por bot (semillas {seed}..), {ms} ms por intento..."),
+    ("tournament.bot", "bot"),
+    ("tournament.games", "partidas"),
+    ("tournament.wins", "ganadas"),
+    ("tournament.forfeits", "abandonos"),
+    ("tournament.mean", "media"),
+    ("tournament.worst", "peor"),
+    ("tournament.forfeited.one", "{bot} perdió {n} partida por abandono; la primera porque: {reason}"),
+    ("tournament.forfeited.other", "{bot} perdió {n} partidas por abandono; la primera porque: {reason}"),
+    // Achievements and profile
+    ("achievement.unlocked", "¡Logro desbloqueado: {name} ({description})!"),
+    ("achievement.first_try.name", "A la primera"),
+    ("achievement.first_try.description", "gana con tu primer número"),
+    ("achievement.last_attempt.name", "Por los pelos"),
+    ("achievement.last_attempt.description", "gana en tu último intento"),
+    ("achievement.win_streak.name", "Racha imparable"),
+    ("achievement.win_streak.description", "gana {goal} partidas seguidas"),
+    ("achievement.perfect_search.name", "Búsqueda binaria perfecta"),
+    ("achievement.perfect_search.description", "gana partiendo siempre a la mitad exacta los números posibles"),
+    ("profile.summary", "Partidas: {games}, ganadas: {wins}, racha actual: {streak} (mejor {best})"),
+    ("profile.unlocked", "Desbloqueados"),
+    ("profile.locked", "Bloqueados"),
+    ("profile.none", "ninguno todavía"),
+    ("profile.unlocked_on", "desbloqueado el {date}"),
+    ("profile.read_error", "No se pudo leer el perfil: {error}"),
+    ("profile.save_error", "No se pudo actualizar el perfil: {error}"),
+    // Leaderboard
+    ("leaderboard.read_error", "No se pudo leer la tabla de puntajes: {error}"),
+    ("leaderboard.empty", "Todavía no hay partidas. ¡Juega una con `cargo run`!"),
+    ("leaderboard.best", "Mejores puntajes"),
+    ("leaderboard.players", "Jugadores"),
+    ("leaderboard.name", "nombre"),
+    ("leaderboard.games", "partidas"),
+    ("leaderboard.wins", "ganadas"),
+    ("leaderboard.avg_attempts", "intentos prom."),
+    ("leaderboard.avg_time", "tiempo prom."),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn placeholders(template: &str) -> BTreeSet<&str> {
+        template.split('{').skip(1).filter_map(|part| part.split_once('}').map(|(name, _)| name)).collect()
+    }
+
+    #[test]
+    fn every_locale_has_every_key_with_the_same_placeholders() {
+        for lang in Lang::ALL {
+            let keys: BTreeSet<&str> = lang.catalog().iter().map(|(k, _)| *k).collect();
+            assert_eq!(keys.len(), lang.catalog().len(), "{} has duplicate keys", lang);
+            for (key, english) in EN {
+                let translated = lookup(lang, key).unwrap_or_else(|| panic!("{} is missing {}", lang, key));
+                assert_eq!(placeholders(translated), placeholders(english), "{}: {}", lang, key);
+            }
+            for key in keys {
+                assert!(lookup(Lang::En, key).is_some(), "{} has {} but English does not", lang, key);
+                if let Some(base) = key.strip_suffix(".one") {
+                    assert!(lookup(lang, &format!("{}.other", base)).is_some(), "{}: {} has no .other form", lang, base);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fills_placeholders_and_detects_locales() {
+        let n = 3;
+        assert_eq!(fill("{n} of {max} {missing} {", &[("n", &n), ("max", &"ten")]), "3 of ten {missing} {");
+        assert_eq!(Lang::from_locale("es_AR.UTF-8"), Some(Lang::Es));
+        assert_eq!(Lang::from_locale("en"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("C"), None);
+        assert_eq!("ES".parse(), Ok(Lang::Es));
+    }
+}
@@ -0,0 +1,140 @@
+//! Persistent results of finished games and the statistics derived from them.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, Outcome};
+use crate::storage;
+
+const FILE_NAME: &str = "leaderboard.json";
+
+/// One finished game as stored on disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub player: String,
+    /// Preset name (`easy`, `normal`, ...) or `custom`.
+    pub difficulty: String,
+    pub won: bool,
+    pub attempts: usize,
+    pub duration_ms: u64,
+    /// Seconds since the Unix epoch.
+    pub finished_at: u64,
+    pub seed: u64,
+    /// Points left after wrong guesses and hints (see `hints`); absent in older records.
+    #[serde(default)]
+    pub score: Option<u32>,
+}
+
+impl GameRecord {
+    /// Build a record from a finished game. Returns `None` while the game is still running.
+    pub fn from_game(player: &str, game: &Game, duration: Duration) -> Option<Self> {
+        let won = match game.outcome() {
+            Outcome::InProgress => return None,
+            Outcome::Won { .. } => true,
+            Outcome::Lost { .. } => false,
+        };
+        Some(Self {
+            player: player.to_string(),
+            difficulty: game.config().label().to_string(),
+            won,
+            attempts: game.attempts(),
+            duration_ms: duration.as_millis() as u64,
+            finished_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            seed: game.seed(),
+            score: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub records: Vec<GameRecord>,
+}
+
+/// Aggregated results of one player across all difficulties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerStats {
+    pub player: String,
+    pub games: usize,
+    pub wins: usize,
+    /// Mean attempts over won games; `None` when the player never won.
+    pub avg_attempts: Option<f64>,
+    pub avg_duration_ms: u64,
+}
+
+impl Leaderboard {
+    pub fn path() -> io::Result<PathBuf> { Ok(storage::data_dir()?.join(FILE_NAME)) }
+
+    pub fn load() -> io::Result<Self> { storage::read_json(&Self::path()?) }
+
+    /// Append a record under the file lock so concurrent games don't overwrite each other.
+    pub fn record(record: GameRecord) -> io::Result<()> {
+        storage::update_json(&Self::path()?, |board: &mut Leaderboard| board.records.push(record))
+    }
+
+    /// Best wins for each difficulty: fewest attempts first, then fastest.
+    pub fn best_by_difficulty(&self, limit: usize) -> BTreeMap<String, Vec<&GameRecord>> {
+        let mut best: BTreeMap<String, Vec<&GameRecord>> = BTreeMap::new();
+        for r in self.records.iter().filter(|r| r.won) {
+            best.entry(r.difficulty.clone()).or_default().push(r);
+        }
+        for list in best.values_mut() {
+            list.sort_by_key(|r| (r.attempts, r.duration_ms));
+            list.truncate(limit);
+        }
+        best
+    }
+
+    /// Per-player averages, sorted by player name.
+    pub fn player_stats(&self) -> Vec<PlayerStats> {
+        let mut by_player: BTreeMap<&str, Vec<&GameRecord>> = BTreeMap::new();
+        for r in &self.records {
+            by_player.entry(r.player.as_str()).or_default().push(r);
+        }
+        by_player
+            .into_iter()
+            .map(|(player, games)| {
+                let wins: Vec<_> = games.iter().filter(|r| r.won).collect();
+                let avg_attempts = (!wins.is_empty())
+                    .then(|| wins.iter().map(|r| r.attempts as f64).sum::<f64>() / wins.len() as f64);
+                let avg_duration_ms = games.iter().map(|r| r.duration_ms).sum::<u64>() / games.len() as u64;
+                PlayerStats { player: player.to_string(), games: games.len(), wins: wins.len(), avg_attempts, avg_duration_ms }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(player: &str, difficulty: &str, won: bool, attempts: usize, duration_ms: u64) -> GameRecord {
+        GameRecord { player: player.into(), difficulty: difficulty.into(), won, attempts, duration_ms, finished_at: 0, seed: 0, score: None }
+    }
+
+    #[test]
+    fn best_and_averages() {
+        let board = Leaderboard {
+            records: vec![
+                record("ana", "easy", true, 5, 9_000),
+                record("ana", "easy", true, 3, 12_000),
+                record("bob", "easy", true, 3, 4_000),
+                record("bob", "hard", false, 12, 30_000),
+            ],
+        };
+        let best = board.best_by_difficulty(2);
+        let easy: Vec<_> = best["easy"].iter().map(|r| (r.player.as_str(), r.attempts)).collect();
+        assert_eq!(easy, vec![("bob", 3), ("ana", 3)]);
+        assert!(!best.contains_key("hard"));
+
+        let stats = board.player_stats();
+        assert_eq!(stats[0].player, "ana");
+        assert_eq!(stats[0].avg_attempts, Some(4.0));
+        assert_eq!(stats[1].wins, 1);
+        assert_eq!(stats[1].avg_duration_ms, 17_000);
+    }
+}
@@ -0,0 +1,226 @@
+//! Ulam's searching game: "Too high/Too low" feedback may be a lie, at most `k` times.
+//!
+//! `LiarGame` wraps a normal `Game` (which always knows the truth) and decides, from the
+//! same seed, when to flip the reported `Ordering`. A correct guess is always reported
+//! truthfully. `LieTracker` is the matching solver: for every candidate secret it counts
+//! how many of the answers so far would have to be lies, and drops candidates that would
+//! need more than `k`.
+
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::difficulty::GameConfig;
+use crate::game::{Game, GuessError, Outcome};
+
+/// Chance that a wrong guess gets a lie while the lie budget lasts.
+const LIE_PROBABILITY: f64 = 1.0 / 3.0;
+
+/// One answer as the player saw it, plus whether it was a lie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiarAnswer {
+    pub value: u32,
+    /// The feedback shown to the player (guess compared to the secret).
+    pub reported: Ordering,
+    pub lie: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct LiarGame {
+    game: Game,
+    rng: ChaCha8Rng,
+    max_lies: u32,
+    lies_told: u32,
+    answers: Vec<LiarAnswer>,
+}
+
+impl LiarGame {
+    pub fn new(seed: u64, config: GameConfig, max_lies: u32) -> Self {
+        // Same seed as the secret, separate stream, so a seed replays the lies too.
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(1);
+        Self { game: Game::with_config(seed, config), rng, max_lies, lies_told: 0, answers: Vec::new() }
+    }
+
+    /// Submit a guess and get the (possibly false) feedback.
+    pub fn guess(&mut self, value: u32) -> Result<Ordering, GuessError> {
+        let truth = self.game.guess(value)?;
+        let lie = truth != Ordering::Equal && self.lies_told < self.max_lies && self.rng.gen_bool(LIE_PROBABILITY);
+        let reported = if lie { truth.reverse() } else { truth };
+        if lie {
+            self.lies_told += 1;
+        }
+        self.answers.push(LiarAnswer { value, reported, lie });
+        Ok(reported)
+    }
+
+    pub fn outcome(&self) -> Outcome { self.game.outcome() }
+    pub fn is_over(&self) -> bool { self.game.is_over() }
+    pub fn answers(&self) -> &[LiarAnswer] { &self.answers }
+    pub fn attempts_left(&self) -> Option<u32> { self.game.attempts_left() }
+    pub fn max_lies(&self) -> u32 { self.max_lies }
+    pub fn lies_told(&self) -> u32 { self.lies_told }
+    pub fn config(&self) -> GameConfig { self.game.config() }
+    pub fn seed(&self) -> u64 { self.game.seed() }
+
+    /// The underlying truthful game, e.g. for recording results.
+    pub fn game(&self) -> &Game { &self.game }
+
+    /// The secret number. Front-ends should only reveal it once the game is over.
+    pub fn secret(&self) -> u32 { self.game.secret() }
+}
+
+/// Candidate set of a liar game: per candidate, the number of answers that contradict it.
+///
+/// Every answer splits the range at most twice (at the guess and just after it), so the
+/// counts are kept as runs of neighbouring candidates that need the same number of lies.
+/// That keeps memory and time proportional to the number of answers, not the range.
+#[derive(Debug, Clone)]
+pub struct LieTracker {
+    max: u32,
+    max_lies: u32,
+    /// `(start, lies)` pairs sorted by `start`: every candidate from `start` up to the
+    /// next run's start (or `max`) would need `lies` lies.
+    runs: Vec<(u32, u32)>,
+}
+
+impl LieTracker {
+    pub fn new(config: GameConfig, max_lies: u32) -> Self {
+        Self { max: config.max, max_lies, runs: vec![(config.min, 0)] }
+    }
+
+    /// Account for one reported answer.
+    pub fn record(&mut self, value: u32, reported: Ordering) {
+        self.split_at(value);
+        if let Some(next) = value.checked_add(1) {
+            self.split_at(next);
+        }
+        for (start, lies) in &mut self.runs {
+            // Runs never straddle `value`, so their first candidate stands for all of them.
+            if *start == value && reported != Ordering::Equal {
+                // Correct guesses are never disguised, so this candidate is simply gone.
+                *lies = u32::MAX;
+            } else if value.cmp(start) != reported {
+                *lies = lies.saturating_add(1);
+            }
+        }
+    }
+
+    /// Start a new run at `at` if it falls inside one.
+    fn split_at(&mut self, at: u32) {
+        if at > self.max {
+            return;
+        }
+        let i = self.runs.partition_point(|(start, _)| *start <= at);
+        if i == 0 || self.runs[i - 1].0 == at {
+            return;
+        }
+        let lies = self.runs[i - 1].1;
+        self.runs.insert(i, (at, lies));
+    }
+
+    /// Remaining candidates, as ranges that each need the same number of lies.
+    pub fn candidates(&self) -> impl Iterator<Item = (RangeInclusive<u32>, u32)> + '_ {
+        let ends = self.runs.iter().skip(1).map(|(start, _)| start - 1).chain(std::iter::once(self.max));
+        self.runs
+            .iter()
+            .zip(ends)
+            .filter(|((_, lies), _)| *lies <= self.max_lies)
+            .map(|(&(start, lies), end)| (start..=end, lies))
+    }
+
+    /// Lies needed if the secret were `value`, or `None` if it has been ruled out.
+    pub fn lies_for(&self, value: u32) -> Option<u32> {
+        self.candidates().find(|(range, _)| range.contains(&value)).map(|(_, lies)| lies)
+    }
+
+    pub fn candidate_count(&self) -> u64 { self.candidates().map(|(range, _)| run_len(&range)).sum() }
+
+    /// Suggested next guess: the weighted median of the candidates, where a candidate
+    /// weighs more the more lies it still has in reserve (weight `2^(k - lies)`), since
+    /// those are the ones later answers can least easily rule out.
+    pub fn suggest(&self) -> Option<u32> {
+        let weight = |lies: u32| 1u128 << (self.max_lies - lies).min(32);
+        let total: u128 = self.candidates().map(|(range, l)| weight(l) * run_len(&range) as u128).sum();
+        let mut acc = 0;
+        for (range, lies) in self.candidates() {
+            let w = weight(lies);
+            let run_weight = w * run_len(&range) as u128;
+            if (acc + run_weight) * 2 >= total {
+                // The first candidate in the run that brings the running total to half.
+                let needed = (total - acc * 2).div_ceil(w * 2).max(1);
+                return Some(range.start() + (needed - 1) as u32);
+            }
+            acc += run_weight;
+        }
+        None
+    }
+}
+
+fn run_len(range: &RangeInclusive<u32>) -> u64 { (*range.end() - *range.start()) as u64 + 1 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> GameConfig { GameConfig::new(1, 100, None).unwrap() }
+
+    #[test]
+    fn never_lies_more_than_k_times_and_never_on_the_secret() {
+        for seed in 0..50 {
+            let mut game = LiarGame::new(seed, config(), 2);
+            for value in 1..=100 {
+                if game.is_over() {
+                    break;
+                }
+                game.guess(value).unwrap();
+            }
+            assert!(game.lies_told() <= 2);
+            let last = game.answers().last().unwrap();
+            assert_eq!((last.value, last.reported, last.lie), (game.secret(), Ordering::Equal, false));
+        }
+    }
+
+    #[test]
+    fn tracker_keeps_the_secret_and_solves_the_game() {
+        for seed in 0..50 {
+            let mut game = LiarGame::new(seed, config(), 1);
+            let mut tracker = LieTracker::new(config(), 1);
+            while !game.is_over() {
+                let guess = tracker.suggest().unwrap();
+                let reported = game.guess(guess).unwrap();
+                tracker.record(guess, reported);
+                assert_eq!(tracker.lies_for(game.secret()), Some(game.lies_told()));
+            }
+            assert!(game.answers().len() <= 20, "seed {} took {} guesses", seed, game.answers().len());
+        }
+    }
+
+    #[test]
+    fn tracker_handles_the_full_u32_range() {
+        let mut tracker = LieTracker::new(GameConfig::new(0, u32::MAX, None).unwrap(), 1);
+        assert_eq!(tracker.candidate_count(), 1 << 32);
+        tracker.record(u32::MAX, Ordering::Greater);
+        tracker.record(0, Ordering::Greater);
+        // Both guesses were wrong, so they are gone; everything else contradicts "0 is too high".
+        assert_eq!(tracker.lies_for(0), None);
+        assert_eq!(tracker.lies_for(u32::MAX), None);
+        assert_eq!(tracker.lies_for(7), Some(1));
+        assert_eq!(tracker.candidate_count(), (1 << 32) - 2);
+        assert!(tracker.suggest().is_some());
+    }
+
+    #[test]
+    fn suggestion_is_the_weighted_median() {
+        let mut tracker = LieTracker::new(config(), 1);
+        assert_eq!(tracker.suggest(), Some(50));
+        // 1..=39 now needs a lie and weighs half as much as 41..=100.
+        tracker.record(40, Ordering::Less);
+        assert_eq!(tracker.lies_for(39), Some(1));
+        assert_eq!(tracker.lies_for(40), None);
+        assert_eq!(tracker.lies_for(41), Some(0));
+        assert_eq!(tracker.suggest(), Some(61));
+    }
+}
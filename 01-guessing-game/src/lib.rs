@@ -0,0 +1,22 @@
+// Library crate exposing the game engine so the CLI, bots and tests share one core.
+pub mod analysis;
+pub mod blitz;
+pub mod bulls;
+pub mod daily;
+pub mod difficulty;
+pub mod game;
+pub mod hints;
+pub mod i18n;
+pub mod leaderboard;
+pub mod liar;
+pub mod machine;
+pub mod profile;
+pub mod protocol;
+pub mod reverse;
+pub mod server;
+pub mod session;
+pub mod storage;
+pub mod tournament;
+
+pub use difficulty::{ConfigError, Difficulty, GameConfig};
+pub use game::{Game, Guess, GuessError, Outcome};
@@ -0,0 +1,112 @@
+//! Line protocol for bots, used by `basic_game --machine` and the tournament harness.
+//!
+//! The game speaks first with a `start` line. The bot answers with one guess per line:
+//! a bare decimal number such as `50`. Each guess gets a `result` line, and the last one
+//! is followed by `game_over`:
+//!
+//! ```text
+//! < {"type":"start","min":1,"max":100,"max_attempts":10}
+//! > 50
+//! < {"type":"result","guess":50,"result":"too_low","attempts_left":9}
+//! > 75
+//! < {"type":"result","guess":75,"result":"correct","attempts_left":8}
+//! < {"type":"game_over","outcome":"won","attempts":2,"secret":75}
+//! ```
+//!
+//! The protocol is strict: anything that is not a number in range gets an `error` line
+//! and ends the game.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, Outcome};
+use crate::protocol::Feedback;
+
+/// Lines sent by the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MachineReply {
+    Start { min: u32, max: u32, max_attempts: Option<u32> },
+    Result { guess: u32, result: Feedback, attempts_left: Option<u32> },
+    /// `outcome` is `won` or `lost`.
+    GameOver { outcome: String, attempts: usize, secret: u32 },
+    Error { message: String },
+}
+
+impl MachineReply {
+    /// The reply as one line of JSON, without the trailing newline.
+    pub fn to_line(&self) -> String { serde_json::to_string(self).expect("replies always serialize") }
+}
+
+/// Parse a guess line. Only ASCII digits are accepted (a trailing `\r` is tolerated).
+pub fn parse_guess(line: &str) -> Result<u32, String> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    if line.is_empty() || !line.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("expected a number, got {:?}", line));
+    }
+    line.parse().map_err(|_| format!("{} is too large", line))
+}
+
+/// Runs one game on behalf of a bot: turns guess lines into replies.
+#[derive(Debug, Clone)]
+pub struct Referee {
+    game: Game,
+}
+
+impl Referee {
+    pub fn new(game: Game) -> Self { Self { game } }
+
+    pub fn start(&self) -> MachineReply {
+        let config = self.game.config();
+        MachineReply::Start { min: config.min, max: config.max, max_attempts: config.max_attempts }
+    }
+
+    /// Handle one line from the bot. `Err` is a protocol violation: send it and stop.
+    pub fn handle(&mut self, line: &str) -> Result<Vec<MachineReply>, MachineReply> {
+        let error = |message: String| MachineReply::Error { message };
+        let guess = parse_guess(line).map_err(error)?;
+        let ordering = self.game.guess(guess).map_err(|e| error(e.to_string()))?;
+
+        let mut replies = vec![MachineReply::Result { guess, result: ordering.into(), attempts_left: self.game.attempts_left() }];
+        let outcome = match self.game.outcome() {
+            Outcome::InProgress => None,
+            Outcome::Won { attempts } => Some(("won", attempts)),
+            Outcome::Lost { attempts } => Some(("lost", attempts)),
+        };
+        if let Some((outcome, attempts)) = outcome {
+            replies.push(MachineReply::GameOver { outcome: outcome.into(), attempts, secret: self.game.secret() });
+        }
+        Ok(replies)
+    }
+
+    pub fn game(&self) -> &Game { &self.game }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::GameConfig;
+
+    #[test]
+    fn parses_guesses_strictly() {
+        assert_eq!(parse_guess("42"), Ok(42));
+        assert_eq!(parse_guess("42\r"), Ok(42));
+        assert!(parse_guess(" 42").is_err());
+        assert!(parse_guess("+42").is_err());
+        assert!(parse_guess("").is_err());
+        assert!(parse_guess("99999999999").is_err());
+    }
+
+    #[test]
+    fn referee_reports_results_and_game_over() {
+        let config = GameConfig::new(1, 10, Some(5)).unwrap();
+        let mut referee = Referee::new(Game::with_config(4, config));
+        let secret = referee.game().secret();
+        assert_eq!(referee.start().to_line(), r#"{"type":"start","min":1,"max":10,"max_attempts":5}"#);
+        assert!(referee.handle("11").is_err());
+
+        let replies = referee.handle(&secret.to_string()).unwrap();
+        assert_eq!(replies[0], MachineReply::Result { guess: secret, result: Feedback::Correct, attempts_left: Some(4) });
+        assert_eq!(replies[1], MachineReply::GameOver { outcome: "won".into(), attempts: 1, secret });
+        assert!(referee.handle("1").is_err());
+    }
+}
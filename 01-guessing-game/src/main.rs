@@ -1,44 +1,370 @@
-use rand::Rng;
 use std::cmp::Ordering;
-use std::io;
+use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand};
 use colored::*;
 
-fn main() {
-    println!("Welcome to the number guessing game!");
-    println!("I've chosen a number between 1 and 100. Can you guess it?");
+use basic_game::analysis;
+use basic_game::bulls::MAX_DIGITS;
+use basic_game::i18n::{self, Lang};
+use basic_game::leaderboard::Leaderboard;
+use basic_game::profile::{Achievement, Profile, STREAK_GOAL};
+use basic_game::session::read_session;
+use basic_game::{t, tn, Difficulty, Game, GameConfig};
 
-    let numero_secreto = rand::thread_rng().gen_range(1..=100);
+mod modes;
+mod screen;
+mod tui;
 
-    loop {
-        println!("Please enter your guess (1-100):");
+/// Guess the secret number before your attempts run out.
+#[derive(Debug, Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    play: PlayArgs,
+    /// Language of the messages: en or es (default: from LC_ALL, LC_MESSAGES or LANG).
+    #[arg(long, global = true)]
+    lang: Option<Lang>,
+}
 
-        let mut entrada = String::new();
-        if let Err(e) = io::stdin().read_line(&mut entrada) {
-            eprintln!("{}", format!("Error reading input: {}", e).red());
-            continue;
-        }
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Show the best scores for each difficulty and per-player averages.
+    Leaderboard,
+    /// Show your achievements, unlocked and still locked, with progress.
+    Profile,
+    /// You pick the number and the computer guesses it.
+    Reverse(ConfigArgs),
+    /// Ulam's game: the "too high/too low" feedback may lie a few times.
+    Liar(LiarArgs),
+    /// Bulls and Cows: crack an N-digit code with distinct digits.
+    Bulls(BullsArgs),
+    /// Today's challenge: the same number for everyone, once per UTC day.
+    Daily(DailyArgs),
+    /// Race the clock: an overall time limit and a deadline for every guess.
+    Blitz(BlitzArgs),
+    /// Continue the game saved when the last one was interrupted.
+    Resume(UiArgs),
+    /// Play back a recorded session with its original timing.
+    Replay(ReplayArgs),
+    /// Review the strategy of a recorded game, guess by guess.
+    Analyze(AnalyzeArgs),
+    /// Pit bot executables against each other on the same seeded games.
+    Tournament(TournamentArgs),
+}
 
-        let entrada = entrada.trim();
-        let adivinanza: u32 = match entrada.parse() {
-            Ok(num) => num,
-            Err(_) => {
-                println!("{}", "Invalid input. Please enter a number.".red());
-                continue;
+#[derive(Debug, Args)]
+struct TournamentArgs {
+    /// Bot command lines, e.g. `./target/debug/bisect-bot` or "python3 bot.py".
+    #[arg(required = true)]
+    bots: Vec<String>,
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Games played by each bot.
+    #[arg(long, default_value_t = 100)]
+    games: u32,
+    /// Seed of the first game; game i uses seed + i (random when omitted).
+    #[arg(long)]
+    seed: Option<u64>,
+    /// A bot that takes longer than this to guess forfeits the game.
+    #[arg(long, default_value_t = 2000)]
+    timeout_ms: u64,
+}
+
+#[derive(Debug, Args)]
+struct ReplayArgs {
+    /// Session file (`.jsonl`) written while playing.
+    file: PathBuf,
+    /// Playback speed multiplier; 2 plays twice as fast.
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+}
+
+#[derive(Debug, Args)]
+struct AnalyzeArgs {
+    /// Session file (`.jsonl`) written while playing.
+    file: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct UiArgs {
+    /// Full-screen interface with a number line (plain output when stdout is not a
+    /// terminal or NO_COLOR is set).
+    #[arg(long)]
+    tui: bool,
+}
+
+#[derive(Debug, Args)]
+struct BlitzArgs {
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Time limit for the whole game, in seconds.
+    #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+    total_secs: u64,
+    /// Time for each guess, in seconds; a guess that comes later wastes the attempt.
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    turn_secs: u64,
+    /// Replay a specific game: the same seed always picks the same secret.
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct DailyArgs {
+    #[command(flatten)]
+    ui: UiArgs,
+    /// Play again even if today's challenge is done (the result is not recorded).
+    #[arg(long)]
+    practice: bool,
+}
+
+#[derive(Debug, Args)]
+struct BullsArgs {
+    /// Length of the secret code.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..=MAX_DIGITS as i64))]
+    digits: u8,
+    /// Number of guesses allowed.
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    max_attempts: u32,
+    /// Let the built-in Knuth solver play the game (codes of up to 5 digits).
+    #[arg(long)]
+    solve: bool,
+    /// Replay a specific game: the same seed always picks the same code.
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct LiarArgs {
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Maximum number of lies per game.
+    #[arg(long, default_value_t = 1)]
+    lies: u32,
+    /// After each answer, show the remaining candidates and a suggested guess.
+    #[arg(long)]
+    hints: bool,
+    /// Replay a specific game (secret and lies).
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct PlayArgs {
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Replay a specific game: the same seed always picks the same secret.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Player name for the leaderboard (asked interactively when omitted).
+    #[arg(long)]
+    name: Option<String>,
+    /// Where to record the session (default: the sessions folder in the data directory).
+    #[arg(long)]
+    record: Option<PathBuf>,
+    #[command(flatten)]
+    ui: UiArgs,
+    /// Play over a strict line protocol for bots: numbers in, JSON out (see src/machine.rs).
+    #[arg(long, conflicts_with_all = ["name", "record", "tui"])]
+    machine: bool,
+}
+
+#[derive(Debug, Args)]
+struct ConfigArgs {
+    /// Preset range and attempt budget: easy, normal, hard or insane.
+    #[arg(long, default_value_t = Difficulty::Normal)]
+    difficulty: Difficulty,
+    /// Lowest possible secret (overrides the preset).
+    #[arg(long)]
+    min: Option<u32>,
+    /// Highest possible secret (overrides the preset).
+    #[arg(long)]
+    max: Option<u32>,
+    /// Number of guesses allowed (overrides the preset).
+    #[arg(long)]
+    max_attempts: Option<u32>,
+}
+
+impl ConfigArgs {
+    /// The validated config; exits with a usage error when the options are inconsistent.
+    fn config(&self) -> GameConfig {
+        match GameConfig::from_preset(self.difficulty, self.min, self.max, self.max_attempts) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("{}", t!("error.config", error = e).red());
+                std::process::exit(2);
             }
-        };
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Some(lang) = cli.lang {
+        i18n::set_lang(lang);
+    }
+    match cli.command {
+        Some(Command::Leaderboard) => show_leaderboard(),
+        Some(Command::Profile) => show_profile(),
+        Some(Command::Reverse(args)) => modes::reverse::play(args.config()),
+        Some(Command::Liar(args)) => modes::liar::play(args),
+        Some(Command::Bulls(args)) => modes::bulls::play(args),
+        Some(Command::Daily(args)) => modes::daily::play(args),
+        Some(Command::Blitz(args)) => modes::blitz::play(args),
+        Some(Command::Resume(args)) => modes::classic::resume(args),
+        Some(Command::Replay(args)) => modes::replay::play(args),
+        Some(Command::Analyze(args)) => analyze(args),
+        Some(Command::Tournament(args)) => modes::bots::run_tournament(args),
+        None if cli.play.machine => modes::bots::play_machine(cli.play),
+        None => modes::classic::play(cli.play),
+    }
+}
+
+fn analyze(args: AnalyzeArgs) {
+    let lineas = match read_session(&args.file) {
+        Ok(lineas) => lineas,
+        Err(e) => {
+            eprintln!("{}", t!("replay.read_error", file = args.file.display(), error = e).red());
+            std::process::exit(1);
+        }
+    };
+    let Some(juego) = analysis::game_from_session(&lineas) else {
+        eprintln!("{}", t!("analysis.no_game", file = args.file.display()).red());
+        std::process::exit(1);
+    };
+    print_review(&juego);
+}
 
-        if !(1..=100).contains(&adivinanza) {
-            println!("{}", "Please enter a number between 1 and 100.".red());
-            continue;
+/// Show how much each guess narrowed the range compared with halving it.
+fn print_review(juego: &Game) {
+    let revision = analysis::review(juego.config(), juego.history());
+    println!();
+    if revision.guesses.is_empty() {
+        println!("{}", t!("analysis.no_guesses"));
+        return;
+    }
+    println!("{}", tn!("analysis.title", revision.optimal_attempts).bold());
+    for (i, g) in revision.guesses.iter().enumerate() {
+        let (low, high) = (g.candidates.start(), g.candidates.end());
+        if g.wasted {
+            println!("{}", t!("analysis.wasted", index = i + 1, guess = g.value, low = low, high = high).red());
+        } else {
+            let percent = (g.eliminated as f64 * 100.0 / g.candidate_count() as f64).round();
+            let quality = (g.quality * 100.0).round();
+            let linea = t!("analysis.guess", index = i + 1, guess = g.value, low = low, high = high, eliminated = g.eliminated, percent = percent, quality = quality);
+            println!("{}", linea);
         }
+    }
+    let percent = (revision.efficiency() * 100.0).round();
+    println!("{}", tn!("analysis.summary", revision.wasted(), percent = percent).bold());
+}
 
-        match adivinanza.cmp(&numero_secreto) {
-            Ordering::Less => println!("{}", "Too low. Try again!".red()),
-            Ordering::Greater => println!("{}", "Too high. Try again!".red()),
-            Ordering::Equal => {
-                println!("{}", format!("Congratulations! You guessed the number: {}", numero_secreto).green());
-                break;
+/// Add a finished game to the profile and announce the achievements it unlocked.
+fn record_achievements(juego: &Game) {
+    match Profile::record(juego) {
+        Ok(nuevos) => {
+            for logro in nuevos {
+                println!("{}", t!("achievement.unlocked", name = achievement_name(logro), description = achievement_description(logro)).green());
             }
         }
+        Err(e) => eprintln!("{}", t!("profile.save_error", error = e).red()),
+    }
+}
+
+fn achievement_name(logro: Achievement) -> String { t!(&format!("achievement.{}.name", logro.key())) }
+
+fn achievement_description(logro: Achievement) -> String {
+    t!(&format!("achievement.{}.description", logro.key()), goal = STREAK_GOAL)
+}
+
+fn show_profile() {
+    let perfil = match Profile::load() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", t!("profile.read_error", error = e).red());
+            std::process::exit(1);
+        }
+    };
+    println!("{}", t!("profile.summary", games = perfil.games, wins = perfil.wins, streak = perfil.streak, best = perfil.best_streak));
+
+    let (desbloqueados, bloqueados): (Vec<_>, Vec<_>) = Achievement::ALL.into_iter().partition(|&a| perfil.is_unlocked(a));
+    println!("{}", t!("profile.unlocked").bold());
+    if desbloqueados.is_empty() {
+        println!("  {}", t!("profile.none").dimmed());
+    }
+    for logro in desbloqueados {
+        let fecha = chrono::DateTime::from_timestamp(perfil.unlocked[&logro] as i64, 0).map(|d| d.date_naive().to_string()).unwrap_or_default();
+        let cuando = t!("profile.unlocked_on", date = fecha);
+        println!("  {} {} — {} ({})", "✓".green(), achievement_name(logro).green(), achievement_description(logro), cuando);
+    }
+    println!("{}", t!("profile.locked").bold());
+    if bloqueados.is_empty() {
+        println!("  {}", t!("profile.none").dimmed());
+    }
+    for logro in bloqueados {
+        let (hecho, meta) = perfil.progress(logro);
+        println!("  {} {} — {}  {}/{}", "✗".dimmed(), achievement_name(logro), achievement_description(logro), hecho, meta);
+    }
+}
+
+fn show_leaderboard() {
+    let board = match Leaderboard::load() {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("{}", t!("leaderboard.read_error", error = e).red());
+            std::process::exit(1);
+        }
+    };
+    if board.records.is_empty() {
+        println!("{}", t!("leaderboard.empty"));
+        return;
+    }
+
+    println!("{}", t!("leaderboard.best").bold());
+    for (difficulty, best) in board.best_by_difficulty(5) {
+        println!("  {}", difficulty.bold());
+        for (rank, r) in best.iter().enumerate() {
+            let score = r.score.map_or(String::new(), |s| format!("  {:>11}", tn!("points", s)));
+            let intentos = tn!("attempts", r.attempts);
+            println!("    {}. {:<16} {:>12}  {:>6.1}s{}", rank + 1, r.player, intentos, r.duration_ms as f64 / 1000.0, score);
+        }
+    }
+
+    println!("{}", t!("leaderboard.players").bold());
+    let cabecera = ["name", "games", "wins", "avg_attempts", "avg_time"].map(|c| t!(&format!("leaderboard.{}", c)));
+    let anchos = column_widths(&cabecera, &[16, 5, 5, 13, 9]);
+    println!("  {}", table_row(&cabecera, &anchos));
+    for p in board.player_stats() {
+        let avg_attempts = p.avg_attempts.map_or("-".to_string(), |a| format!("{:.1}", a));
+        let avg_time = format!("{:.1}s", p.avg_duration_ms as f64 / 1000.0);
+        let fila = [p.player, p.games.to_string(), p.wins.to_string(), avg_attempts, avg_time];
+        println!("  {}", table_row(&fila, &anchos));
+    }
+}
+
+/// Column widths: at least `minimum`, wider when a (translated) header needs it.
+fn column_widths(cabecera: &[String], minimum: &[usize]) -> Vec<usize> {
+    cabecera.iter().zip(minimum).map(|(c, &m)| c.chars().count().max(m)).collect()
+}
+
+/// One table row: the first cell left-aligned, the others right-aligned.
+fn table_row(celdas: &[String], anchos: &[usize]) -> String {
+    celdas
+        .iter()
+        .zip(anchos)
+        .enumerate()
+        .map(|(i, (c, &w))| if i == 0 { format!("{:<w$}", c) } else { format!("{:>w$}", c) })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The library's error and clue types display in English; these give the catalog text.
+
+fn describe_feedback(ordering: Ordering) -> String {
+    match ordering {
+        Ordering::Less => t!("feedback.too_low"),
+        Ordering::Greater => t!("feedback.too_high"),
+        Ordering::Equal => t!("feedback.correct"),
     }
 }
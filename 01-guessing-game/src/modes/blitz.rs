@@ -0,0 +1,136 @@
+//! Blitz mode: an overall time limit and a deadline for every guess.
+
+use std::cmp::Ordering;
+use std::io::{self, IsTerminal, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use colored::*;
+
+use basic_game::blitz::BlitzClock;
+use basic_game::{t, tn, Game, GuessError, Outcome};
+
+use crate::{print_review, record_achievements, BlitzArgs};
+
+/// How often the countdown on the prompt line is redrawn.
+const CLOCK_TICK: Duration = Duration::from_millis(100);
+
+pub fn play(args: BlitzArgs) {
+    let config = args.config.config();
+    let mut juego = match args.seed {
+        Some(seed) => Game::with_config(seed, config),
+        None => Game::from_rng(&mut rand::thread_rng(), config),
+    };
+    println!("{}", t!("blitz.welcome"));
+    println!("{}", t!("play.intro", min = config.min, max = config.max));
+    if let Some(max_attempts) = config.max_attempts {
+        println!("{}", tn!("play.budget", max_attempts));
+    }
+    println!("{}", t!("blitz.rules", total = args.total_secs, turn = args.turn_secs));
+
+    // Only redraw the countdown in place when a person is watching and typing.
+    let en_vivo = io::stdin().is_terminal() && io::stdout().is_terminal();
+    let lineas = read_lines_in_background();
+    let mut reloj = BlitzClock::start(Duration::from_secs(args.total_secs), Duration::from_secs(args.turn_secs), Instant::now());
+
+    while !juego.is_over() {
+        let prompt = match juego.attempts_left() {
+            Some(left) => tn!("play.prompt_left", left, min = config.min, max = config.max),
+            None => t!("play.prompt", min = config.min, max = config.max),
+        };
+        if en_vivo {
+            print!("{} {}", clock_text(&reloj), prompt);
+            let _ = io::stdout().flush();
+        } else {
+            println!("{} {}", clock_text(&reloj), prompt);
+        }
+
+        // Wait for a valid guess or the deadline. Invalid input does not restart the turn.
+        let a_tiempo = loop {
+            let quedan = reloj.turn_left(Instant::now());
+            if quedan.is_zero() {
+                break false;
+            }
+            let entrada = match lineas.recv_timeout(quedan.min(CLOCK_TICK)) {
+                Ok(entrada) => entrada,
+                Err(RecvTimeoutError::Timeout) => {
+                    if en_vivo {
+                        // Rewrite only the clock at the start of the line; what the player
+                        // has typed so far stays where it is.
+                        print!("\x1b7\r{}\x1b8", clock_text(&reloj));
+                        let _ = io::stdout().flush();
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            let Ok(adivinanza) = entrada.trim().parse::<u32>() else {
+                println!("{}", t!("error.not_a_number").red());
+                continue;
+            };
+            match juego.guess(adivinanza) {
+                Ok(Ordering::Less) => println!("{}", t!("play.too_low").red()),
+                Ok(Ordering::Greater) => println!("{}", t!("play.too_high").red()),
+                Ok(Ordering::Equal) | Err(GuessError::GameOver) => {}
+                Err(GuessError::OutOfRange { min, max }) => {
+                    println!("{}", t!("error.out_of_range", min = min, max = max).red());
+                    continue;
+                }
+            }
+            break true;
+        };
+
+        let ahora = Instant::now();
+        if !a_tiempo {
+            if en_vivo {
+                println!();
+            }
+            if reloj.out_of_time(ahora) {
+                juego.resign();
+                break;
+            }
+            println!("{}", t!("blitz.too_slow").red());
+            let _ = juego.waste_attempt();
+        }
+        reloj.next_turn(ahora);
+    }
+
+    let sobran = reloj.total_left(Instant::now());
+    match juego.outcome() {
+        Outcome::Won { attempts } => {
+            println!("{}", tn!("blitz.won", attempts, secret = juego.secret(), left = sobran.as_secs()).green());
+        }
+        Outcome::Lost { .. } if sobran.is_zero() => println!("{}", t!("blitz.out_of_time", secret = juego.secret()).red()),
+        Outcome::Lost { attempts } => println!("{}", tn!("play.lost", attempts, secret = juego.secret()).red()),
+        Outcome::InProgress => {}
+    }
+    if juego.wasted() > 0 {
+        println!("{}", tn!("blitz.wasted", juego.wasted()));
+    }
+    print_review(&juego);
+    record_achievements(&juego);
+}
+
+/// The countdown shown before the prompt, with fixed-width numbers so it can be redrawn in place.
+fn clock_text(reloj: &BlitzClock) -> String {
+    let ahora = Instant::now();
+    let turno = format!("{:4.1}", reloj.turn_left(ahora).as_secs_f64());
+    let total = format!("{:3}", reloj.total_left(ahora).as_millis().div_ceil(1000));
+    t!("blitz.clock", turn = turno, total = total).bold().to_string()
+}
+
+/// Read stdin on its own thread so waiting for a line never blocks a countdown.
+/// The channel closes when input ends.
+fn read_lines_in_background() -> Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for linea in io::stdin().lines() {
+            let Ok(linea) = linea else { break };
+            if tx.send(linea).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
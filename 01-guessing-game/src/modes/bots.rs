@@ -0,0 +1,68 @@
+//! Games for bots: the `--machine` line protocol and the tournament harness.
+
+use std::io;
+use std::time::Duration;
+
+use colored::*;
+
+use basic_game::machine::Referee;
+use basic_game::tournament::{self, Bot};
+use basic_game::{t, tn, Game};
+
+use crate::{column_widths, table_row, PlayArgs, TournamentArgs};
+
+/// Referee one game for a bot on stdin/stdout. No colors, no prompts, nothing recorded.
+pub fn play_machine(args: PlayArgs) {
+    let config = args.config.config();
+    let juego = match args.seed {
+        Some(seed) => Game::with_config(seed, config),
+        None => Game::from_rng(&mut rand::thread_rng(), config),
+    };
+    let mut arbitro = Referee::new(juego);
+    println!("{}", arbitro.start().to_line());
+
+    for linea in io::stdin().lines() {
+        let Ok(linea) = linea else { std::process::exit(1) };
+        match arbitro.handle(&linea) {
+            Ok(respuestas) => respuestas.iter().for_each(|r| println!("{}", r.to_line())),
+            Err(error) => {
+                println!("{}", error.to_line());
+                std::process::exit(1);
+            }
+        }
+        if arbitro.game().is_over() {
+            return;
+        }
+    }
+    // Input ended before the game did.
+    std::process::exit(1);
+}
+
+pub fn run_tournament(args: TournamentArgs) {
+    let config = args.config.config();
+    let bots: Vec<Bot> = args.bots.iter().filter_map(|b| Bot::parse(b)).collect();
+    if bots.is_empty() {
+        eprintln!("{}", t!("error.config", error = t!("tournament.no_bots")).red());
+        std::process::exit(2);
+    }
+    let seed = args.seed.unwrap_or_else(rand::random);
+    println!("{}", t!("tournament.intro", games = args.games, min = config.min, max = config.max, seed = seed, ms = args.timeout_ms));
+
+    let tabla = tournament::run(&bots, config, args.games, seed, Duration::from_millis(args.timeout_ms));
+
+    let cabecera = ["bot", "games", "wins", "forfeits", "mean", "worst"].map(|c| t!(&format!("tournament.{}", c)));
+    let ancho = tabla.iter().map(|t| t.name.chars().count()).max().unwrap_or(0);
+    let anchos = column_widths(&cabecera, &[ancho, 5, 5, 8, 6, 6]);
+    println!("{}", format!("  {}", table_row(&cabecera, &anchos)).bold());
+    for t in &tabla {
+        let mean = t.mean_attempts.map_or("-".to_string(), |m| format!("{:.2}", m));
+        let worst = t.worst_attempts.map_or("-".to_string(), |w| w.to_string());
+        let fila = [t.name.clone(), t.games.to_string(), t.wins.to_string(), t.forfeits.to_string(), mean, worst];
+        println!("  {}", table_row(&fila, &anchos));
+    }
+    for t in &tabla {
+        if let Some(reason) = &t.first_forfeit {
+            println!("{}", tn!("tournament.forfeited", t.forfeits, bot = t.name, reason = reason).red());
+        }
+    }
+}
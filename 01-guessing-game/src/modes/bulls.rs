@@ -0,0 +1,95 @@
+//! Bulls and Cows: crack an N-digit code, or watch the Knuth solver do it.
+
+use std::io;
+
+use clap::error::ErrorKind;
+use clap::CommandFactory;
+use colored::*;
+
+use basic_game::bulls::{BullsGame, Code, CodeError, DigitHint, KnuthSolver, Score, MAX_SOLVER_DIGITS};
+use basic_game::{t, tn, Outcome};
+
+use crate::{BullsArgs, Cli};
+
+pub fn play(args: BullsArgs) {
+    let largo = args.digits as usize;
+    if args.solve && largo > MAX_SOLVER_DIGITS {
+        Cli::command().error(ErrorKind::ArgumentConflict, t!("bulls.solver_digits", max = MAX_SOLVER_DIGITS)).exit();
+    }
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut juego = BullsGame::new(seed, largo, Some(args.max_attempts));
+    let mut solver = args.solve.then(|| KnuthSolver::new(largo));
+
+    println!("{}", t!("bulls.welcome"));
+    println!("{}", tn!("bulls.intro", args.max_attempts, digits = largo));
+    println!("{}", t!("bulls.legend", green = t!("bulls.green").green(), yellow = t!("bulls.yellow").yellow()));
+
+    while !juego.is_over() {
+        let codigo = match solver.as_ref() {
+            Some(solver) => {
+                let Some(codigo) = solver.next_guess() else { break };
+                println!("{}", tn!("bulls.solver", solver.candidates().len(), code = codigo));
+                codigo
+            }
+            None => {
+                println!("{}", tn!("bulls.prompt", juego.attempts_left().unwrap_or(0), digits = largo));
+                let mut entrada = String::new();
+                match io::stdin().read_line(&mut entrada) {
+                    Ok(0) => return,
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("{}", t!("error.read_input", error = e).red());
+                        return;
+                    }
+                }
+                match Code::parse(&entrada, largo) {
+                    Ok(codigo) => codigo,
+                    Err(e) => {
+                        println!("{}", t!("bulls.invalid", error = describe_code_error(&e)).red());
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let pistas = juego.digit_hints(&codigo);
+        let Some(puntaje) = juego.guess(codigo.clone()) else { break };
+        println!("  {}  {}", colored_code(&codigo, &pistas), describe_score(puntaje));
+        if let Some(solver) = solver.as_mut() {
+            solver.record(&codigo, puntaje);
+        }
+    }
+
+    match juego.outcome() {
+        Outcome::Won { attempts } => {
+            println!("{}", tn!("bulls.won", attempts, code = juego.secret()).green());
+        }
+        _ => println!("{}", t!("bulls.lost", code = juego.secret()).red()),
+    }
+}
+
+fn colored_code(codigo: &Code, pistas: &[DigitHint]) -> String {
+    codigo
+        .digits()
+        .iter()
+        .zip(pistas)
+        .map(|(d, pista)| {
+            let d = d.to_string();
+            match pista {
+                DigitHint::Bull => d.green().bold().to_string(),
+                DigitHint::Cow => d.yellow().to_string(),
+                DigitHint::Miss => d.dimmed().to_string(),
+            }
+        })
+        .collect()
+}
+
+fn describe_score(puntaje: Score) -> String { format!("{}, {}", tn!("bulls.bulls", puntaje.bulls), tn!("bulls.cows", puntaje.cows)) }
+
+fn describe_code_error(e: &CodeError) -> String {
+    match e {
+        CodeError::WrongLength { expected, got } => t!("code.wrong_length", expected = expected, got = got),
+        CodeError::NotADigit(c) => t!("code.not_a_digit", char = c),
+        CodeError::DuplicateDigit(d) => t!("code.duplicate", digit = d),
+    }
+}
@@ -0,0 +1,323 @@
+//! The classic game, with hints, session recording and save/resume on interrupt.
+
+use std::cmp::Ordering;
+use std::io;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use colored::*;
+
+use basic_game::hints::{Clue, Hint, HintBank, HintError, HintParseError, MISS_PENALTY, START_SCORE};
+use basic_game::leaderboard::{GameRecord, Leaderboard};
+use basic_game::session::{SavedGame, SessionEvent, SessionRecorder, Tone};
+use basic_game::{t, tn, Game, GuessError, Outcome};
+
+use crate::screen::Screen;
+use crate::{describe_feedback, print_review, record_achievements, tui, PlayArgs, UiArgs};
+
+pub fn play(args: PlayArgs) {
+    let config = args.config.config();
+
+    let mut juego = match args.seed {
+        Some(seed) => Game::with_config(seed, config),
+        None => Game::from_rng(&mut rand::thread_rng(), config),
+    };
+
+    println!("{}", t!("play.welcome"));
+    if let Ok(Some(_)) = SavedGame::load() {
+        println!("{}", t!("play.unfinished").cyan());
+    }
+    let jugador = args.name.unwrap_or_else(ask_player_name);
+
+    let recorder = match &args.record {
+        Some(path) => SessionRecorder::open(path, 0),
+        None => SessionRecorder::create_default(juego.seed()),
+    };
+    let mut screen = Screen::new(recorder).with_tui(args.ui.tui);
+    screen.record(SessionEvent::Start { player: jugador.clone(), seed: juego.seed(), config, resumed: false });
+    screen.say(Tone::Info, t!("play.intro", min = config.min, max = config.max));
+    if let Some(max_attempts) = config.max_attempts {
+        screen.say(Tone::Info, tn!("play.budget", max_attempts));
+    }
+
+    run_game(&jugador, &mut juego, HintBank::default(), &mut screen, 0);
+}
+
+pub fn resume(args: UiArgs) {
+    let saved = match SavedGame::load() {
+        Ok(Some(saved)) => saved,
+        Ok(None) => {
+            println!("{}", t!("resume.none"));
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", t!("resume.read_error", error = e).red());
+            std::process::exit(1);
+        }
+    };
+    let mut juego = match saved.restore() {
+        Ok(juego) => juego,
+        Err(e) => {
+            eprintln!("{}", t!("resume.corrupt", error = describe_guess_error(e)).red());
+            let _ = SavedGame::clear();
+            std::process::exit(1);
+        }
+    };
+
+    let recorder = match &saved.session {
+        Some(path) => SessionRecorder::resume(path),
+        None => SessionRecorder::create_default(saved.seed),
+    };
+    let mut screen = Screen::new(recorder).with_tui(args.tui);
+    let config = juego.config();
+    screen.record(SessionEvent::Start { player: saved.player.clone(), seed: saved.seed, config, resumed: true });
+    screen.say(Tone::Info, t!("resume.welcome", player = saved.player, min = config.min, max = config.max));
+    for (i, g) in juego.history().iter().enumerate() {
+        screen.say(Tone::Info, format!("  #{}: {} -> {}", i + 1, g.value, describe_feedback(g.ordering)));
+    }
+
+    let pistas = HintBank::from_used(saved.hints.clone());
+    if !pistas.used().is_empty() {
+        let compradas = pistas.used().iter().map(|h| h.command()).collect::<Vec<_>>().join(", ");
+        screen.say(Tone::Hint, t!("resume.hints", hints = compradas));
+    }
+    run_game(&saved.player, &mut juego, pistas, &mut screen, saved.elapsed_ms);
+}
+
+/// Play until the game ends, then record the result. If input ends first (or on Ctrl+C)
+/// the game is saved for `resume` instead. `previo_ms` is play time from before a resume.
+fn run_game(jugador: &str, juego: &mut Game, mut pistas: HintBank, screen: &mut Screen, previo_ms: u64) {
+    let sesion = screen.path();
+    let inicio = Instant::now();
+    let checkpoint = |juego: &Game, pistas: &HintBank| Checkpoint {
+        saved: SavedGame::from_game(jugador, juego, pistas.used(), previo_ms, sesion.clone()),
+        since: inicio,
+    };
+    *CHECKPOINT.lock().unwrap_or_else(PoisonError::into_inner) = Some(checkpoint(juego, &pistas));
+    save_on_interrupt();
+
+    let terminado = guess_loop(juego, &mut pistas, screen, |juego, pistas| {
+        *CHECKPOINT.lock().unwrap_or_else(PoisonError::into_inner) = (!juego.is_over()).then(|| checkpoint(juego, pistas));
+    });
+    let pendiente = CHECKPOINT.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+    if !terminado {
+        let Some(pendiente) = pendiente else { return };
+        let saved = pendiente.snapshot();
+        match saved.save() {
+            Ok(()) => {
+                screen.record(SessionEvent::End { outcome: "saved".into(), attempts: juego.attempts() });
+                println!("{}", t!("save.done").cyan());
+            }
+            Err(e) => eprintln!("{}", t!("save.error", error = e).red()),
+        }
+        return;
+    }
+
+    let outcome = if matches!(juego.outcome(), Outcome::Won { .. }) { "won" } else { "lost" };
+    screen.record(SessionEvent::End { outcome: outcome.into(), attempts: juego.attempts() });
+    print_review(juego);
+    if let Err(e) = SavedGame::clear() {
+        eprintln!("{}", t!("save.clear_error", error = e).red());
+    }
+    let duracion = Duration::from_millis(previo_ms) + inicio.elapsed();
+    if let Some(mut record) = GameRecord::from_game(jugador, juego, duracion) {
+        record.score = Some(pistas.score(juego));
+        if let Err(e) = Leaderboard::record(record) {
+            eprintln!("{}", t!("error.save_result", error = e).red());
+        }
+    }
+    record_achievements(juego);
+}
+
+/// The game in progress, kept up to date after every guess or hint so Ctrl+C can save it.
+static CHECKPOINT: Mutex<Option<Checkpoint>> = Mutex::new(None);
+
+struct Checkpoint {
+    saved: SavedGame,
+    since: Instant,
+}
+
+impl Checkpoint {
+    fn snapshot(self) -> SavedGame {
+        let elapsed_ms = self.saved.elapsed_ms + self.since.elapsed().as_millis() as u64;
+        SavedGame { elapsed_ms, ..self.saved }
+    }
+}
+
+fn save_on_interrupt() {
+    let installed = ctrlc::set_handler(|| {
+        if let Some(pendiente) = CHECKPOINT.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            let saved = pendiente.snapshot();
+            match saved.save() {
+                Ok(()) => {
+                    if let Some(path) = &saved.session
+                        && let Ok(mut recorder) = SessionRecorder::resume(path)
+                    {
+                        let _ = recorder.record(SessionEvent::End { outcome: "saved".into(), attempts: saved.guesses.len() });
+                    }
+                    println!("\n{}", t!("save.done").cyan());
+                }
+                Err(e) => eprintln!("\n{}", t!("save.error", error = e).red()),
+            }
+        }
+        std::process::exit(130);
+    });
+    if let Err(e) = installed {
+        eprintln!("{}", t!("save.no_handler", error = e).red());
+    }
+}
+
+/// Prompt for guesses until the game is over, printing feedback and the final result.
+/// Lines starting with `:` are commands such as `:hint parity`. `on_change` runs after
+/// every accepted guess or bought hint. Returns `false` if input ran out first.
+pub fn guess_loop(juego: &mut Game, pistas: &mut HintBank, screen: &mut Screen, mut on_change: impl FnMut(&Game, &HintBank)) -> bool {
+    screen.say(Tone::Hint, t!("play.score_intro", score = pistas.score(juego), penalty = MISS_PENALTY));
+    if screen.tui {
+        match tui::guess_loop(juego, pistas, screen, &mut on_change) {
+            Ok(terminado) => return terminado,
+            Err(e) => {
+                // Keep playing the same game in plain mode.
+                screen.tui = false;
+                eprintln!("{}", t!("tui.failed", error = e).red());
+            }
+        }
+    }
+
+    let config = juego.config();
+    while !juego.is_over() {
+        match juego.attempts_left() {
+            Some(left) => screen.prompt(tn!("play.prompt_left", left, min = config.min, max = config.max)),
+            None => screen.prompt(t!("play.prompt", min = config.min, max = config.max)),
+        }
+        let Some(entrada) = screen.read_line() else { return false };
+        handle_input(&entrada, juego, pistas, screen, &mut on_change);
+    }
+    announce_result(juego, pistas, screen);
+    true
+}
+
+/// Apply one line typed at the guess prompt: a guess or a `:command`.
+pub fn handle_input(entrada: &str, juego: &mut Game, pistas: &mut HintBank, screen: &mut Screen, on_change: &mut dyn FnMut(&Game, &HintBank)) {
+    if let Some(comando) = entrada.strip_prefix(':') {
+        if run_command(comando, juego, pistas, screen) {
+            on_change(juego, pistas);
+        }
+        return;
+    }
+    let adivinanza: u32 = match entrada.parse() {
+        Ok(num) => num,
+        Err(_) => {
+            screen.say(Tone::Error, t!("error.not_a_number"));
+            return;
+        }
+    };
+
+    match juego.guess(adivinanza) {
+        Ok(Ordering::Less) => screen.say(Tone::Error, t!("play.too_low")),
+        Ok(Ordering::Greater) => screen.say(Tone::Error, t!("play.too_high")),
+        Ok(Ordering::Equal) => {}
+        Err(GuessError::OutOfRange { min, max }) => {
+            screen.say(Tone::Error, t!("error.out_of_range", min = min, max = max));
+            return;
+        }
+        Err(GuessError::GameOver) => return,
+    }
+    on_change(juego, pistas);
+}
+
+pub fn announce_result(juego: &Game, pistas: &HintBank, screen: &mut Screen) {
+    let tono = match juego.outcome() {
+        Outcome::InProgress => return,
+        Outcome::Won { attempts } => {
+            screen.say(Tone::Success, tn!("play.won", attempts, secret = juego.secret()));
+            Tone::Success
+        }
+        Outcome::Lost { attempts } => {
+            screen.say(Tone::Error, tn!("play.lost", attempts, secret = juego.secret()));
+            Tone::Error
+        }
+    };
+    let usadas = pistas.used().len();
+    screen.say(tono, tn!("play.final_score", usadas, score = pistas.score(juego), max = START_SCORE, spent = pistas.spent()));
+}
+
+/// Run a `:command` typed at the guess prompt. Returns `true` when a hint was bought.
+fn run_command(comando: &str, juego: &Game, pistas: &mut HintBank, screen: &mut Screen) -> bool {
+    let comando = comando.trim();
+    let (nombre, resto) = comando.split_once(char::is_whitespace).unwrap_or((comando, ""));
+    if nombre != "hint" {
+        screen.say(Tone::Error, t!("hint.unknown_command", command = nombre));
+        return false;
+    }
+    if resto.trim().is_empty() {
+        screen.say(Tone::Hint, t!("hint.list", score = pistas.score(juego)));
+        for pista in Hint::KINDS {
+            screen.say(Tone::Hint, format!("  :hint {:<10} {:>11}", pista.command(), tn!("points", pista.cost())));
+        }
+        return false;
+    }
+
+    let pista: Hint = match resto.parse() {
+        Ok(pista) => pista,
+        Err(e) => {
+            screen.say(Tone::Error, t!("hint.invalid", error = describe_hint_parse_error(&e)));
+            return false;
+        }
+    };
+    match pistas.request(pista, juego) {
+        Ok(clue) => {
+            screen.say(Tone::Hint, t!("hint.bought", cost = pista.cost(), clue = describe_clue(clue), score = pistas.score(juego)));
+            true
+        }
+        Err(e) => {
+            screen.say(Tone::Error, t!("hint.refused", error = describe_hint_error(&e)));
+            false
+        }
+    }
+}
+
+fn ask_player_name() -> String {
+    println!("{}", t!("name.ask"));
+    let mut nombre = String::new();
+    // A failed read just means an anonymous game; it must not stop the player from playing.
+    let _ = io::stdin().read_line(&mut nombre);
+    let nombre = nombre.trim();
+    if nombre.is_empty() { "anonymous".to_string() } else { nombre.to_string() }
+}
+
+fn describe_guess_error(e: GuessError) -> String {
+    match e {
+        GuessError::OutOfRange { min, max } => t!("error.out_of_range", min = min, max = max),
+        GuessError::GameOver => t!("hint.error.game_over"),
+    }
+}
+
+fn describe_hint_parse_error(e: &HintParseError) -> String {
+    match e {
+        HintParseError::Unknown(hint) => t!("hint.parse.unknown", hint = hint),
+        HintParseError::BadDivisor => t!("hint.parse.divisor"),
+        HintParseError::Trailing(word) => t!("hint.parse.trailing", word = word),
+    }
+}
+
+fn describe_hint_error(e: &HintError) -> String {
+    match e {
+        HintError::Unaffordable { cost, score } => t!("hint.error.unaffordable", cost = cost, score = score),
+        HintError::NeedsTwoGuesses => t!("hint.error.two_guesses"),
+        HintError::GameOver => t!("hint.error.game_over"),
+    }
+}
+
+fn describe_clue(clue: Clue) -> String {
+    match clue {
+        Clue::Warmer => t!("clue.warmer"),
+        Clue::Colder => t!("clue.colder"),
+        Clue::SameDistance => t!("clue.same"),
+        Clue::Even => t!("clue.even"),
+        Clue::Odd => t!("clue.odd"),
+        Clue::Divisible { divisor, divisible: true } => t!("clue.divisible", divisor = divisor),
+        Clue::Divisible { divisor, divisible: false } => t!("clue.not_divisible", divisor = divisor),
+        Clue::DigitSum(sum) => t!("clue.digit_sum", sum = sum),
+    }
+}
@@ -0,0 +1,51 @@
+//! Today's challenge: the same number for everyone, once per UTC day.
+
+use colored::*;
+
+use basic_game::daily::{self, DailyLog};
+use basic_game::hints::HintBank;
+use basic_game::{t, tn};
+
+use crate::modes::classic::guess_loop;
+use crate::screen::Screen;
+use crate::{print_review, record_achievements, DailyArgs};
+
+pub fn play(args: DailyArgs) {
+    let hoy = daily::today();
+    if !args.practice {
+        match DailyLog::mark_started(hoy) {
+            Ok(true) => {}
+            Ok(false) => {
+                println!("{}", t!("daily.already", date = hoy).red());
+                return;
+            }
+            Err(e) => {
+                eprintln!("{}", t!("daily.read_error", error = e).red());
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut juego = daily::game_for(hoy);
+    let config = juego.config();
+    println!("{}", t!(if args.practice { "daily.welcome_practice" } else { "daily.welcome" }, date = hoy));
+    println!("{}", t!("daily.intro", min = config.min, max = config.max));
+    if let Some(max_attempts) = config.max_attempts {
+        println!("{}", tn!("play.budget", max_attempts));
+    }
+
+    guess_loop(&mut juego, &mut HintBank::default(), &mut Screen::plain().with_tui(args.ui.tui), |_, _| {});
+    if !juego.is_over() {
+        return;
+    }
+
+    print_review(&juego);
+    println!();
+    println!("{}", daily::share_string(hoy, &juego));
+    if !args.practice {
+        if let Err(e) = DailyLog::mark_finished(hoy, &juego) {
+            eprintln!("{}", t!("error.save_result", error = e).red());
+        }
+        record_achievements(&juego);
+    }
+}
@@ -0,0 +1,85 @@
+//! Ulam's liar game: the "too high/too low" feedback may lie a few times.
+
+use std::cmp::Ordering;
+use std::io;
+
+use colored::*;
+
+use basic_game::liar::{LiarGame, LieTracker};
+use basic_game::{t, tn, GuessError, Outcome};
+
+use crate::{describe_feedback, LiarArgs};
+
+pub fn play(args: LiarArgs) {
+    let mut config = args.config.config();
+    if args.config.max_attempts.is_none() {
+        // Every lie costs the player some guesses, so stretch the preset budget.
+        config.max_attempts = config.max_attempts.map(|a| a.saturating_add(args.lies.saturating_mul(3)));
+    }
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut juego = LiarGame::new(seed, config, args.lies);
+    let mut pistas = LieTracker::new(config, args.lies);
+
+    println!("{}", t!("liar.welcome"));
+    println!("{}", tn!("liar.intro", args.lies, min = config.min, max = config.max));
+    if let Some(max_attempts) = config.max_attempts {
+        println!("{}", tn!("play.budget", max_attempts));
+    }
+
+    while !juego.is_over() {
+        if args.hints {
+            let candidatos = pistas.candidate_count();
+            if let Some(sugerencia) = pistas.suggest() {
+                println!("{}", tn!("liar.hint", candidatos, guess = sugerencia).cyan());
+            }
+        }
+        match juego.attempts_left() {
+            Some(left) => println!("{}", tn!("play.prompt_left", left, min = config.min, max = config.max)),
+            None => println!("{}", t!("play.prompt", min = config.min, max = config.max)),
+        }
+
+        let mut entrada = String::new();
+        match io::stdin().read_line(&mut entrada) {
+            Ok(0) => return,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("{}", t!("error.read_input", error = e).red());
+                return;
+            }
+        }
+        let adivinanza: u32 = match entrada.trim().parse() {
+            Ok(num) => num,
+            Err(_) => {
+                println!("{}", t!("error.not_a_number").red());
+                continue;
+            }
+        };
+
+        match juego.guess(adivinanza) {
+            Ok(respuesta) => {
+                pistas.record(adivinanza, respuesta);
+                match respuesta {
+                    Ordering::Less => println!("{}", t!("play.too_low").red()),
+                    Ordering::Greater => println!("{}", t!("play.too_high").red()),
+                    Ordering::Equal => {}
+                }
+            }
+            Err(GuessError::OutOfRange { min, max }) => {
+                println!("{}", t!("error.out_of_range", min = min, max = max).red());
+            }
+            Err(GuessError::GameOver) => {}
+        }
+    }
+
+    match juego.outcome() {
+        Outcome::Won { attempts } => {
+            println!("{}", tn!("liar.won", attempts, secret = juego.secret()).green());
+        }
+        _ => println!("{}", t!("liar.lost", secret = juego.secret()).red()),
+    }
+    println!("{}", tn!("liar.lies", juego.lies_told()));
+    for (i, a) in juego.answers().iter().enumerate() {
+        let line = format!("  #{}: {} -> {}", i + 1, a.value, describe_feedback(a.reported));
+        if a.lie { println!("{}", format!("{}  {}", line, t!("liar.lie")).yellow()) } else { println!("{}", line) }
+    }
+}
@@ -0,0 +1,10 @@
+//! Game loops for the CLI, one module per way to play.
+
+pub mod blitz;
+pub mod bots;
+pub mod bulls;
+pub mod classic;
+pub mod daily;
+pub mod liar;
+pub mod replay;
+pub mod reverse;
@@ -0,0 +1,50 @@
+//! Play back a recorded session with its original timing.
+
+use std::thread;
+use std::time::Duration;
+
+use colored::*;
+
+use basic_game::session::{read_session, SessionEvent};
+use basic_game::{t, tn};
+
+use crate::screen::paint;
+use crate::ReplayArgs;
+
+pub fn play(args: ReplayArgs) {
+    if !args.speed.is_finite() || args.speed <= 0.0 {
+        eprintln!("{}", t!("error.config", error = t!("replay.speed")).red());
+        std::process::exit(2);
+    }
+    let lineas = match read_session(&args.file) {
+        Ok(lineas) => lineas,
+        Err(e) => {
+            eprintln!("{}", t!("replay.read_error", file = args.file.display(), error = e).red());
+            std::process::exit(1);
+        }
+    };
+
+    let mut anterior = 0;
+    for linea in lineas {
+        // Long pauses (a player thinking, or a saved game) are capped so replays stay watchable.
+        let pausa = Duration::from_millis(linea.at_ms.saturating_sub(anterior)).min(Duration::from_secs(3));
+        thread::sleep(pausa.div_f64(args.speed));
+        anterior = linea.at_ms;
+        match linea.event {
+            SessionEvent::Start { player, seed, config, resumed } => {
+                let clave = if resumed { "replay.resumed" } else { "replay.started" };
+                println!("{}", t!(clave, player = player, min = config.min, max = config.max, seed = seed).dimmed());
+            }
+            SessionEvent::Prompt { text } => println!("{}", text),
+            SessionEvent::Input { text } => println!("{}", text.bold()),
+            SessionEvent::Output { text, tone } => println!("{}", paint(tone, &text)),
+            SessionEvent::End { outcome, attempts } => {
+                let outcome = match outcome.as_str() {
+                    "won" | "lost" | "saved" => t!(&format!("outcome.{}", outcome)),
+                    _ => outcome,
+                };
+                println!("{}", tn!("replay.end", attempts, outcome = outcome).dimmed());
+            }
+        }
+    }
+}
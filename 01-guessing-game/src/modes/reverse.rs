@@ -0,0 +1,61 @@
+//! Reverse mode: the player picks the number and the computer binary-searches it.
+
+use std::cmp::Ordering;
+use std::io;
+
+use colored::*;
+
+use basic_game::reverse::{parse_answer, Contradiction, ReverseSolver};
+use basic_game::{t, tn, GameConfig};
+
+pub fn play(config: GameConfig) {
+    println!("{}", t!("reverse.intro", min = config.min, max = config.max));
+    println!("{}", t!("reverse.how"));
+
+    let mut solver = ReverseSolver::new(config);
+    while let Some(intento) = solver.next_guess() {
+        println!("{}", t!("reverse.ask", guess = intento));
+
+        let mut entrada = String::new();
+        match io::stdin().read_line(&mut entrada) {
+            Ok(0) => return,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("{}", t!("error.read_input", error = e).red());
+                return;
+            }
+        }
+
+        let Some(respuesta) = parse_answer(&entrada) else {
+            println!("{}", t!("reverse.bad_answer").red());
+            continue;
+        };
+
+        match solver.answer(respuesta) {
+            Ok(true) => {
+                println!("{}", tn!("reverse.got_it", solver.attempts(), guess = intento).green());
+            }
+            Ok(false) => {}
+            Err(contradiction) => {
+                println!("{}", t!("reverse.cheated", reason = describe_contradiction(&contradiction)).red());
+                for (i, g) in solver.answers().iter().enumerate() {
+                    let said = match g.ordering {
+                        Ordering::Less => t!("reverse.said.higher"),
+                        Ordering::Greater => t!("reverse.said.lower"),
+                        Ordering::Equal => t!("reverse.said.correct"),
+                    };
+                    let marker = if i + 1 == contradiction.answer || Some(i + 1) == contradiction.conflicts_with { " <-" } else { "" };
+                    println!("  #{}: {} -> {}{}", i + 1, g.value, said, marker);
+                }
+                return;
+            }
+        }
+    }
+}
+
+fn describe_contradiction(c: &Contradiction) -> String {
+    match c.conflicts_with {
+        Some(other) => t!("reverse.conflict", answer = c.answer, other = other),
+        None => t!("reverse.outside", answer = c.answer),
+    }
+}
@@ -0,0 +1,176 @@
+//! The local player profile: lifetime counts, the win streak and unlocked achievements.
+//!
+//! Every finished game is added to `profile.json` in the data directory under the file
+//! lock, and the achievements it unlocked are returned so the CLI can announce them.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis;
+use crate::game::{Game, Outcome};
+use crate::storage;
+
+const FILE_NAME: &str = "profile.json";
+/// Wins in a row needed for `Achievement::WinStreak`.
+pub const STREAK_GOAL: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    /// Win with the first guess.
+    FirstTry,
+    /// Win with the last attempt of a limited budget.
+    LastAttempt,
+    /// Win `STREAK_GOAL` games in a row.
+    WinStreak,
+    /// Win in more than one guess, every guess splitting the candidates as evenly as possible.
+    PerfectSearch,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] =
+        [Achievement::FirstTry, Achievement::LastAttempt, Achievement::WinStreak, Achievement::PerfectSearch];
+
+    /// Stable identifier, as stored in the profile.
+    pub fn key(self) -> &'static str {
+        match self {
+            Achievement::FirstTry => "first_try",
+            Achievement::LastAttempt => "last_attempt",
+            Achievement::WinStreak => "win_streak",
+            Achievement::PerfectSearch => "perfect_search",
+        }
+    }
+
+    /// Whether `game` (already counted in `profile`) earns this achievement.
+    fn earned_by(self, game: &Game, profile: &Profile) -> bool {
+        let Outcome::Won { attempts } = game.outcome() else { return false };
+        match self {
+            Achievement::FirstTry => attempts == 1,
+            Achievement::LastAttempt => game.attempts_left() == Some(0),
+            Achievement::WinStreak => profile.streak >= STREAK_GOAL,
+            Achievement::PerfectSearch => {
+                attempts > 1 && game.wasted() == 0 && analysis::review(game.config(), game.history()).is_perfect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub games: u32,
+    pub wins: u32,
+    /// Consecutive wins up to the last game.
+    pub streak: u32,
+    pub best_streak: u32,
+    /// When each achievement was unlocked, in seconds since the Unix epoch.
+    pub unlocked: BTreeMap<Achievement, u64>,
+}
+
+impl Profile {
+    pub fn path() -> io::Result<PathBuf> { Ok(storage::data_dir()?.join(FILE_NAME)) }
+
+    pub fn load() -> io::Result<Self> { storage::read_json(&Self::path()?) }
+
+    /// Add a finished game to the stored profile and return the achievements it unlocked.
+    pub fn record(game: &Game) -> io::Result<Vec<Achievement>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        storage::update_json(&Self::path()?, |profile: &mut Profile| profile.add_game(game, now))
+    }
+
+    /// Count a finished game played at `now` and return the newly unlocked achievements.
+    /// Games still in progress are ignored.
+    pub fn add_game(&mut self, game: &Game, now: u64) -> Vec<Achievement> {
+        match game.outcome() {
+            Outcome::InProgress => return Vec::new(),
+            Outcome::Won { .. } => {
+                self.wins += 1;
+                self.streak += 1;
+                self.best_streak = self.best_streak.max(self.streak);
+            }
+            Outcome::Lost { .. } => self.streak = 0,
+        }
+        self.games += 1;
+
+        let nuevos: Vec<Achievement> =
+            Achievement::ALL.into_iter().filter(|&a| !self.is_unlocked(a) && a.earned_by(game, self)).collect();
+        for &a in &nuevos {
+            self.unlocked.insert(a, now);
+        }
+        nuevos
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool { self.unlocked.contains_key(&achievement) }
+
+    /// Progress towards an achievement as `(done, needed)`.
+    pub fn progress(&self, achievement: Achievement) -> (u32, u32) {
+        let goal = if achievement == Achievement::WinStreak { STREAK_GOAL } else { 1 };
+        if self.is_unlocked(achievement) {
+            return (goal, goal);
+        }
+        match achievement {
+            Achievement::WinStreak => (self.best_streak.min(goal), goal),
+            _ => (0, goal),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::GameConfig;
+
+    fn first_try_win(config: GameConfig) -> Game {
+        let mut game = Game::with_config(5, config);
+        game.guess(game.secret()).unwrap();
+        game
+    }
+
+    #[test]
+    fn unlocks_first_try_last_attempt_and_perfect_search_once() {
+        let config = GameConfig::new(1, 3, Some(2)).unwrap();
+        let mut profile = Profile::default();
+
+        let first = first_try_win(config);
+        assert_eq!(profile.add_game(&first, 10), [Achievement::FirstTry]);
+        assert!(profile.add_game(&first, 20).is_empty());
+
+        // Range 1..=3: guessing 2 first is the perfect split, and with a budget of two the
+        // second guess is also the last attempt.
+        let seed = (0..).find(|&s| Game::with_config(s, config).secret() != 2).unwrap();
+        let mut game = Game::with_config(seed, config);
+        game.guess(2).unwrap();
+        game.guess(game.secret()).unwrap();
+        assert_eq!(profile.add_game(&game, 30), [Achievement::LastAttempt, Achievement::PerfectSearch]);
+        assert_eq!(profile.unlocked[&Achievement::FirstTry], 10);
+        assert_eq!((profile.games, profile.wins, profile.streak), (3, 3, 3));
+    }
+
+    #[test]
+    fn streak_resets_on_a_loss_and_survives_a_round_trip() {
+        let config = GameConfig::new(1, 100, Some(1)).unwrap();
+        let mut profile = Profile::default();
+        let mut lost = Game::with_config(5, config);
+        lost.guess(if lost.secret() == 1 { 2 } else { 1 }).unwrap();
+
+        for _ in 0..STREAK_GOAL - 1 {
+            profile.add_game(&first_try_win(config), 0);
+        }
+        profile.add_game(&lost, 0);
+        assert_eq!((profile.streak, profile.best_streak), (0, STREAK_GOAL - 1));
+        assert_eq!(profile.progress(Achievement::WinStreak), (STREAK_GOAL - 1, STREAK_GOAL));
+
+        for _ in 0..STREAK_GOAL {
+            profile.add_game(&first_try_win(config), 0);
+        }
+        assert!(profile.is_unlocked(Achievement::WinStreak));
+
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(json.contains("\"win_streak\""));
+        assert_eq!(serde_json::from_str::<Profile>(&json).unwrap(), profile);
+    }
+}
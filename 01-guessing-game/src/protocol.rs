@@ -0,0 +1,79 @@
+//! Wire format of `guess-server`: one JSON object per line in each direction.
+//!
+//! Every message carries a `"type"` tag, e.g. `{"type":"guess","value":50}` from a client
+//! or `{"type":"guess","player":"ana","value":50,"result":"too_low"}` from the server.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+/// Messages sent by a client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Must be the first message. Joining with the name of a disconnected player resumes
+    /// that player's score.
+    Join { name: String },
+    Guess { value: u32 },
+    Quit,
+}
+
+/// Feedback for a guess, as seen by every player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feedback {
+    TooLow,
+    TooHigh,
+    Correct,
+}
+
+impl From<Ordering> for Feedback {
+    fn from(ordering: Ordering) -> Self {
+        match ordering {
+            Ordering::Less => Feedback::TooLow,
+            Ordering::Greater => Feedback::TooHigh,
+            Ordering::Equal => Feedback::Correct,
+        }
+    }
+}
+
+/// One line of the ranking table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u32,
+    /// Guesses made, including turns lost to the per-guess timeout.
+    pub guesses: u32,
+    pub timeouts: u32,
+    pub connected: bool,
+}
+
+/// Messages sent by the server. Everything except `welcome` and `error` is broadcast.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome { name: String, reconnected: bool, rounds: u32, min: u32, max: u32, guess_timeout_secs: u64 },
+    PlayerJoined { name: String, reconnected: bool },
+    PlayerLeft { name: String },
+    RoundStarted { round: u32, min: u32, max: u32 },
+    Guess { player: String, value: u32, result: Feedback },
+    /// The player did not guess within the per-guess timeout; the turn counts as a guess.
+    Timeout { player: String },
+    RoundWon { round: u32, winner: String, secret: u32, attempts: u32 },
+    Ranking { standings: Vec<Standing> },
+    GameOver { standings: Vec<Standing> },
+    Error { message: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_tagged_json() {
+        let msg: ClientMessage = serde_json::from_str(r#"{"type":"guess","value":42}"#).unwrap();
+        assert_eq!(msg, ClientMessage::Guess { value: 42 });
+        let out = ServerMessage::Guess { player: "ana".into(), value: 42, result: Feedback::TooLow };
+        assert_eq!(serde_json::to_string(&out).unwrap(), r#"{"type":"guess","player":"ana","value":42,"result":"too_low"}"#);
+    }
+}
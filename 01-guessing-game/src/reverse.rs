@@ -0,0 +1,156 @@
+//! Reverse mode: the player picks the secret and the computer guesses it.
+//!
+//! The solver keeps the interval of secrets that are still consistent with the player's
+//! answers and always guesses its midpoint (binary search). Answers use the same
+//! `Ordering` model as `Game`: each recorded `Guess` says how the guess compared to the
+//! secret. If an answer leaves no possible secret, the player contradicted themselves.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::difficulty::GameConfig;
+use crate::game::Guess;
+
+/// Parse a player's reply to a computer guess into the guess-vs-secret `Ordering`.
+/// "higher" means the secret is higher, so the guess was `Less`.
+pub fn parse_answer(input: &str) -> Option<Ordering> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "h" | "higher" | "+" => Some(Ordering::Less),
+        "l" | "lower" | "-" => Some(Ordering::Greater),
+        "c" | "correct" | "=" => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+/// The player's answers can't all be true. Answer numbers are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contradiction {
+    /// The answer that emptied the interval.
+    pub answer: usize,
+    /// The earlier answer it conflicts with, or `None` when it conflicts with the range itself.
+    pub conflicts_with: Option<usize>,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.conflicts_with {
+            Some(other) => write!(f, "answer #{} contradicts answer #{}", self.answer, other),
+            None => write!(f, "answer #{} puts the secret outside the allowed range", self.answer),
+        }
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+#[derive(Debug, Clone)]
+pub struct ReverseSolver {
+    config: GameConfig,
+    low: u32,
+    high: u32,
+    /// Answer numbers that last raised `low` / lowered `high`.
+    low_set_by: Option<usize>,
+    high_set_by: Option<usize>,
+    answers: Vec<Guess>,
+    solved: bool,
+}
+
+impl ReverseSolver {
+    pub fn new(config: GameConfig) -> Self {
+        Self { config, low: config.min, high: config.max, low_set_by: None, high_set_by: None, answers: Vec::new(), solved: false }
+    }
+
+    /// The next guess: the midpoint of the still-possible interval. `None` once solved.
+    pub fn next_guess(&self) -> Option<u32> {
+        if self.solved {
+            return None;
+        }
+        Some(self.low + (self.high - self.low) / 2)
+    }
+
+    /// Record the player's answer to the current guess.
+    /// Returns `Ok(true)` when the secret has been found.
+    pub fn answer(&mut self, ordering: Ordering) -> Result<bool, Contradiction> {
+        let Some(guess) = self.next_guess() else { return Ok(true) };
+        self.answers.push(Guess { value: guess, ordering });
+        let number = self.answers.len();
+
+        match ordering {
+            Ordering::Equal => {
+                self.solved = true;
+                return Ok(true);
+            }
+            Ordering::Less => {
+                if guess >= self.high {
+                    return Err(Contradiction { answer: number, conflicts_with: self.high_set_by });
+                }
+                self.low = guess + 1;
+                self.low_set_by = Some(number);
+            }
+            Ordering::Greater => {
+                if guess <= self.low {
+                    return Err(Contradiction { answer: number, conflicts_with: self.low_set_by });
+                }
+                self.high = guess - 1;
+                self.high_set_by = Some(number);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Every answer given so far, in order.
+    pub fn answers(&self) -> &[Guess] { &self.answers }
+    pub fn attempts(&self) -> usize { self.answers.len() }
+    pub fn config(&self) -> GameConfig { self.config }
+
+    /// The interval of secrets still consistent with the answers.
+    pub fn remaining(&self) -> (u32, u32) { (self.low, self.high) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(secret: u32, config: GameConfig) -> usize {
+        let mut solver = ReverseSolver::new(config);
+        loop {
+            let guess = solver.next_guess().unwrap();
+            if solver.answer(guess.cmp(&secret)).unwrap() {
+                assert_eq!(guess, secret);
+                return solver.attempts();
+            }
+        }
+    }
+
+    #[test]
+    fn finds_every_secret_within_log2_attempts() {
+        let config = GameConfig::new(1, 100, None).unwrap();
+        for secret in 1..=100 {
+            assert!(play(secret, config) <= 7, "secret {}", secret);
+        }
+    }
+
+    #[test]
+    fn detects_cheating() {
+        let mut solver = ReverseSolver::new(GameConfig::new(1, 100, None).unwrap());
+        assert_eq!(solver.next_guess(), Some(50));
+        solver.answer(Ordering::Less).unwrap(); // "higher": 51..=100
+        assert_eq!(solver.next_guess(), Some(75));
+        solver.answer(Ordering::Greater).unwrap(); // "lower": 51..=74
+        assert_eq!(solver.next_guess(), Some(62));
+        solver.answer(Ordering::Greater).unwrap(); // "lower": 51..=61
+        assert_eq!(solver.next_guess(), Some(56));
+        solver.answer(Ordering::Greater).unwrap(); // "lower": 51..=55
+        solver.answer(Ordering::Greater).unwrap(); // 53 "lower": 51..=52
+        assert_eq!(solver.next_guess(), Some(51));
+        let err = solver.answer(Ordering::Greater).unwrap_err(); // "lower" than 51 contradicts #1
+        assert_eq!(err, Contradiction { answer: 6, conflicts_with: Some(1) });
+    }
+
+    #[test]
+    fn parses_answers() {
+        assert_eq!(parse_answer(" Higher "), Some(Ordering::Less));
+        assert_eq!(parse_answer("l"), Some(Ordering::Greater));
+        assert_eq!(parse_answer("="), Some(Ordering::Equal));
+        assert_eq!(parse_answer("maybe"), None);
+    }
+}
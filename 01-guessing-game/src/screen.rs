@@ -0,0 +1,96 @@
+//! Console output for a game in progress, mirrored into the session recording.
+
+use std::io;
+use std::path::PathBuf;
+
+use colored::*;
+
+use basic_game::session::{SessionEvent, SessionRecorder, Tone};
+use basic_game::t;
+
+use crate::tui;
+
+/// Console output that is also appended to the session recording, when there is one.
+pub struct Screen {
+    recorder: Option<SessionRecorder>,
+    /// Full-screen mode: output is kept for the TUI to draw instead of being printed.
+    pub tui: bool,
+    captured: Vec<(Tone, String)>,
+}
+
+impl Screen {
+    pub fn new(recorder: io::Result<SessionRecorder>) -> Self {
+        match recorder {
+            Ok(recorder) => Self { recorder: Some(recorder), ..Self::plain() },
+            Err(e) => {
+                eprintln!("{}", t!("session.not_recording", error = e).red());
+                Self::plain()
+            }
+        }
+    }
+
+    pub fn plain() -> Self { Self { recorder: None, tui: false, captured: Vec::new() } }
+
+    /// Switch to the full-screen interface when asked for and the terminal supports it.
+    pub fn with_tui(mut self, requested: bool) -> Self {
+        self.tui = requested && tui::supported();
+        self
+    }
+
+    /// Output said since the last call (TUI mode only).
+    pub fn take_captured(&mut self) -> Vec<(Tone, String)> { std::mem::take(&mut self.captured) }
+
+    pub fn path(&self) -> Option<PathBuf> { self.recorder.as_ref().map(|r| r.path().to_path_buf()) }
+
+    /// Record an event. A failed write stops the recording but never the game.
+    pub fn record(&mut self, event: SessionEvent) {
+        if let Some(recorder) = self.recorder.as_mut()
+            && let Err(e) = recorder.record(event)
+        {
+            eprintln!("{}", t!("session.stopped", error = e).red());
+            self.recorder = None;
+        }
+    }
+
+    pub fn say(&mut self, tone: Tone, text: String) {
+        if self.tui {
+            self.captured.push((tone, text.clone()));
+        } else {
+            println!("{}", paint(tone, &text));
+        }
+        self.record(SessionEvent::Output { text, tone });
+    }
+
+    pub fn prompt(&mut self, text: String) {
+        if !self.tui {
+            println!("{}", text);
+        }
+        self.record(SessionEvent::Prompt { text });
+    }
+
+    /// Read one line of input. `None` when stdin is closed or unreadable.
+    pub fn read_line(&mut self) -> Option<String> {
+        let mut entrada = String::new();
+        match io::stdin().read_line(&mut entrada) {
+            Ok(0) => None,
+            Ok(_) => {
+                let entrada = entrada.trim().to_string();
+                self.record(SessionEvent::Input { text: entrada.clone() });
+                Some(entrada)
+            }
+            Err(e) => {
+                eprintln!("{}", t!("error.read_input", error = e).red());
+                None
+            }
+        }
+    }
+}
+
+pub fn paint(tone: Tone, text: &str) -> ColoredString {
+    match tone {
+        Tone::Info => text.normal(),
+        Tone::Error => text.red(),
+        Tone::Success => text.green(),
+        Tone::Hint => text.cyan(),
+    }
+}
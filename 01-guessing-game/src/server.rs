@@ -0,0 +1,329 @@
+//! Multiplayer race over TCP: every connected player hunts the same secret.
+//!
+//! Shared state lives in a `Lobby` behind a mutex; each connection task applies the
+//! client's messages to it and the resulting events are broadcast to all players via a
+//! `tokio::sync::broadcast` channel. Events are sent while the lobby lock is held, so
+//! every player sees them in the same order. A ticker enforces the per-guess timeout.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+use crate::difficulty::GameConfig;
+use crate::game::Game;
+use crate::protocol::{ClientMessage, Feedback, ServerMessage, Standing};
+use crate::t;
+
+const MAX_NAME_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ServerConfig {
+    /// Range of each round's secret. The attempt budget is ignored: rounds are a race.
+    pub game: GameConfig,
+    pub rounds: u32,
+    /// A player who doesn't guess within this time loses a turn.
+    pub guess_timeout: Duration,
+    /// Players needed before the first round starts.
+    pub min_players: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug, Default)]
+struct Player {
+    connected: bool,
+    wins: u32,
+    guesses: u32,
+    round_guesses: u32,
+    timeouts: u32,
+    deadline: Option<Instant>,
+}
+
+struct Lobby {
+    config: ServerConfig,
+    rng: ChaCha8Rng,
+    round: u32,
+    game: Option<Game>,
+    players: BTreeMap<String, Player>,
+    finished: bool,
+}
+
+impl Lobby {
+    fn new(config: ServerConfig) -> Self {
+        Self { config, rng: ChaCha8Rng::seed_from_u64(config.seed), round: 0, game: None, players: BTreeMap::new(), finished: false }
+    }
+
+    fn join(&mut self, name: &str) -> Result<(ServerMessage, Vec<ServerMessage>), String> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_NAME_LEN {
+            return Err(format!("name must be 1 to {} characters", MAX_NAME_LEN));
+        }
+        if self.finished {
+            return Err("the game is over".into());
+        }
+        let reconnected = match self.players.get(name) {
+            Some(p) if p.connected => return Err(format!("name '{}' is already taken", name)),
+            Some(_) => true,
+            None => false,
+        };
+
+        let deadline = self.game.is_some().then(|| Instant::now() + self.config.guess_timeout);
+        let player = self.players.entry(name.to_string()).or_default();
+        player.connected = true;
+        player.deadline = deadline;
+
+        let welcome = ServerMessage::Welcome {
+            name: name.to_string(),
+            reconnected,
+            rounds: self.config.rounds,
+            min: self.config.game.min,
+            max: self.config.game.max,
+            guess_timeout_secs: self.config.guess_timeout.as_secs(),
+        };
+        let mut events = vec![ServerMessage::PlayerJoined { name: name.to_string(), reconnected }];
+        if self.round == 0 && self.connected_count() >= self.config.min_players {
+            events.push(self.start_round());
+        } else if let Some(game) = &self.game {
+            // Late joiners need the current round to know what they are guessing.
+            events.push(ServerMessage::RoundStarted { round: self.round, min: *game.range().start(), max: *game.range().end() });
+        }
+        Ok((welcome, events))
+    }
+
+    fn leave(&mut self, name: &str) -> Vec<ServerMessage> {
+        match self.players.get_mut(name) {
+            Some(p) => {
+                p.connected = false;
+                p.deadline = None;
+                vec![ServerMessage::PlayerLeft { name: name.to_string() }]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn guess(&mut self, name: &str, value: u32) -> Result<Vec<ServerMessage>, String> {
+        let Some(game) = self.game.as_mut() else {
+            return Err("no round in progress".into());
+        };
+        let ordering = game.guess(value).map_err(|e| e.to_string())?;
+        let player = self.players.get_mut(name).ok_or("unknown player")?;
+        player.guesses += 1;
+        player.round_guesses += 1;
+        player.deadline = Some(Instant::now() + self.config.guess_timeout);
+
+        let result = Feedback::from(ordering);
+        let mut events = vec![ServerMessage::Guess { player: name.to_string(), value, result }];
+        if result == Feedback::Correct {
+            player.wins += 1;
+            let attempts = player.round_guesses;
+            let secret = game.secret();
+            events.push(ServerMessage::RoundWon { round: self.round, winner: name.to_string(), secret, attempts });
+            events.push(ServerMessage::Ranking { standings: self.standings() });
+            if self.round >= self.config.rounds {
+                self.finish();
+                events.push(ServerMessage::GameOver { standings: self.standings() });
+            } else {
+                events.push(self.start_round());
+            }
+        }
+        Ok(events)
+    }
+
+    fn start_round(&mut self) -> ServerMessage {
+        self.round += 1;
+        let config = GameConfig { max_attempts: None, ..self.config.game };
+        self.game = Some(Game::with_config(self.rng.r#gen(), config));
+        let deadline = Instant::now() + self.config.guess_timeout;
+        for p in self.players.values_mut() {
+            p.round_guesses = 0;
+            p.deadline = p.connected.then_some(deadline);
+        }
+        ServerMessage::RoundStarted { round: self.round, min: config.min, max: config.max }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+        self.game = None;
+        for p in self.players.values_mut() {
+            p.deadline = None;
+        }
+    }
+
+    /// Charge a wasted guess to every player whose deadline has passed.
+    fn expire_deadlines(&mut self, now: Instant) -> Vec<ServerMessage> {
+        let timeout = self.config.guess_timeout;
+        self.players
+            .iter_mut()
+            .filter(|(_, p)| p.deadline.is_some_and(|d| d <= now))
+            .map(|(name, p)| {
+                p.guesses += 1;
+                p.round_guesses += 1;
+                p.timeouts += 1;
+                p.deadline = Some(now + timeout);
+                ServerMessage::Timeout { player: name.clone() }
+            })
+            .collect()
+    }
+
+    fn connected_count(&self) -> usize { self.players.values().filter(|p| p.connected).count() }
+
+    /// Most wins first, then fewest guesses, then name.
+    fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .players
+            .iter()
+            .map(|(name, p)| Standing { name: name.clone(), wins: p.wins, guesses: p.guesses, timeouts: p.timeouts, connected: p.connected })
+            .collect();
+        standings.sort_by(|a, b| b.wins.cmp(&a.wins).then(a.guesses.cmp(&b.guesses)).then_with(|| a.name.cmp(&b.name)));
+        standings
+    }
+}
+
+#[derive(Clone)]
+struct Shared {
+    lobby: Arc<Mutex<Lobby>>,
+    events: broadcast::Sender<ServerMessage>,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Lobby> {
+        // A panicking connection task must not take the whole game down with it.
+        self.lobby.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn broadcast(&self, events: Vec<ServerMessage>) {
+        for e in events {
+            // No receivers just means nobody is connected right now.
+            let _ = self.events.send(e);
+        }
+    }
+}
+
+/// Run the server until the last round is won. Returns the final standings.
+pub async fn serve(listener: TcpListener, config: ServerConfig) -> io::Result<Vec<Standing>> {
+    let (events, _) = broadcast::channel(1024);
+    let shared = Shared { lobby: Arc::new(Mutex::new(Lobby::new(config))), events };
+    let mut connections = JoinSet::new();
+    let mut ticker = tokio::time::interval(Duration::from_millis(100));
+
+    while !shared.lock().finished {
+        tokio::select! {
+            accepted = listener.accept() => {
+                match accepted {
+                    Ok((stream, _)) => {
+                        connections.spawn(handle_connection(stream, shared.clone()));
+                    }
+                    Err(e) => {
+                        // Running out of file descriptors or a client hanging up mid-handshake
+                        // must not end the match for everyone already playing.
+                        eprintln!("{}", t!("server.accept_error", error = e));
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                let mut lobby = shared.lock();
+                let expired = lobby.expire_deadlines(Instant::now());
+                shared.broadcast(expired);
+            }
+        }
+    }
+
+    // Give connections a moment to deliver the final ranking before returning.
+    let _ = tokio::time::timeout(Duration::from_secs(2), async { while connections.join_next().await.is_some() {} }).await;
+    let standings = shared.lock().standings();
+    Ok(standings)
+}
+
+async fn handle_connection(stream: TcpStream, shared: Shared) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut events = shared.events.subscribe();
+    let mut name: Option<String> = None;
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Ok(Some(line)) = line else { break };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let (reply, quit) = match serde_json::from_str::<ClientMessage>(&line) {
+                    Ok(msg) => dispatch(&shared, &mut name, &mut events, msg),
+                    Err(e) => (Some(ServerMessage::Error { message: format!("malformed message: {}", e) }), false),
+                };
+                if let Some(reply) = reply
+                    && send(&mut writer, &reply).await.is_err()
+                {
+                    break;
+                }
+                if quit {
+                    break;
+                }
+            }
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let over = matches!(event, ServerMessage::GameOver { .. });
+                    if (name.is_some() || over) && send(&mut writer, &event).await.is_err() {
+                        break;
+                    }
+                    if over {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    if let Some(name) = name {
+        let mut lobby = shared.lock();
+        let left = lobby.leave(&name);
+        shared.broadcast(left);
+    }
+}
+
+/// Apply one client message. Returns a direct reply (if any) and whether to disconnect.
+fn dispatch(
+    shared: &Shared,
+    name: &mut Option<String>,
+    events: &mut broadcast::Receiver<ServerMessage>,
+    msg: ClientMessage,
+) -> (Option<ServerMessage>, bool) {
+    let mut lobby = shared.lock();
+    let result = match (msg, name.as_deref()) {
+        (ClientMessage::Quit, _) => return (None, true),
+        (ClientMessage::Join { .. }, Some(_)) => Err("already joined".to_string()),
+        (ClientMessage::Join { name: requested }, None) => lobby.join(&requested).map(|(welcome, joined_events)| {
+            if let ServerMessage::Welcome { name: joined, .. } = &welcome {
+                *name = Some(joined.clone());
+            }
+            // Drop whatever was broadcast before this player joined.
+            *events = shared.events.subscribe();
+            shared.broadcast(joined_events);
+            Some(welcome)
+        }),
+        (ClientMessage::Guess { .. }, None) => Err("join first".to_string()),
+        (ClientMessage::Guess { value }, Some(player)) => lobby.guess(player, value).map(|events| {
+            shared.broadcast(events);
+            None
+        }),
+    };
+    (result.unwrap_or_else(|message| Some(ServerMessage::Error { message })), false)
+}
+
+async fn send(writer: &mut OwnedWriteHalf, msg: &ServerMessage) -> io::Result<()> {
+    let mut line = serde_json::to_vec(msg)?;
+    line.push(b'\n');
+    writer.write_all(&line).await
+}
@@ -0,0 +1,205 @@
+//! Session recordings (JSON Lines) and the save file used to resume interrupted games.
+//!
+//! A recording has one `SessionLine` per prompt, input and response, each stamped with
+//! the milliseconds since the session started, so `replay` can reproduce its timing.
+//! A `SavedGame` only needs the seed, the config and the guesses: replaying the guesses
+//! on a fresh `Game` restores the same secret and attempt count.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::GameConfig;
+use crate::game::{Game, GuessError};
+use crate::hints::Hint;
+use crate::storage;
+
+const SAVE_FILE: &str = "saved_game.json";
+const SESSIONS_DIR: &str = "sessions";
+
+/// How a response was presented, so a replay can color it the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tone {
+    Info,
+    Error,
+    Success,
+    Hint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    Start { player: String, seed: u64, config: GameConfig, resumed: bool },
+    Prompt { text: String },
+    Input { text: String },
+    Output { text: String, tone: Tone },
+    /// `outcome` is `won`, `lost`, `saved` or `abandoned`.
+    End { outcome: String, attempts: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLine {
+    /// Milliseconds since the session started (time spent while saved is not counted).
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub event: SessionEvent,
+}
+
+/// Appends events to a `.jsonl` file, flushing every line so nothing is lost on exit.
+#[derive(Debug)]
+pub struct SessionRecorder {
+    path: PathBuf,
+    file: File,
+    started: Instant,
+    offset_ms: u64,
+}
+
+impl SessionRecorder {
+    /// A new recording in the sessions folder of the data directory.
+    pub fn create_default(seed: u64) -> io::Result<Self> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let path = storage::data_dir()?.join(SESSIONS_DIR).join(format!("{}-{}.jsonl", now, seed));
+        Self::open(&path, 0)
+    }
+
+    /// Continue an existing recording, timestamping after its last line.
+    pub fn resume(path: &Path) -> io::Result<Self> {
+        let last = match read_session(path) {
+            Ok(lines) => lines.last().map_or(0, |l| l.at_ms),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        Self::open(path, last)
+    }
+
+    /// Open (or continue) a recording at `path`; timestamps start at `offset_ms`.
+    pub fn open(path: &Path, offset_ms: u64) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { path: path.to_path_buf(), file, started: Instant::now(), offset_ms })
+    }
+
+    pub fn path(&self) -> &Path { &self.path }
+
+    pub fn elapsed_ms(&self) -> u64 { self.offset_ms + self.started.elapsed().as_millis() as u64 }
+
+    pub fn record(&mut self, event: SessionEvent) -> io::Result<()> {
+        let line = SessionLine { at_ms: self.elapsed_ms(), event };
+        let mut bytes = serde_json::to_vec(&line)?;
+        bytes.push(b'\n');
+        self.file.write_all(&bytes)
+    }
+}
+
+/// Read every line of a recording. Blank lines are skipped; a malformed line is an error.
+pub fn read_session(path: &Path) -> io::Result<Vec<SessionLine>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed = serde_json::from_str(&line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, e)))?;
+        lines.push(parsed);
+    }
+    Ok(lines)
+}
+
+/// A game interrupted before it ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub player: String,
+    pub seed: u64,
+    pub config: GameConfig,
+    pub guesses: Vec<u32>,
+    /// Hints bought so far; their cost stays off the score after resuming.
+    #[serde(default)]
+    pub hints: Vec<Hint>,
+    /// Play time before the interruption.
+    pub elapsed_ms: u64,
+    /// Recording to continue on resume.
+    pub session: Option<PathBuf>,
+}
+
+impl SavedGame {
+    pub fn from_game(player: &str, game: &Game, hints: &[Hint], elapsed_ms: u64, session: Option<PathBuf>) -> Self {
+        Self {
+            player: player.to_string(),
+            seed: game.seed(),
+            config: game.config(),
+            guesses: game.history().iter().map(|g| g.value).collect(),
+            hints: hints.to_vec(),
+            elapsed_ms,
+            session,
+        }
+    }
+
+    /// Rebuild the game by replaying the saved guesses from the seed.
+    pub fn restore(&self) -> Result<Game, GuessError> {
+        let mut game = Game::with_config(self.seed, self.config);
+        for &value in &self.guesses {
+            game.guess(value)?;
+        }
+        Ok(game)
+    }
+
+    pub fn path() -> io::Result<PathBuf> { Ok(storage::data_dir()?.join(SAVE_FILE)) }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::write_atomic(&Self::path()?, &serde_json::to_vec_pretty(self)?)
+    }
+
+    /// The saved game, if any.
+    pub fn load() -> io::Result<Option<Self>> { storage::read_json(&Self::path()?) }
+
+    pub fn clear() -> io::Result<()> {
+        match fs::remove_file(Self::path()?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_game_restores_secret_and_attempts() {
+        let mut game = Game::new(99);
+        let wrong = if game.secret() == 1 { 2 } else { 1 };
+        game.guess(wrong).unwrap();
+        game.guess(wrong).unwrap();
+        let saved = SavedGame::from_game("ana", &game, &[Hint::Parity], 1234, None);
+        let restored = saved.restore().unwrap();
+        assert_eq!(restored.secret(), game.secret());
+        assert_eq!(restored.attempts(), 2);
+    }
+
+    #[test]
+    fn recording_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        let mut rec = SessionRecorder::open(&path, 500).unwrap();
+        rec.record(SessionEvent::Prompt { text: "Guess?".into() }).unwrap();
+        rec.record(SessionEvent::Input { text: "50".into() }).unwrap();
+        drop(rec);
+        let mut rec = SessionRecorder::resume(&path).unwrap();
+        rec.record(SessionEvent::Output { text: "Too low".into(), tone: Tone::Error }).unwrap();
+
+        let lines = read_session(&path).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].at_ms >= 500 && lines[2].at_ms >= lines[1].at_ms);
+        assert_eq!(lines[1].event, SessionEvent::Input { text: "50".into() });
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(raw.starts_with(r#"{"at_ms":"#) && raw.contains(r#""event":"prompt""#));
+    }
+}
@@ -0,0 +1,138 @@
+//! Local data files: where they live and how to replace them safely.
+//!
+//! Files go under `$XDG_DATA_HOME/basic_game` (e.g. `~/.local/share/basic_game`), or under
+//! `$GUESSING_GAME_DATA_DIR` when set. Writers take a lock file, write a temporary file,
+//! fsync it and rename it over the target, so readers never see a half-written file and
+//! two games finishing at the same time cannot lose each other's updates.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const APP_DIR: &str = "basic_game";
+/// A lock older than this is assumed to belong to a crashed process.
+const STALE_LOCK_AFTER: Duration = Duration::from_secs(10);
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Directory holding all persistent game data.
+pub fn data_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("GUESSING_GAME_DATA_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::data_dir()
+        .map(|d| d.join(APP_DIR))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "could not determine the user data directory"))
+}
+
+/// Read a JSON file, returning `T::default()` when it does not exist yet.
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> io::Result<T> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replace `path` with `bytes` atomically: write a sibling temp file, fsync, then rename.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Read-modify-write a JSON file under an exclusive lock.
+pub fn update_json<T, R>(path: &Path, update: impl FnOnce(&mut T) -> R) -> io::Result<R>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let _lock = FileLock::acquire(path)?;
+    let mut value: T = read_json(path)?;
+    let result = update(&mut value);
+    write_atomic(path, &serde_json::to_vec_pretty(&value)?)?;
+    Ok(result)
+}
+
+/// Exclusive lock implemented as a `<file>.lock` created with `create_new`.
+/// The lock file is removed when the guard is dropped.
+struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    fn acquire(target: &Path) -> io::Result<Self> {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let path = target.with_extension("lock");
+        let started = SystemTime::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if Self::is_stale(&path) {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if started.elapsed().unwrap_or_default() > LOCK_TIMEOUT {
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, format!("timed out waiting for {}", path.display())));
+                    }
+                    thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn is_stale(path: &Path) -> bool {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(|t| t.elapsed().unwrap_or_default() > STALE_LOCK_AFTER)
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counter.json");
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    for _ in 0..10 {
+                        update_json(&path, |n: &mut u32| *n += 1).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(read_json::<u32>(&path).unwrap(), 80);
+        assert!(!path.with_extension("lock").exists());
+    }
+}
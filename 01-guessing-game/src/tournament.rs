@@ -0,0 +1,198 @@
+//! Strategy tournament: bot executables play the same seeded games over the machine
+//! protocol (see `machine`), and their attempts are compared.
+//!
+//! Every bot process plays one game and is then killed. A bot that exits early, sends a
+//! malformed line or takes longer than the move timeout forfeits that game.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::difficulty::GameConfig;
+use crate::game::{Game, Outcome};
+use crate::machine::{MachineReply, Referee};
+
+/// A bot command line, e.g. `./target/release/bisect-bot` or `python3 bot.py`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bot {
+    /// Shown in the results table: the program's file name plus its arguments.
+    pub name: String,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl Bot {
+    /// Split a command line on whitespace. `None` when it is empty.
+    pub fn parse(command: &str) -> Option<Self> {
+        let mut words = command.split_whitespace().map(str::to_string);
+        let program = words.next()?;
+        let args: Vec<String> = words.collect();
+        let file = Path::new(&program).file_name().map_or(program.clone(), |n| n.to_string_lossy().into_owned());
+        let name = std::iter::once(file).chain(args.iter().cloned()).collect::<Vec<_>>().join(" ");
+        Some(Self { name, program, args })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    Finished { won: bool, attempts: usize },
+    Forfeit { reason: String },
+}
+
+/// Play `game` against one run of `bot`.
+pub fn play_game(bot: &Bot, game: Game, move_timeout: Duration) -> GameResult {
+    let spawned = Command::new(&bot.program)
+        .args(&bot.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => return GameResult::Forfeit { reason: format!("could not start: {}", e) },
+    };
+    let result = referee_bot(&mut child, Referee::new(game), move_timeout);
+    // The bot is done either way; don't leave it running.
+    let _ = child.kill();
+    let _ = child.wait();
+    result
+}
+
+fn referee_bot(child: &mut Child, mut referee: Referee, move_timeout: Duration) -> GameResult {
+    let forfeit = |reason: String| GameResult::Forfeit { reason };
+    let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        return forfeit("no stdin/stdout pipes".into());
+    };
+
+    // A blocking read can't time out, so lines arrive through a channel instead. The
+    // thread ends when the bot is killed and its stdout closes.
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Write errors are ignored: a bot that stopped listening is judged on what it printed,
+    // and one that exited shows up as a closed channel below.
+    let _ = send(&mut stdin, &referee.start());
+    loop {
+        let line = match rx.recv_timeout(move_timeout) {
+            Ok(Ok(line)) => line,
+            Ok(Err(e)) => return forfeit(format!("unreadable output: {}", e)),
+            Err(RecvTimeoutError::Timeout) => return forfeit(format!("no guess within {} ms", move_timeout.as_millis())),
+            Err(RecvTimeoutError::Disconnected) => return forfeit("exited before the game ended".into()),
+        };
+        match referee.handle(&line) {
+            Ok(replies) => {
+                for reply in &replies {
+                    let _ = send(&mut stdin, reply);
+                }
+            }
+            Err(reply) => {
+                let _ = send(&mut stdin, &reply);
+                let MachineReply::Error { message } = reply else { unreachable!("handle only fails with errors") };
+                return forfeit(message);
+            }
+        }
+        match referee.game().outcome() {
+            Outcome::InProgress => {}
+            Outcome::Won { attempts } => return GameResult::Finished { won: true, attempts },
+            Outcome::Lost { attempts } => return GameResult::Finished { won: false, attempts },
+        }
+    }
+}
+
+fn send(stdin: &mut ChildStdin, reply: &MachineReply) -> std::io::Result<()> {
+    writeln!(stdin, "{}", reply.to_line())?;
+    stdin.flush()
+}
+
+/// One row of the results table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotStats {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub forfeits: u32,
+    /// Over the games that were not forfeited (a loss counts the whole budget).
+    pub mean_attempts: Option<f64>,
+    pub worst_attempts: Option<usize>,
+    /// Why the first forfeit happened, to help debug the bot.
+    pub first_forfeit: Option<String>,
+}
+
+impl BotStats {
+    fn new(name: &str) -> Self {
+        Self { name: name.to_string(), games: 0, wins: 0, forfeits: 0, mean_attempts: None, worst_attempts: None, first_forfeit: None }
+    }
+}
+
+/// Play `games` games per bot, game `i` using seed `seed + i` for every bot, and rank
+/// the bots: fewest forfeits, then most wins, then lowest mean attempts. Bots run in
+/// parallel, each on its own thread.
+pub fn run(bots: &[Bot], config: GameConfig, games: u32, seed: u64, move_timeout: Duration) -> Vec<BotStats> {
+    // Without a budget a bot repeating one wrong guess would never finish; a linear scan
+    // of the whole range always wins within this many attempts.
+    let config = GameConfig { max_attempts: config.max_attempts.or(Some((config.max - config.min).saturating_add(1))), ..config };
+
+    let mut table: Vec<BotStats> = thread::scope(|scope| {
+        let handles: Vec<_> = bots
+            .iter()
+            .map(|bot| {
+                scope.spawn(move || {
+                    let mut stats = BotStats::new(&bot.name);
+                    let mut attempts = Vec::new();
+                    for i in 0..games {
+                        let game = Game::with_config(seed.wrapping_add(i as u64), config);
+                        stats.games += 1;
+                        match play_game(bot, game, move_timeout) {
+                            GameResult::Finished { won, attempts: n } => {
+                                stats.wins += won as u32;
+                                attempts.push(n);
+                            }
+                            GameResult::Forfeit { reason } => {
+                                stats.forfeits += 1;
+                                stats.first_forfeit.get_or_insert(reason);
+                            }
+                        }
+                    }
+                    if !attempts.is_empty() {
+                        stats.mean_attempts = Some(attempts.iter().sum::<usize>() as f64 / attempts.len() as f64);
+                        stats.worst_attempts = attempts.iter().copied().max();
+                    }
+                    stats
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().expect("bot thread panicked")).collect()
+    });
+
+    table.sort_by(|a, b| {
+        a.forfeits
+            .cmp(&b.forfeits)
+            .then(b.wins.cmp(&a.wins))
+            .then(a.mean_attempts.unwrap_or(f64::INFINITY).total_cmp(&b.mean_attempts.unwrap_or(f64::INFINITY)))
+    });
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bot_commands() {
+        let bot = Bot::parse("  python3 bots/smart.py --fast ").unwrap();
+        assert_eq!(bot.program, "python3");
+        assert_eq!(bot.args, ["bots/smart.py", "--fast"]);
+        assert_eq!(bot.name, "python3 bots/smart.py --fast");
+        assert_eq!(Bot::parse("./target/release/bisect-bot").unwrap().name, "bisect-bot");
+        assert_eq!(Bot::parse("   "), None);
+    }
+}
@@ -0,0 +1,221 @@
+//! Full-screen interface (`--tui`): the candidate interval as a shrinking number line,
+//! the guess history, attempts left, score and a timer, with messages shown inline.
+//!
+//! Game logic is shared with the plain loop through `handle_input`; this module only
+//! draws the state and collects keystrokes.
+
+use std::cmp::Ordering;
+use std::io::{self, IsTerminal};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use basic_game::hints::HintBank;
+use basic_game::session::{SessionEvent, Tone};
+use basic_game::{t, tn, Game, GameConfig};
+
+use crate::modes::classic::{announce_result, handle_input};
+use crate::screen::{paint, Screen};
+
+/// Longest line the input box accepts, in characters (`:hint divisible 1000000` fits).
+const MAX_INPUT: usize = 32;
+
+/// Whether the TUI can run: stdin and stdout are terminals and `NO_COLOR` is not set.
+pub fn supported() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color && io::stdout().is_terminal() && io::stdin().is_terminal()
+}
+
+/// The full-screen equivalent of the plain guess loop. Returns `Ok(false)` when the
+/// player quits with Esc or Ctrl+C, so the game gets saved.
+pub fn guess_loop(
+    juego: &mut Game,
+    pistas: &mut HintBank,
+    screen: &mut Screen,
+    on_change: &mut dyn FnMut(&Game, &HintBank),
+) -> io::Result<bool> {
+    let mut terminal = ratatui::try_init()?;
+    let mut estado = State { entrada: String::new(), mensajes: screen.take_captured(), inicio: Instant::now() };
+    let resultado = run(&mut terminal, juego, pistas, screen, on_change, &mut estado);
+    ratatui::restore();
+
+    if resultado.as_ref().is_ok_and(|&terminado| terminado) {
+        // Leave the result in the normal scrollback once the alternate screen is gone.
+        for (tono, texto) in &estado.mensajes {
+            println!("{}", paint(*tono, texto));
+        }
+    }
+    resultado
+}
+
+struct State {
+    entrada: String,
+    /// Output of the last action; replaced (not appended) so history stays on screen.
+    mensajes: Vec<(Tone, String)>,
+    inicio: Instant,
+}
+
+fn run(
+    terminal: &mut DefaultTerminal,
+    juego: &mut Game,
+    pistas: &mut HintBank,
+    screen: &mut Screen,
+    on_change: &mut dyn FnMut(&Game, &HintBank),
+    estado: &mut State,
+) -> io::Result<bool> {
+    loop {
+        terminal.draw(|frame| draw(frame, juego, pistas, estado))?;
+        // Redraw at least every 250 ms so the timer keeps moving.
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        let Event::Key(tecla) = event::read()? else { continue };
+        if tecla.kind != KeyEventKind::Press {
+            continue;
+        }
+        if juego.is_over() {
+            return Ok(true);
+        }
+        match tecla.code {
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if tecla.modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+            KeyCode::Char(c) if estado.entrada.chars().count() < MAX_INPUT => estado.entrada.push(c),
+            KeyCode::Backspace => {
+                estado.entrada.pop();
+            }
+            KeyCode::Enter => {
+                let linea = std::mem::take(&mut estado.entrada).trim().to_string();
+                if linea.is_empty() {
+                    continue;
+                }
+                screen.record(SessionEvent::Input { text: linea.clone() });
+                handle_input(&linea, juego, pistas, screen, on_change);
+                announce_result(juego, pistas, screen);
+                estado.mensajes = screen.take_captured();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, juego: &Game, pistas: &HintBank, estado: &State) {
+    let [recta, datos, historial, mensajes, entrada] = Layout::vertical([
+        Constraint::Length(6),
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(4),
+        Constraint::Length(3),
+    ])
+    .areas(frame.area());
+
+    draw_number_line(frame, recta, juego);
+
+    let segundos = estado.inicio.elapsed().as_secs();
+    let intentos = juego.attempts_left().map_or(t!("tui.unlimited"), |n| n.to_string());
+    let datos_linea = Line::from(vec![
+        format!(" {} ", t!("tui.attempts_left")).into(),
+        intentos.bold(),
+        format!("   {} ", t!("tui.score")).into(),
+        pistas.score(juego).to_string().bold(),
+        format!("   {} ", t!("tui.time")).into(),
+        format!("{:02}:{:02}", segundos / 60, segundos % 60).bold(),
+    ]);
+    frame.render_widget(Paragraph::new(datos_linea), datos);
+
+    // Newest guesses at the bottom; older ones scroll off the top.
+    let visibles = historial.height.saturating_sub(2) as usize;
+    let lineas: Vec<Line> = juego
+        .history()
+        .iter()
+        .enumerate()
+        .skip(juego.history().len().saturating_sub(visibles))
+        .map(|(i, g)| {
+            let (dicho, flecha, color) = match g.ordering {
+                Ordering::Less => (t!("feedback.too_low"), "↑", Color::Red),
+                Ordering::Greater => (t!("feedback.too_high"), "↓", Color::Red),
+                Ordering::Equal => (t!("feedback.correct"), "✓", Color::Green),
+            };
+            let dicho = format!("{:<8} {}", dicho, flecha);
+            Line::from(vec![format!(" #{:<3} {:>6}  ", i + 1, g.value).into(), Span::styled(dicho, Style::new().fg(color))])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lineas).block(Block::bordered().title(format!(" {} ", t!("tui.history")))), historial);
+
+    let lineas: Vec<Line> = estado.mensajes.iter().map(|(tono, texto)| Line::styled(texto.as_str(), tone_style(*tono))).collect();
+    frame.render_widget(Paragraph::new(lineas), mensajes);
+
+    let (titulo, texto) = if juego.is_over() {
+        (t!("tui.game_over"), t!("tui.press_key"))
+    } else {
+        (t!("tui.input"), format!("> {}", estado.entrada))
+    };
+    frame.render_widget(Paragraph::new(texto).block(Block::bordered().title(format!(" {} ", titulo))), entrada);
+    if !juego.is_over() {
+        frame.set_cursor_position(Position::new(entrada.x + 3 + estado.entrada.chars().count() as u16, entrada.y + 1));
+    }
+}
+
+fn draw_number_line(frame: &mut Frame, area: Rect, juego: &Game) {
+    let config = juego.config();
+    let bloque = Block::bordered().title(format!(" {}–{} ", config.min, config.max));
+    let interior = bloque.inner(area);
+    let ancho = interior.width as usize;
+    let candidatos = juego.candidates();
+
+    let celdas = columns(config, ancho);
+    let barra: Vec<Span> = celdas
+        .iter()
+        .map(|c| {
+            if overlaps(c, &candidatos) {
+                Span::styled("█", Style::new().fg(Color::Green))
+            } else {
+                Span::styled("░", Style::new().fg(Color::DarkGray))
+            }
+        })
+        .collect();
+    let ultima = juego.history().last().map(|g| g.value);
+    let marcas: Vec<Span> = celdas
+        .iter()
+        .map(|c| match juego.history().iter().rev().find(|g| c.contains(&g.value)) {
+            Some(g) if Some(g.value) == ultima => Span::styled("▲", Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Some(_) => Span::styled("▲", Style::new().fg(Color::DarkGray)),
+            None => Span::raw(" "),
+        })
+        .collect();
+    let (min, max) = (config.min.to_string(), config.max.to_string());
+    let extremos = format!("{}{:>w$}", min, max, w = ancho.saturating_sub(min.len()));
+    let resumen = tn!("tui.possible", juego.candidate_count(), low = candidatos.start(), high = candidatos.end());
+
+    let lineas = vec![Line::from(barra), Line::from(marcas), Line::from(extremos), Line::from(resumen.bold())];
+    frame.render_widget(Paragraph::new(lineas).block(bloque), area);
+}
+
+/// Split the configured range into `width` consecutive columns of the number line.
+fn columns(config: GameConfig, width: usize) -> Vec<RangeInclusive<u32>> {
+    let span = (config.max - config.min) as u64 + 1;
+    let width = (width as u64).min(span).max(1);
+    (0..width)
+        .map(|i| {
+            let start = config.min as u64 + span * i / width;
+            let end = config.min as u64 + span * (i + 1) / width - 1;
+            start as u32..=end as u32
+        })
+        .collect()
+}
+
+fn overlaps(a: &RangeInclusive<u32>, b: &RangeInclusive<u32>) -> bool { a.start() <= b.end() && b.start() <= a.end() }
+
+fn tone_style(tone: Tone) -> Style {
+    match tone {
+        Tone::Info => Style::new(),
+        Tone::Error => Style::new().fg(Color::Red),
+        Tone::Success => Style::new().fg(Color::Green),
+        Tone::Hint => Style::new().fg(Color::Cyan),
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+
+use basic_game::protocol::{ClientMessage, Feedback, ServerMessage};
+use basic_game::server::{serve, ServerConfig};
+use basic_game::GameConfig;
+
+struct Client {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+impl Client {
+    async fn connect(addr: std::net::SocketAddr, name: &str) -> Self {
+        let (reader, writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut client = Self { lines: BufReader::new(reader).lines(), writer };
+        client.send(&ClientMessage::Join { name: name.into() }).await;
+        client
+    }
+
+    async fn send(&mut self, msg: &ClientMessage) {
+        let mut line = serde_json::to_vec(msg).unwrap();
+        line.push(b'\n');
+        self.writer.write_all(&line).await.unwrap();
+    }
+
+    async fn recv(&mut self) -> ServerMessage {
+        let line = timeout(Duration::from_secs(5), self.lines.next_line()).await.unwrap().unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    /// Read until a message matches, returning it.
+    async fn wait_for(&mut self, pred: impl Fn(&ServerMessage) -> bool) -> ServerMessage {
+        loop {
+            let msg = self.recv().await;
+            if pred(&msg) {
+                return msg;
+            }
+        }
+    }
+}
+
+async fn start(min_players: usize, rounds: u32, guess_timeout: Duration) -> (std::net::SocketAddr, tokio::task::JoinHandle<Vec<basic_game::protocol::Standing>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let config = ServerConfig { game: GameConfig::new(1, 100, None).unwrap(), rounds, guess_timeout, min_players, seed: 7 };
+    let handle = tokio::spawn(async move { serve(listener, config).await.unwrap() });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn players_race_and_see_each_others_guesses() {
+    let (addr, server) = start(2, 1, Duration::from_secs(30)).await;
+    let mut ana = Client::connect(addr, "ana").await;
+    assert!(matches!(ana.recv().await, ServerMessage::Welcome { reconnected: false, .. }));
+    let mut bob = Client::connect(addr, "bob").await;
+    ana.wait_for(|m| matches!(m, ServerMessage::RoundStarted { round: 1, .. })).await;
+
+    // Ana binary-searches; Bob only watches.
+    let (mut low, mut high) = (1, 100);
+    loop {
+        let value = (low + high) / 2;
+        ana.send(&ClientMessage::Guess { value }).await;
+        let ServerMessage::Guess { player, result, .. } = ana.wait_for(|m| matches!(m, ServerMessage::Guess { .. })).await else { unreachable!() };
+        assert_eq!(player, "ana");
+        match result {
+            Feedback::TooLow => low = value + 1,
+            Feedback::TooHigh => high = value - 1,
+            Feedback::Correct => break,
+        }
+    }
+
+    let seen = bob.wait_for(|m| matches!(m, ServerMessage::Guess { .. })).await;
+    assert!(matches!(seen, ServerMessage::Guess { ref player, .. } if player == "ana"));
+    let won = bob.wait_for(|m| matches!(m, ServerMessage::RoundWon { .. })).await;
+    assert!(matches!(won, ServerMessage::RoundWon { ref winner, .. } if winner == "ana"));
+    let ServerMessage::GameOver { standings } = bob.wait_for(|m| matches!(m, ServerMessage::GameOver { .. })).await else { unreachable!() };
+    assert_eq!(standings[0].name, "ana");
+    assert_eq!(standings[0].wins, 1);
+
+    let final_standings = server.await.unwrap();
+    assert_eq!(final_standings[0].name, "ana");
+}
+
+#[tokio::test]
+async fn idle_players_time_out_and_can_reconnect() {
+    let (addr, _server) = start(1, 1, Duration::from_millis(300)).await;
+    let mut ana = Client::connect(addr, "ana").await;
+    ana.wait_for(|m| matches!(m, ServerMessage::RoundStarted { .. })).await;
+    let timed_out = ana.wait_for(|m| matches!(m, ServerMessage::Timeout { .. })).await;
+    assert_eq!(timed_out, ServerMessage::Timeout { player: "ana".into() });
+
+    let mut impostor = Client::connect(addr, "ana").await;
+    assert!(matches!(impostor.recv().await, ServerMessage::Error { .. }));
+
+    ana.send(&ClientMessage::Quit).await;
+    drop(ana);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut again = Client::connect(addr, "ana").await;
+    assert!(matches!(again.recv().await, ServerMessage::Welcome { reconnected: true, .. }));
+
+    again.send(&ClientMessage::Guess { value: 1000 }).await;
+    again.wait_for(|m| matches!(m, ServerMessage::Error { .. })).await;
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use basic_game::tournament::{self, Bot};
+use basic_game::GameConfig;
+
+fn bot(command: &str) -> Bot { Bot::parse(command).unwrap() }
+
+#[test]
+fn bisect_bot_wins_every_game_within_log2_attempts() {
+    let bisect = bot(env!("CARGO_BIN_EXE_bisect-bot"));
+    let config = GameConfig::new(1, 100, Some(10)).unwrap();
+    let table = tournament::run(&[bisect], config, 20, 1, Duration::from_secs(5));
+    assert_eq!(table[0].name, "bisect-bot");
+    assert_eq!((table[0].games, table[0].wins, table[0].forfeits), (20, 20, 0));
+    assert!(table[0].worst_attempts.unwrap() <= 7);
+}
+
+#[test]
+fn hanging_and_malformed_bots_forfeit() {
+    let bots = [bot("sleep 10"), bot("echo fifty"), bot("true"), bot(env!("CARGO_BIN_EXE_bisect-bot"))];
+    let config = GameConfig::new(1, 100, Some(10)).unwrap();
+    let table = tournament::run(&bots, config, 2, 1, Duration::from_millis(300));
+
+    assert_eq!(table[0].name, "bisect-bot");
+    let reason = |name: &str| table.iter().find(|t| t.name == name).unwrap().first_forfeit.clone().unwrap();
+    assert!(reason("sleep 10").contains("no guess within 300 ms"));
+    assert!(reason("echo fifty").contains("expected a number"));
+    assert!(reason("true").contains("exited"));
+    assert!(table[1..].iter().all(|t| t.forfeits == 2 && t.mean_attempts.is_none()));
+}
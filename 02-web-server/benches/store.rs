@@ -0,0 +1,122 @@
+// Throughput of the two user stores under a mixed read/write load from several threads.
+//
+//     cargo bench -p web-server-01
+//
+// Each thread runs OPS_PER_THREAD operations against a store prefilled with USERS users:
+// 80% lookups, 15% renames, 5% insert-then-delete of a fresh user. In `mixed_load` the
+// commit hook does nothing, so it measures the locking alone; `mixed_load_wal` appends every
+// change to a write-ahead log in a temp dir, fsync included, as the server does with
+// USERS_DATA_DIR set. Fsyncs are slow, so that group runs fewer operations per thread.
+
+use std::{
+    hint::black_box,
+    sync::{Mutex, PoisonError},
+    thread,
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use uuid::Uuid;
+use web_server_01::{
+    persistence::{Mutation, Wal},
+    store::{Change, MutexStore, ShardedStore, UserStore},
+    ApiError, User,
+};
+
+const USERS: usize = 10_000;
+const OPS_PER_THREAD: usize = 10_000;
+const WAL_OPS_PER_THREAD: usize = 500;
+
+type Hook<'a> = dyn Fn(&[Change]) -> Result<(), ApiError> + Sync + 'a;
+
+fn user(n: usize) -> User {
+    User { id: Uuid::new_v4(), name: format!("User {}", n), email: format!("user{}@example.com", n) }
+}
+
+fn no_hook(_: &[Change]) -> Result<(), ApiError> {
+    Ok(())
+}
+
+// xorshift: cheap, and deterministic per thread so both stores see the same operations
+fn next(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+fn run(store: &dyn UserStore, ids: &[Uuid], thread: usize, ops: usize, hook: &Hook<'_>) {
+    let mut seed = 0x9E37_79B9_7F4A_7C15 ^ (thread as u64 + 1);
+    let commit = &mut |changes: &[Change]| hook(changes);
+    for op in 0..ops {
+        let roll = next(&mut seed);
+        let id = ids[roll as usize % ids.len()];
+        match roll % 100 {
+            0..=79 => {
+                black_box(store.get(&id));
+            }
+            80..=94 => {
+                let rename = |u: &mut User| u.name = format!("Renamed {}", op);
+                black_box(store.update(&id, &rename, commit).ok());
+            }
+            _ => {
+                let fresh = user(USERS + thread * ops + op);
+                let fresh_id = fresh.id;
+                store.insert(fresh, commit).expect("fresh email");
+                store.remove(&fresh_id, commit).expect("just inserted");
+            }
+        }
+    }
+}
+
+fn stores(users: &[User]) -> [(&'static str, Box<dyn UserStore>); 2] {
+    [
+        ("mutex", Box::new(MutexStore::from_users(users.to_vec()))),
+        ("sharded", Box::new(ShardedStore::from_users(users.to_vec()))),
+    ]
+}
+
+fn bench_stores(c: &mut Criterion, group: &str, ops: usize, hook: &Hook<'_>) {
+    let users: Vec<User> = (0..USERS).map(user).collect();
+    let ids: Vec<Uuid> = users.iter().map(|u| u.id).collect();
+    let stores = stores(&users);
+
+    let mut group = c.benchmark_group(group);
+    if ops < OPS_PER_THREAD {
+        group.sample_size(10);
+    }
+    for threads in [1, 4, 8] {
+        group.throughput(Throughput::Elements((threads * ops) as u64));
+        for (name, store) in &stores {
+            group.bench_with_input(BenchmarkId::new(*name, threads), &threads, |b, &threads| {
+                b.iter(|| {
+                    thread::scope(|scope| {
+                        for thread in 0..threads {
+                            let (store, ids) = (store.as_ref(), ids.as_slice());
+                            scope.spawn(move || run(store, ids, thread, ops, hook));
+                        }
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn mixed_load(c: &mut Criterion) {
+    bench_stores(c, "mixed_load", OPS_PER_THREAD, &no_hook);
+}
+
+fn mixed_load_wal(c: &mut Criterion) {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let (wal, _) = Wal::open(dir.path()).expect("open log");
+    let wal = Mutex::new(wal);
+    let append = |changes: &[Change]| -> Result<(), ApiError> {
+        let mutations: Vec<Mutation> = changes.iter().map(Mutation::from).collect();
+        wal.lock().unwrap_or_else(PoisonError::into_inner).append(&mutations).expect("append to log");
+        Ok(())
+    };
+    bench_stores(c, "mixed_load_wal", WAL_OPS_PER_THREAD, &append);
+}
+
+criterion_group!(benches, mixed_load, mixed_load_wal);
+criterion_main!(benches);
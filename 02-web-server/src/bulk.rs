@@ -0,0 +1,432 @@
+// Bulk import and export of users as CSV or NDJSON.
+//
+// Both directions stream. Import reads the body record by record and inserts the rows of each
+// received chunk in one store operation with one log fsync; if it fails partway, the report
+// still lists the rows stored before the failure. Export collects only the ids,
+// then serializes the users a chunk at a time, looking each one up as it goes. Neither
+// holds the store's locks for the whole export or clones the store.
+
+use std::io;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{
+        header::{ACCEPT, CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::{stream, StreamExt};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{api_error, validate_email, ApiError, AppState, CreateUserRequest, User};
+
+// A line that never ends is a broken upload, not a user
+const MAX_LINE_BYTES: usize = 64 * 1024;
+// Users serialized per chunk of the export body
+const EXPORT_CHUNK: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Ndjson,
+}
+
+impl Format {
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Csv => "text/csv; charset=utf-8",
+            Format::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn from_mime(mime: &str) -> Option<Self> {
+        match mime.split(';').next()?.trim().to_ascii_lowercase().as_str() {
+            "text/csv" => Some(Format::Csv),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => Some(Format::Ndjson),
+            _ => None,
+        }
+    }
+
+    // The first supported type listed in Accept wins; no header or a wildcard means NDJSON
+    fn from_accept(accept: Option<&str>) -> Option<Self> {
+        let Some(accept) = accept else { return Some(Format::Ndjson) };
+        accept.split(',').find_map(|mime| match mime.split(';').next().unwrap_or("").trim() {
+            "*/*" | "application/*" => Some(Format::Ndjson),
+            "text/*" => Some(Format::Csv),
+            mime => Format::from_mime(mime),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum RowStatus {
+    Created { id: Uuid },
+    // Taken by an existing user or by an earlier row of the same import
+    Duplicate { email: String },
+    Invalid { error: String },
+}
+
+#[derive(Debug, Serialize)]
+struct RowReport {
+    // 1-based line number in the uploaded file (the CSV header is line 1)
+    line: usize,
+    #[serde(flatten)]
+    status: RowStatus,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    created: usize,
+    duplicates: usize,
+    invalid: usize,
+    rows: Vec<RowReport>,
+    // Set when the import stopped early; the rows above were stored all the same
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed_line: Option<usize>,
+}
+
+impl ImportReport {
+    fn push(&mut self, line: usize, status: RowStatus) {
+        match status {
+            RowStatus::Created { .. } => self.created += 1,
+            RowStatus::Duplicate { .. } => self.duplicates += 1,
+            RowStatus::Invalid { .. } => self.invalid += 1,
+        }
+        self.rows.push(RowReport { line, status });
+    }
+}
+
+// A parsed row and the line it came from
+type Row = (usize, Result<User, String>);
+
+// Why an import stopped, and the line it stopped at
+type Failure = (usize, ApiError);
+
+struct Importer {
+    format: Format,
+    // CSV positions of the name and email columns, once the header has been read
+    columns: Option<(usize, usize)>,
+    // Lines of the upload consumed so far
+    line: usize,
+    report: ImportReport,
+}
+
+impl Importer {
+    fn new(format: Format) -> Self {
+        Self { format, columns: None, line: 0, report: ImportReport::default() }
+    }
+
+    // Read the body a chunk at a time, storing the rows of each chunk as it arrives
+    async fn run(&mut self, state: &AppState, body: Body) -> Result<(), Failure> {
+        // Bytes of a record whose end hasn't arrived yet
+        let mut partial = Vec::new();
+        let mut chunks = body.into_data_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk =
+                chunk.map_err(|_| (self.line + 1, api_error(StatusCode::BAD_REQUEST, "Failed to read request body")))?;
+            partial.extend_from_slice(&chunk);
+            let (rows, used) = self.parse(&partial, false)?;
+            self.apply(state, rows).await?;
+            partial.drain(..used);
+            if partial.len() > MAX_LINE_BYTES {
+                let message = format!("Line {} is longer than {} bytes", self.line + 1, MAX_LINE_BYTES);
+                return Err((self.line + 1, api_error(StatusCode::PAYLOAD_TOO_LARGE, message)));
+            }
+        }
+        let (rows, _) = self.parse(&partial, true)?;
+        self.apply(state, rows).await
+    }
+
+    // Parse the complete records at the start of `bytes`; returns them and the number of bytes
+    // they cover. At the end of the body (`last`) whatever is left counts as complete.
+    fn parse(&mut self, bytes: &[u8], last: bool) -> Result<(Vec<Row>, usize), Failure> {
+        match self.format {
+            Format::Ndjson => Ok(self.parse_ndjson(bytes, last)),
+            Format::Csv => self.parse_csv(bytes, last),
+        }
+    }
+
+    fn parse_ndjson(&mut self, bytes: &[u8], last: bool) -> (Vec<Row>, usize) {
+        let used = if last { bytes.len() } else { bytes.iter().rposition(|&b| b == b'\n').map_or(0, |end| end + 1) };
+        let mut rows = Vec::new();
+        for raw in bytes[..used].split_inclusive(|&b| b == b'\n') {
+            self.line += 1;
+            let raw = raw.strip_suffix(b"\n").unwrap_or(raw);
+            let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+            let request = match std::str::from_utf8(raw) {
+                Ok(text) if text.trim().is_empty() => continue,
+                Ok(text) => serde_json::from_str::<CreateUserRequest>(text).map_err(|e| e.to_string()),
+                Err(_) => Err("Line is not valid UTF-8".to_string()),
+            };
+            rows.push((self.line, request.and_then(new_user)));
+        }
+        (rows, used)
+    }
+
+    // 📄 RFC 4180 via the csv crate: quoted fields may hold commas, quotes and line breaks
+    fn parse_csv(&mut self, bytes: &[u8], last: bool) -> Result<(Vec<Row>, usize), Failure> {
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_reader(bytes);
+        let mut record = csv::ByteRecord::new();
+        let mut rows = Vec::new();
+        let mut used = 0;
+        // Line breaks in `bytes` before `counted`, to number records without rescanning
+        let (mut counted, mut breaks) = (0, 0);
+        loop {
+            // The reader reports a record from the end of the previous one, before the line
+            // breaks it skips; number it from its first byte instead
+            let from = reader.position().byte() as usize;
+            let start = bytes[from..].iter().position(|b| !b"\r\n".contains(b)).map_or(bytes.len(), |i| from + i);
+            breaks += bytes[counted..start].iter().filter(|&&b| b == b'\n').count();
+            counted = start;
+            let line = self.line + breaks + 1;
+
+            match reader.read_byte_record(&mut record) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => return Err((line, api_error(StatusCode::BAD_REQUEST, format!("Malformed CSV: {}", e)))),
+            }
+            let end = reader.position().byte() as usize;
+            if !last && end >= bytes.len() {
+                // The record may go on in the next chunk (e.g. inside a quoted field)
+                break;
+            }
+            used = end;
+            if record.iter().all(|field| field.trim_ascii().is_empty()) {
+                continue;
+            }
+            let record = csv::StringRecord::from_byte_record(record.clone());
+            match (self.columns, record) {
+                (None, record) => {
+                    let header = record.map_err(|_| csv_header_error());
+                    self.columns = Some(header.and_then(|h| csv_header(&h)).map_err(|e| (line, e))?);
+                }
+                (Some(_), Err(_)) => rows.push((line, Err("Line is not valid UTF-8".to_string()))),
+                (Some(columns), Ok(record)) => rows.push((line, csv_row(&record, columns).and_then(new_user))),
+            }
+        }
+        self.line += bytes[..used].iter().filter(|&&b| b == b'\n').count();
+        Ok((rows, used))
+    }
+
+    // Store the valid rows whose emails are free, with one log write
+    async fn apply(&mut self, state: &AppState, rows: Vec<Row>) -> Result<(), Failure> {
+        let Some(&(first_line, _)) = rows.first() else { return Ok(()) };
+        // 🏠 Ownership: the users move into the store; the report keeps what it needs
+        let mut users = Vec::new();
+        let mut outcomes = Vec::new();
+        for (line, row) in rows {
+            match row {
+                Ok(user) => {
+                    outcomes.push((line, Ok((user.id, user.email.clone()))));
+                    users.push(user);
+                }
+                Err(error) => outcomes.push((line, Err(error))),
+            }
+        }
+        let created = if users.is_empty() {
+            Vec::new()
+        } else {
+            state
+                .write(move |state| state.store.insert_many(users, &mut |changes| state.commit(changes)))
+                .await
+                .map_err(|e| (first_line, e.into()))?
+        };
+        let mut created = created.into_iter();
+        for (line, outcome) in outcomes {
+            let status = match outcome {
+                Err(error) => RowStatus::Invalid { error },
+                Ok((id, _)) if created.next() == Some(true) => RowStatus::Created { id },
+                Ok((_, email)) => RowStatus::Duplicate { email },
+            };
+            self.report.push(line, status);
+        }
+        Ok(())
+    }
+}
+
+// Same checks and normalization as POST /users
+fn new_user(request: CreateUserRequest) -> Result<User, String> {
+    let email = validate_email(&request.email)?.to_string();
+    Ok(User { id: Uuid::new_v4(), name: request.name.trim().to_string(), email })
+}
+
+fn csv_header_error() -> ApiError {
+    api_error(StatusCode::BAD_REQUEST, "CSV header must name a 'name' and an 'email' column")
+}
+
+fn csv_header(header: &csv::StringRecord) -> Result<(usize, usize), ApiError> {
+    let column = |wanted: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(wanted));
+    Ok((column("name").ok_or_else(csv_header_error)?, column("email").ok_or_else(csv_header_error)?))
+}
+
+fn csv_row(record: &csv::StringRecord, (name, email): (usize, usize)) -> Result<CreateUserRequest, String> {
+    let field = |i: usize, column: &str| record.get(i).map(str::to_string).ok_or(format!("Missing {} column", column));
+    Ok(CreateUserRequest { name: field(name, "name")?, email: field(email, "email")? })
+}
+
+// POST /users/import — CSV (with a header row) or NDJSON, by Content-Type
+pub async fn import_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<(StatusCode, Json<ImportReport>), ApiError> {
+    let format = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(Format::from_mime)
+        .ok_or_else(|| {
+            api_error(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Send Content-Type: text/csv or application/x-ndjson")
+        })?;
+
+    let mut importer = Importer::new(format);
+    if let Err((line, (status, Json(error)))) = importer.run(&state, body).await {
+        // Earlier chunks are already stored, so answer with what was imported and where it stopped
+        importer.report.error = Some(error.error);
+        importer.report.failed_line = Some(line);
+        return Ok((status, Json(importer.report)));
+    }
+    Ok((StatusCode::OK, Json(importer.report)))
+}
+
+// GET /users/export — NDJSON by default, CSV with Accept: text/csv
+pub async fn export_users(State(state): State<AppState>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let accept = headers.get(ACCEPT).and_then(|v| v.to_str().ok());
+    let format = Format::from_accept(accept)
+        .ok_or_else(|| api_error(StatusCode::NOT_ACCEPTABLE, "Accept text/csv or application/x-ndjson"))?;
+
+    // 👥 Borrowing: only the ids are copied while walking the store
+    let mut ids: Vec<Uuid> = Vec::with_capacity(state.store.len());
+    state.store.for_each(&mut |u| ids.push(u.id));
+    ids.sort_unstable();
+
+    // Users deleted while the export runs are skipped; changed ones are exported as they are then
+    let header = (format == Format::Csv).then(|| Ok(b"id,name,email\n".to_vec()));
+    let chunks: Vec<Vec<Uuid>> = ids.chunks(EXPORT_CHUNK).map(<[Uuid]>::to_vec).collect();
+    let rows = stream::iter(chunks).map(move |chunk| render_chunk(&state, format, &chunk));
+    let body = Body::from_stream(stream::iter(header).chain(rows));
+
+    let file_name = match format {
+        Format::Csv => "users.csv",
+        Format::Ndjson => "users.ndjson",
+    };
+    let disposition = format!("attachment; filename=\"{}\"", file_name);
+    Ok(([(CONTENT_TYPE, format.content_type().to_string()), (CONTENT_DISPOSITION, disposition)], body).into_response())
+}
+
+fn render_chunk(state: &AppState, format: Format, ids: &[Uuid]) -> io::Result<Vec<u8>> {
+    let users = ids.iter().filter_map(|id| state.store.get(id));
+    match format {
+        Format::Ndjson => {
+            let mut out = Vec::new();
+            for user in users {
+                serde_json::to_writer(&mut out, &user)?;
+                out.push(b'\n');
+            }
+            Ok(out)
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for user in users {
+                writer.write_record([user.id.to_string().as_str(), &user.name, &user.email])?;
+            }
+            writer.into_inner().map_err(|e| io::Error::other(e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::http::HeaderValue;
+
+    use super::*;
+    use crate::store::ShardedStore;
+
+    fn state() -> AppState {
+        AppState::new(Arc::new(ShardedStore::default()), None)
+    }
+
+    // Import a body that arrives as the given chunks
+    async fn import(state: &AppState, content_type: &str, chunks: Vec<io::Result<String>>) -> (StatusCode, serde_json::Value) {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str(content_type).unwrap());
+        let body = Body::from_stream(stream::iter(chunks));
+        let (status, Json(report)) = import_users(State(state.clone()), headers, body).await.unwrap();
+        (status, serde_json::to_value(report).unwrap())
+    }
+
+    fn statuses(report: &serde_json::Value) -> Vec<(u64, String)> {
+        let rows = report["rows"].as_array().unwrap();
+        rows.iter().map(|r| (r["line"].as_u64().unwrap(), r["status"].as_str().unwrap().to_string())).collect()
+    }
+
+    #[tokio::test]
+    async fn quoted_commas_and_line_breaks_survive_any_chunking() {
+        let csv = "name,email\r\n\"Builder, Bob\",bob@x.io\r\n\"Ada\r\nLovelace\",ada@x.io\n\"Say \"\"hi\"\"\",hi@x.io";
+        // One byte per chunk puts a boundary inside every quoted field and every CRLF
+        for size in [1, 3, csv.len()] {
+            let state = state();
+            let chunks = csv.as_bytes().chunks(size).map(|c| Ok(String::from_utf8_lossy(c).into_owned())).collect();
+            let (status, report) = import(&state, "text/csv", chunks).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(statuses(&report), [(2, "created".into()), (3, "created".into()), (5, "created".into())], "chunks of {}", size);
+
+            let mut names = Vec::new();
+            state.store.for_each(&mut |u| names.push(u.name.clone()));
+            names.sort();
+            assert_eq!(names, ["Ada\r\nLovelace", "Builder, Bob", "Say \"hi\""]);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_bad_row_is_reported_and_the_rest_imported() {
+        let state = state();
+        let csv = "email,name\nada@x.io,Ada\nno-at-sign,Eve\nonly-one-column\n\n\"bob@x.io\",\"Bob\"\n";
+        let (status, report) = import(&state, "text/csv", vec![Ok(csv.to_string())]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((report["created"].as_u64(), report["invalid"].as_u64()), (Some(2), Some(2)));
+        assert_eq!(report["rows"][1]["error"], "Email must contain '@'");
+        assert_eq!(report["rows"][2]["error"], "Missing name column");
+        let lines: Vec<u64> = statuses(&report).into_iter().map(|(line, _)| line).collect();
+        assert_eq!(lines, [2, 3, 4, 6]);
+        assert!(report.get("error").is_none());
+
+        let (status, report) = import(&state, "text/csv", vec![Ok("nombre,correo\nAda,ada@x.io\n".into())]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(report["failed_line"], 1);
+        assert!(report["rows"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_failure_mid_stream_still_reports_what_was_stored() {
+        let state = state();
+        let chunks = vec![
+            Ok("{\"name\":\"Ada\",\"email\":\"ada@x.io\"}\n{\"name\":\"Bob\",\"email\":\"bob@x.io\"}\n".to_string()),
+            Ok("{\"name\":\"Carol\",".to_string()),
+            Err(io::Error::other("connection reset")),
+        ];
+        let (status, report) = import(&state, "application/x-ndjson", chunks).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(report["created"], 2);
+        assert_eq!(statuses(&report), [(1, "created".into()), (2, "created".into())]);
+        assert_eq!(report["error"], "Failed to read request body");
+        assert_eq!(report["failed_line"], 3);
+        assert_eq!(state.store.len(), 2);
+
+        // A runaway record stops the import the same way
+        let long = format!("\"{}", "x".repeat(MAX_LINE_BYTES + 1));
+        let chunks = vec![Ok("name,email\nDan,dan@x.io\n".to_string()), Ok(long)];
+        let (status, report) = import(&state, "text/csv", chunks).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(report["created"], 1);
+        assert_eq!(report["failed_line"], 3);
+    }
+}
@@ -0,0 +1,293 @@
+// Live feed of user changes, served over Server-Sent Events and WebSocket.
+//
+// Events are published from the store's commit hook, while it holds the locks for the
+// change, so event ids follow the order of the writes to each user. Every event goes to a
+// broadcast channel and into a bounded ring buffer.
+// A client that reconnects with the last id it saw gets the buffered events after it, then
+// the live ones. If it is too far behind (or the id is from before a restart) it gets a
+// `reset` instead and should refetch GET /users.
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch};
+
+use crate::{AppState, User};
+
+// Events kept for resuming, and the depth of the broadcast channel
+pub const EVENT_BUFFER: usize = 1024;
+// Sent in place of events that are no longer buffered
+const RESET: &str = r#"{"type":"reset"}"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl EventKind {
+    fn name(self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Updated => "updated",
+            EventKind::Deleted => "deleted",
+        }
+    }
+}
+
+// e.g. {"id":7,"type":"deleted","user":{...}} (a deleted user as it was)
+#[derive(Debug, Clone, Serialize)]
+pub struct UserEvent {
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub kind: EventKind,
+    pub user: User,
+}
+
+struct Ring {
+    next_id: u64,
+    buffer: VecDeque<UserEvent>,
+    capacity: usize,
+}
+
+// Cheap to clone: every clone shares the same ring, channel and shutdown flag
+#[derive(Clone)]
+pub struct Events {
+    ring: Arc<Mutex<Ring>>,
+    sender: broadcast::Sender<UserEvent>,
+    closed: Arc<watch::Sender<bool>>,
+}
+
+pub struct Subscription {
+    // The resume point is no longer buffered: send a reset first
+    pub missed: bool,
+    // Id of the newest event so far (0 before the first); where a reset client resumes from
+    pub latest: u64,
+    pub backlog: Vec<UserEvent>,
+    pub receiver: broadcast::Receiver<UserEvent>,
+}
+
+impl Events {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        let ring = Ring { next_id: 1, buffer: VecDeque::with_capacity(capacity), capacity };
+        Self { ring: Arc::new(Mutex::new(ring)), sender, closed: Arc::new(watch::Sender::new(false)) }
+    }
+
+    pub fn publish(&self, kind: EventKind, user: &User) {
+        // The ring is only ever pushed to, so it is still usable after a panic elsewhere
+        let mut ring = self.ring.lock().unwrap_or_else(PoisonError::into_inner);
+        let event = UserEvent { id: ring.next_id, kind, user: user.clone() };
+        ring.next_id += 1;
+        if ring.buffer.len() == ring.capacity {
+            ring.buffer.pop_front();
+        }
+        ring.buffer.push_back(event.clone());
+        // No subscribers is fine
+        let _ = self.sender.send(event);
+    }
+
+    // Events after `last_id`, then live ones. Subscribing under the ring lock means no event
+    // is both in the backlog and on the channel, and none falls between them.
+    pub fn subscribe(&self, last_id: Option<u64>) -> Subscription {
+        let ring = self.ring.lock().unwrap_or_else(PoisonError::into_inner);
+        let receiver = self.sender.subscribe();
+        let latest = ring.next_id - 1;
+        let Some(last_id) = last_id else {
+            return Subscription { missed: false, latest, backlog: Vec::new(), receiver };
+        };
+        let oldest = ring.buffer.front().map_or(ring.next_id, |e| e.id);
+        // The id comes from the client; checking it against latest first keeps the + 1 from overflowing
+        if last_id > latest || last_id + 1 < oldest {
+            return Subscription { missed: true, latest, backlog: Vec::new(), receiver };
+        }
+        let backlog = ring.buffer.iter().filter(|e| e.id > last_id).cloned().collect();
+        Subscription { missed: false, latest, backlog, receiver }
+    }
+
+    // End every open stream so graceful shutdown doesn't wait on them forever
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    fn closed(&self) -> watch::Receiver<bool> {
+        self.closed.subscribe()
+    }
+}
+
+// Browsers can't set headers on a WebSocket, so the resume point can also be a query value
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    last_event_id: Option<u64>,
+}
+
+// Live events until the channel lags or the server shuts down
+fn live(receiver: broadcast::Receiver<UserEvent>, closed: watch::Receiver<bool>) -> impl Stream<Item = UserEvent> {
+    stream::unfold((receiver, closed), |(mut receiver, mut closed)| async move {
+        let event = tokio::select! {
+            event = receiver.recv() => event.ok(),
+            _ = shutdown(&mut closed) => None,
+        };
+        event.map(|event| (event, (receiver, closed)))
+    })
+}
+
+async fn shutdown(closed: &mut watch::Receiver<bool>) {
+    let _ = closed.wait_for(|closed| *closed).await;
+}
+
+fn sse_event(event: &UserEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(event.kind.name())
+        .json_data(event)
+        .expect("events serialize")
+}
+
+// GET /users/events — SSE; resume with the Last-Event-ID header (or ?last_event_id=)
+pub async fn sse_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let last_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .or(query.last_event_id);
+    let subscription = state.events.subscribe(last_id);
+
+    // The reset carries the latest id so a reconnect after it doesn't reset again
+    let reset = subscription
+        .missed
+        .then(|| Event::default().id(subscription.latest.to_string()).event("reset").data(RESET));
+    let backlog = subscription.backlog.iter().map(sse_event).collect::<Vec<_>>();
+    // When the channel lags the stream ends; EventSource reconnects with Last-Event-ID and
+    // picks up the missed events from the buffer
+    let live = live(subscription.receiver, state.events.closed()).map(|event| sse_event(&event));
+    let events = stream::iter(reset).chain(stream::iter(backlog)).chain(live).map(Ok);
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+// GET /ws — the same events as JSON text messages; resume with ?last_event_id=
+pub async fn ws_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let subscription = state.events.subscribe(query.last_event_id);
+    let closed = state.events.closed();
+    upgrade.on_upgrade(move |socket| ws_session(socket, subscription, closed))
+}
+
+async fn ws_session(mut socket: WebSocket, subscription: Subscription, mut closed: watch::Receiver<bool>) {
+    let Subscription { missed, backlog, mut receiver, .. } = subscription;
+    if missed && socket.send(Message::Text(RESET.into())).await.is_err() {
+        return;
+    }
+    for event in backlog {
+        if socket.send(ws_message(&event)).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let message = match event {
+                    Ok(event) => ws_message(&event),
+                    // Too slow to keep up: events were dropped, the client should refetch
+                    Err(broadcast::error::RecvError::Lagged(_)) => Message::Text(RESET.into()),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+            // Only closes matter; the socket answers pings itself
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = shutdown(&mut closed) => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+        }
+    }
+}
+
+fn ws_message(event: &UserEvent) -> Message {
+    Message::Text(serde_json::to_string(event).expect("events serialize"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn user() -> User {
+        User { id: Uuid::new_v4(), name: "Ada".into(), email: "ada@x.io".into() }
+    }
+
+    #[test]
+    fn resumes_from_the_buffer_and_resets_when_too_far_behind() {
+        let events = Events::new(3);
+        let ada = user();
+        for kind in [EventKind::Created, EventKind::Updated, EventKind::Updated, EventKind::Deleted] {
+            events.publish(kind, &ada);
+        }
+        // Buffer now holds 2..=4
+        let ids = |s: &Subscription| s.backlog.iter().map(|e| e.id).collect::<Vec<_>>();
+        let resumed = events.subscribe(Some(2));
+        assert!(!resumed.missed);
+        assert_eq!(ids(&resumed), [3, 4]);
+        assert_eq!(ids(&events.subscribe(Some(1))), [2, 3, 4]);
+        assert!(events.subscribe(Some(4)).backlog.is_empty());
+
+        // Event 1 was evicted, and 9 is from a previous run of the server
+        assert!(events.subscribe(Some(0)).missed);
+        assert!(events.subscribe(Some(9)).missed);
+        assert!(!events.subscribe(None).missed);
+    }
+
+    #[test]
+    fn an_id_past_the_end_of_u64_resets_instead_of_overflowing() {
+        let events = Events::new(3);
+        events.publish(EventKind::Created, &user());
+        let subscription = events.subscribe(Some(u64::MAX));
+        assert!(subscription.missed);
+        assert!(subscription.backlog.is_empty());
+    }
+
+    #[tokio::test]
+    async fn backlog_and_live_events_neither_overlap_nor_leave_gaps() {
+        let events = Events::new(8);
+        let ada = user();
+        events.publish(EventKind::Created, &ada);
+        let mut subscription = events.subscribe(Some(0));
+        events.publish(EventKind::Deleted, &ada);
+
+        assert_eq!(subscription.backlog.iter().map(|e| e.id).collect::<Vec<_>>(), [1]);
+        let next = subscription.receiver.recv().await.unwrap();
+        assert_eq!((next.id, next.kind), (2, EventKind::Deleted));
+        assert!(subscription.receiver.try_recv().is_err());
+    }
+}
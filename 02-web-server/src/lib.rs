@@ -0,0 +1,646 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{HeaderName, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+mod bulk;
+mod events;
+pub mod persistence;
+pub mod store;
+
+use events::{EventKind, Events, EVENT_BUFFER};
+use persistence::{Mutation, Wal};
+use store::{Change, UserStore};
+
+// Types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CreateUserRequest {
+    name: String,
+    email: String,
+}
+
+// PATCH body: only the fields present are changed
+#[derive(Debug, Clone, Deserialize)]
+struct PatchUserRequest {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+// GET /users?page=&per_page=&q=
+#[derive(Debug, Deserialize)]
+struct ListUsersQuery {
+    page: Option<u32>,
+    per_page: Option<u32>,
+    q: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Paginated<T> {
+    items: Vec<T>,
+    page: u32,
+    per_page: u32,
+    total: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    error: String,
+    // Machine-readable details, only sent for errors a client can act on (e.g. 409)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<&'static str>,
+}
+
+pub type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn api_error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(ErrorResponse { error: message.into(), code: None, field: None }))
+}
+
+fn email_taken() -> ApiError {
+    (
+        StatusCode::CONFLICT,
+        Json(ErrorResponse {
+            error: "A user with this email already exists".into(),
+            code: Some("email_taken"),
+            field: Some("email"),
+        }),
+    )
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+// How often the write-ahead log is folded into the snapshot
+const COMPACT_INTERVAL: Duration = Duration::from_secs(30);
+static REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+// Shared state (immutably referenced across handlers)
+#[derive(Clone)]
+pub struct AppState {
+    store: Arc<dyn UserStore>,
+    // None when USERS_DATA_DIR is unset: users live in memory only
+    wal: Option<Arc<Mutex<Wal>>>,
+    // 📣 Change feed for /users/events and /ws
+    events: Events,
+}
+
+impl AppState {
+    pub fn new(store: Arc<dyn UserStore>, wal: Option<Wal>) -> Self {
+        Self { store, wal: wal.map(|wal| Arc::new(Mutex::new(wal))), events: Events::new(EVENT_BUFFER) }
+    }
+
+    // End the event streams, which would otherwise hold up graceful shutdown forever
+    pub fn close_streams(&self) {
+        self.events.close();
+    }
+
+    // ✍️ Write-ahead: the store calls this while it holds the locks for the change and
+    // before applying it, so the log and the feed see each user's changes in order and a
+    // failed log write leaves the store untouched
+    fn commit(&self, changes: &[Change]) -> Result<(), ApiError> {
+        if let Some(wal) = &self.wal {
+            let mutations: Vec<Mutation> = changes.iter().map(Mutation::from).collect();
+            // The log is append-only, so it is still usable after a panic elsewhere
+            let mut wal = wal.lock().unwrap_or_else(PoisonError::into_inner);
+            wal.append(&mutations).map_err(|e| {
+                eprintln!("⚠️  Failed to append to write-ahead log: {}", e);
+                api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to persist change")
+            })?;
+        }
+        for change in changes {
+            match change {
+                Change::Created(user) => self.events.publish(EventKind::Created, user),
+                Change::Updated(user) => self.events.publish(EventKind::Updated, user),
+                Change::Deleted(user) => self.events.publish(EventKind::Deleted, user),
+            }
+        }
+        Ok(())
+    }
+
+    // 💾 With the log on, a write fsyncs inside the commit hook while the store holds its
+    // locks, so it runs on the blocking pool: a slow disk then holds up that write and the
+    // requests that need the same shards, but not a runtime worker thread. Without the log
+    // a write never touches the disk and runs in place.
+    async fn write<T, E>(&self, write: impl FnOnce(&AppState) -> Result<T, E> + Send + 'static) -> Result<T, E>
+    where
+        T: Send + 'static,
+        E: Send + 'static,
+    {
+        if self.wal.is_none() {
+            return write(self);
+        }
+        let state = self.clone();
+        tokio::task::spawn_blocking(move || write(&state))
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+}
+
+// Access log + X-Request-Id: reuse the caller's id when it sent a sensible one, else make one
+async fn access_log(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let id = request
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_valid_request_id(v))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID.clone(), value);
+    }
+    println!(
+        "📝 {} {} {} {:.1}ms request_id={}",
+        method,
+        path,
+        response.status().as_u16(),
+        started.elapsed().as_secs_f64() * 1000.0,
+        id
+    );
+    response
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b))
+}
+
+pub fn app(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route("/health", get(health))
+        .route("/users", get(list_users).post(create_user))
+        .route("/users/import", post(bulk::import_users))
+        .route("/users/export", get(bulk::export_users))
+        .route("/users/events", get(events::sse_handler))
+        .route("/ws", get(events::ws_handler))
+        .route("/users/:id", get(get_user).put(replace_user).patch(patch_user).delete(delete_user))
+        .layer(middleware::from_fn(access_log))
+        .with_state(state)
+}
+
+// GET /health
+async fn health() -> impl IntoResponse {
+    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+}
+
+// GET /
+async fn index() -> impl IntoResponse {
+    (StatusCode::OK, Json(serde_json::json!({
+        "message": "Rust Web Server 01",
+        "endpoints": [
+            "/health",
+            "/users (GET ?page=&per_page=&q=, POST)",
+            "/users/<id> (GET, PUT, PATCH, DELETE)",
+            "/users/import (POST text/csv or application/x-ndjson)",
+            "/users/export (GET, Accept: text/csv or application/x-ndjson)",
+            "/users/events (GET, Server-Sent Events)",
+            "/ws (WebSocket change feed)"
+        ]
+    })))
+}
+
+// POST /users
+async fn create_user(
+    State(state): State<AppState>,
+    Json(request): Json<CreateUserRequest>, // 🏠 Ownership: take ownership of request body
+) -> Result<impl IntoResponse, ApiError> {
+    // 👥 Borrowing: validate via &str
+    let email = validate_email(&request.email).map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+
+    // 🔄 Shadowing: transform request fields into final types
+    let id = Uuid::new_v4(); // generate ID
+
+    // maybe trim name as a transform
+    let name = request.name.trim().to_string();
+
+    let user = User {
+        id,
+        name,
+        email: email.to_string(),
+    };
+
+    // 🏠 Ownership: the store gets its own copy; it checks the email and inserts under the
+    // same lock so two requests can't both claim it
+    let stored = user.clone();
+    state.write(move |state| state.store.insert(stored, &mut |changes| state.commit(changes))).await?;
+
+    // 🏠 Ownership: move user to response
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+// GET /users?page=1&per_page=20&q=ada
+async fn list_users(
+    State(state): State<AppState>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    // 🔄 Shadowing: optional query values become clamped, concrete ones
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let needle = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()).map(str::to_lowercase);
+
+    // 👥 Borrowing: filter by reference under the store's locks, clone only the matches
+    let mut users: Vec<User> = Vec::new();
+    state.store.for_each(&mut |u| {
+        let matches = match &needle {
+            Some(q) => u.name.to_lowercase().contains(q) || u.email.to_lowercase().contains(q),
+            None => true,
+        };
+        if matches {
+            users.push(u.clone());
+        }
+    });
+    // HashMap order is arbitrary; sort so pages are stable between requests
+    users.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+
+    let total = users.len();
+    let start = ((page - 1) as usize).saturating_mul(per_page as usize).min(total);
+    let items: Vec<User> = users.into_iter().skip(start).take(per_page as usize).collect();
+    Ok(Json(Paginated { items, page, per_page, total }))
+}
+
+// GET /users/:id
+async fn get_user(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    // 🔄 Shadowing: String -> Uuid
+    let id = parse_id(&id)?;
+
+    // The store only holds its lock while it clones the user
+    match state.store.get(&id) {
+        Some(user) => Ok((StatusCode::OK, Json(user))),
+        None => Err(api_error(StatusCode::NOT_FOUND, "User not found")),
+    }
+}
+
+// PUT /users/:id — replace name and email
+async fn replace_user(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<CreateUserRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_id(&id)?;
+    let email = validate_email(&request.email).map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+    let name = request.name.trim().to_string();
+
+    let email = email.to_string();
+    let user = state
+        .write(move |state| {
+            let replace = |user: &mut User| {
+                user.name = name.clone();
+                user.email = email.clone();
+            };
+            state.store.update(&id, &replace, &mut |changes| state.commit(changes))
+        })
+        .await?;
+    Ok((StatusCode::OK, Json(user)))
+}
+
+// PATCH /users/:id — change only the fields sent
+async fn patch_user(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<PatchUserRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_id(&id)?;
+    // Validate before taking the lock so a bad request never touches the store
+    let email = match &request.email {
+        Some(email) => Some(validate_email(email).map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?.to_string()),
+        None => None,
+    };
+    let name = request.name.map(|n| n.trim().to_string());
+
+    let user = state
+        .write(move |state| {
+            // 👥 Borrowing: the edit borrows the new values instead of moving them into the store
+            let patch = |user: &mut User| {
+                if let Some(name) = &name {
+                    user.name = name.clone();
+                }
+                if let Some(email) = &email {
+                    user.email = email.clone();
+                }
+            };
+            state.store.update(&id, &patch, &mut |changes| state.commit(changes))
+        })
+        .await?;
+    Ok((StatusCode::OK, Json(user)))
+}
+
+// DELETE /users/:id
+async fn delete_user(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = parse_id(&id)?;
+    state.write(move |state| state.store.remove(&id, &mut |changes| state.commit(changes))).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Fold the write-ahead log into a fresh snapshot every COMPACT_INTERVAL
+pub async fn compact_periodically(state: AppState) {
+    let mut ticker = tokio::time::interval(COMPACT_INTERVAL);
+    loop {
+        ticker.tick().await;
+        // Writing the snapshot holds every shard and fsyncs, so keep it off the runtime's workers
+        let snapshot = state.clone();
+        let result = tokio::task::spawn_blocking(move || compact(&snapshot)).await.unwrap_or_else(|e| Err(e.to_string()));
+        if let Err(e) = result {
+            eprintln!("⚠️  Compaction failed (the log is kept): {}", e);
+        }
+    }
+}
+
+pub fn compact(state: &AppState) -> Result<(), String> {
+    let Some(wal) = &state.wal else { return Ok(()) };
+    let lock_wal = || wal.lock().unwrap_or_else(PoisonError::into_inner);
+    if lock_wal().pending() == 0 {
+        return Ok(());
+    }
+    // Same lock order as a write (store, then log). While the store holds every shard, no
+    // change can commit, so the snapshot has exactly what the log had.
+    let mut result = Ok(());
+    state.store.with_all(&mut |users| result = lock_wal().compact(users).map_err(|e| e.to_string()));
+    result
+}
+
+fn parse_id(id: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(id).map_err(|_| api_error(StatusCode::BAD_REQUEST, "Invalid UUID"))
+}
+
+// Validation using borrowing and Result
+fn validate_email(email: &str) -> Result<&str, String> {
+    // Very basic validation for demo
+    if email.trim().is_empty() {
+        return Err("Email cannot be empty".into());
+    }
+    if !email.contains('@') {
+        return Err("Email must contain '@'".into());
+    }
+    Ok(email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::util::ServiceExt; // for `oneshot`
+
+    fn test_app() -> Router {
+        app(AppState::new(Arc::new(store::ShardedStore::default()), None))
+    }
+
+    async fn send(app: &Router, method: &str, uri: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn listing_pages_and_searches_case_insensitively() {
+        let app = test_app();
+        for (name, email) in [("Ada", "ada@x.io"), ("bob", "bob@x.io"), ("Carol", "c@Lovelace.org"), ("Dan", "dan@x.io"), ("Eve", "eve@x.io")] {
+            send(&app, "POST", "/users", serde_json::json!({"name": name, "email": email})).await;
+        }
+        let names = |page: &serde_json::Value| -> Vec<String> {
+            page["items"].as_array().unwrap().iter().map(|u| u["name"].as_str().unwrap().to_string()).collect()
+        };
+
+        let (status, page) = send(&app, "GET", "/users?page=1&per_page=2", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((page["total"].as_u64(), page["page"].as_u64(), page["per_page"].as_u64()), (Some(5), Some(1), Some(2)));
+        assert_eq!(names(&page), ["Ada", "Carol"]);
+        let (_, page) = send(&app, "GET", "/users?page=3&per_page=2", serde_json::Value::Null).await;
+        assert_eq!(names(&page), ["bob"]);
+        // Past the end: an empty page that still reports the total
+        let (_, page) = send(&app, "GET", "/users?page=4&per_page=2", serde_json::Value::Null).await;
+        assert!(names(&page).is_empty());
+        assert_eq!(page["total"], 5);
+
+        // Out-of-range values are clamped instead of rejected
+        let (_, page) = send(&app, "GET", "/users?page=0&per_page=0", serde_json::Value::Null).await;
+        assert_eq!((page["page"].as_u64(), page["per_page"].as_u64()), (Some(1), Some(1)));
+        assert_eq!(names(&page), ["Ada"]);
+        let (_, page) = send(&app, "GET", &format!("/users?per_page={}", MAX_PAGE_SIZE + 1), serde_json::Value::Null).await;
+        assert_eq!(page["per_page"], MAX_PAGE_SIZE);
+        let (_, page) = send(&app, "GET", "/users", serde_json::Value::Null).await;
+        assert_eq!(page["per_page"], DEFAULT_PAGE_SIZE);
+
+        // q matches the name or the email, ignoring case and surrounding spaces
+        let (_, page) = send(&app, "GET", "/users?q=BOB", serde_json::Value::Null).await;
+        assert_eq!(names(&page), ["bob"]);
+        let (_, page) = send(&app, "GET", "/users?q=%20lovelace%20", serde_json::Value::Null).await;
+        assert_eq!(names(&page), ["Carol"]);
+        let (_, page) = send(&app, "GET", "/users?q=X.IO&per_page=2&page=2", serde_json::Value::Null).await;
+        assert_eq!(page["total"], 4);
+        assert_eq!(names(&page), ["Eve", "bob"]);
+        let (_, page) = send(&app, "GET", "/users?q=%20", serde_json::Value::Null).await;
+        assert_eq!(page["total"], 5);
+    }
+
+    #[tokio::test]
+    async fn updates_validate_the_email_and_deleted_users_are_gone() {
+        let app = test_app();
+        let (_, user) = send(&app, "POST", "/users", serde_json::json!({"name": "Ada", "email": "ada@x.io"})).await;
+        let uri = format!("/users/{}", user["id"].as_str().unwrap());
+
+        for (method, body) in [("PUT", serde_json::json!({"name": "Ada", "email": "no-at-sign"})), ("PATCH", serde_json::json!({"email": "  "}))] {
+            let (status, error) = send(&app, method, &uri, body).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", method);
+            assert!(error["error"].as_str().unwrap().starts_with("Email"), "{}: {}", method, error);
+            assert!(error.get("code").is_none());
+        }
+        let (_, unchanged) = send(&app, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(unchanged["email"], "ada@x.io");
+
+        let (status, patched) = send(&app, "PATCH", &uri, serde_json::json!({"name": "  Ada L. "})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((patched["name"].as_str(), patched["email"].as_str()), (Some("Ada L."), Some("ada@x.io")));
+
+        let (status, _) = send(&app, "DELETE", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, error) = send(&app, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["error"], "User not found");
+        let (status, _) = send(&app, "DELETE", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn import_reports_every_row_and_export_streams_it_back() {
+        let app = test_app();
+        send(&app, "POST", "/users", serde_json::json!({"name": "Ada", "email": "ada@x.io"})).await;
+
+        let csv = "Email,Name,team\r\nbob@x.io,\"Builder, Bob\",a\n\nbad-email,Eve,b\nADA@x.io,Ada again,c\nbob@X.io,Bob twice,d\ncarol@x.io,Carol";
+        let request = Request::post("/users/import").header("content-type", "text/csv").body(Body::from(csv)).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!((report["created"].as_u64(), report["duplicates"].as_u64(), report["invalid"].as_u64()), (Some(2), Some(2), Some(1)));
+        let statuses: Vec<(u64, &str)> =
+            report["rows"].as_array().unwrap().iter().map(|r| (r["line"].as_u64().unwrap(), r["status"].as_str().unwrap())).collect();
+        assert_eq!(statuses, [(2, "created"), (4, "invalid"), (5, "duplicate"), (6, "duplicate"), (7, "created")]);
+
+        let request = Request::post("/users/import").header("content-type", "application/x-ndjson");
+        let ndjson = "{\"name\":\"Dan\",\"email\":\"dan@x.io\"}\n{\"name\":\"No email\"}\n";
+        let response = app.clone().oneshot(request.body(Body::from(ndjson)).unwrap()).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(report["rows"][1]["status"], "invalid");
+
+        let request = Request::get("/users/export").header("accept", "text/csv").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/csv; charset=utf-8");
+        let bytes = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(text.starts_with("id,name,email\n"));
+        assert!(text.contains(",\"Builder, Bob\",bob@x.io\n"));
+        assert_eq!(text.lines().count(), 5);
+
+        let request = Request::get("/users/export").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let users: Vec<User> = bytes.split(|&b| b == b'\n').filter(|l| !l.is_empty()).map(|l| serde_json::from_slice(l).unwrap()).collect();
+        assert_eq!(users.len(), 4);
+
+        let request = Request::post("/users/import").header("content-type", "application/json").body(Body::empty()).unwrap();
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn responses_carry_a_request_id() {
+        let app = test_app();
+        let request = |id: &str| Request::get("/health").header("x-request-id", id).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(request("test-api-1712-3")).await.unwrap();
+        assert_eq!(response.headers()["x-request-id"], "test-api-1712-3");
+
+        // Unusable ids are replaced by a fresh one
+        let response = app.clone().oneshot(request("has spaces")).await.unwrap();
+        let id = response.headers()["x-request-id"].to_str().unwrap();
+        assert!(Uuid::parse_str(id).is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn parallel_creates_with_the_same_email_admit_exactly_one() {
+        let app = test_app();
+        let tasks: Vec<_> = (0..32)
+            .map(|i| {
+                let app = app.clone();
+                // Same address, different case
+                let email = if i % 2 == 0 { "Same@Example.com" } else { "same@example.COM" };
+                tokio::spawn(async move { send(&app, "POST", "/users", serde_json::json!({"name": i.to_string(), "email": email})).await })
+            })
+            .collect();
+
+        let mut created = 0;
+        for task in tasks {
+            let (status, body) = task.await.unwrap();
+            match status {
+                StatusCode::CREATED => created += 1,
+                StatusCode::CONFLICT => {
+                    assert_eq!(body["code"], "email_taken");
+                    assert_eq!(body["field"], "email");
+                }
+                other => panic!("unexpected status {}", other),
+            }
+        }
+        assert_eq!(created, 1);
+        let (_, page) = send(&app, "GET", "/users", serde_json::Value::Null).await;
+        assert_eq!(page["total"], 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn updates_keep_the_email_index_consistent() {
+        let app = test_app();
+        let mut ids = Vec::new();
+        for i in 0..8 {
+            let (status, user) = send(&app, "POST", "/users", serde_json::json!({"name": "u", "email": format!("u{}@x.io", i)})).await;
+            assert_eq!(status, StatusCode::CREATED);
+            ids.push(user["id"].as_str().unwrap().to_string());
+        }
+
+        // Eight users race to take the same new address: one wins, the rest keep theirs
+        let tasks: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                let (app, uri) = (app.clone(), format!("/users/{}", id));
+                tokio::spawn(async move { send(&app, "PATCH", &uri, serde_json::json!({"email": "taken@x.io"})).await.0 })
+            })
+            .collect();
+        let mut winners = 0;
+        for task in tasks {
+            match task.await.unwrap() {
+                StatusCode::OK => winners += 1,
+                status => assert_eq!(status, StatusCode::CONFLICT),
+            }
+        }
+        assert_eq!(winners, 1);
+
+        // The winner's old address was released; a user may re-case its own email
+        let (_, page) = send(&app, "GET", "/users?q=taken", serde_json::Value::Null).await;
+        let winner = page["items"][0]["id"].as_str().unwrap().to_string();
+        let freed = ids.iter().position(|id| *id == winner).unwrap();
+        let (status, _) = send(&app, "POST", "/users", serde_json::json!({"name": "n", "email": format!("U{}@X.IO", freed)})).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, _) = send(&app, "PUT", &format!("/users/{}", winner), serde_json::json!({"name": "w", "email": "TAKEN@x.io"})).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Deleting frees the address again
+        let (status, _) = send(&app, "DELETE", &format!("/users/{}", winner), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send(&app, "POST", "/users", serde_json::json!({"name": "n", "email": "taken@x.io"})).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn writes_with_the_log_on_are_logged_and_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let (wal, users) = Wal::open(dir.path()).unwrap();
+        let app = app(AppState::new(Arc::new(store::ShardedStore::from_users(users.into_values())), Some(wal)));
+
+        let (_, ada) = send(&app, "POST", "/users", serde_json::json!({"name": "Ada", "email": "ada@x.io"})).await;
+        let (_, bob) = send(&app, "POST", "/users", serde_json::json!({"name": "Bob", "email": "bob@x.io"})).await;
+        let ada_uri = format!("/users/{}", ada["id"].as_str().unwrap());
+        let (status, _) = send(&app, "PATCH", &ada_uri, serde_json::json!({"name": "Ada L."})).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, "DELETE", &format!("/users/{}", bob["id"].as_str().unwrap()), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        drop(app);
+
+        let (_, users) = Wal::open(dir.path()).unwrap();
+        let names: Vec<&str> = users.values().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["Ada L."]);
+    }
+}
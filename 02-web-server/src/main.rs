@@ -1,140 +1,102 @@
-use std::{collections::HashMap, net::SocketAddr, sync::{Arc, Mutex}};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
-    Json, Router,
+use web_server_01::{
+    app, compact, compact_periodically,
+    persistence::Wal,
+    store::{ShardedStore, UserStore},
+    AppState,
 };
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-// Types
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct User {
-    id: Uuid,
-    name: String,
-    email: String,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-struct CreateUserRequest {
-    name: String,
-    email: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    error: String,
-}
-
-// Shared state (immutably referenced across handlers)
-type Database = Arc<Mutex<HashMap<Uuid, User>>>;
+const DEFAULT_ADDR: &str = "127.0.0.1:3000";
 
 #[tokio::main]
 async fn main() {
     // 🔒 Immutability: config and shared state bindings are immutable
-    let database: Database = Arc::new(Mutex::new(HashMap::new()));
-    let app = Router::new()
-        .route("/", get(index))
-        .route("/health", get(health))
-        .route("/users", post(create_user))
-        .route("/users/:id", get(get_user))
-        .with_state(database);
-
-    // Immutable config for listener address
-    let addr: SocketAddr = "127.0.0.1:3000".parse().expect("valid address");
-    println!("🚀 Server running on http://{}", addr);
-    println!("📋 Try these endpoints:\n   GET  /health\n   POST /users\n   GET  /users/<id>");
+    let addr = listen_addr().unwrap_or_else(|e| {
+        eprintln!("❌ {}\nUsage: web-server-01 [--addr HOST:PORT]   (or set BIND_ADDR)", e);
+        std::process::exit(2);
+    });
+    let data_dir = std::env::var_os("USERS_DATA_DIR").map(PathBuf::from);
+    let state = match &data_dir {
+        Some(dir) => {
+            let (wal, users) = Wal::open(dir).unwrap_or_else(|e| {
+                eprintln!("❌ Failed to load users from USERS_DATA_DIR ({}): {}", dir.display(), e);
+                std::process::exit(1);
+            });
+            let store = ShardedStore::from_users(users.into_values());
+            println!("💾 Persisting users to {} ({} loaded)", dir.display(), store.len());
+            let state = AppState::new(Arc::new(store), Some(wal));
+            tokio::spawn(compact_periodically(state.clone()));
+            state
+        }
+        None => {
+            println!("💾 Users are kept in memory only (set USERS_DATA_DIR to persist them)");
+            AppState::new(Arc::new(ShardedStore::default()), None)
+        }
+    };
+    let app = app(state.clone());
 
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .expect("Failed to bind TCP listener");
-    axum::serve(listener, app).await.expect("server error");
-}
+    // Port 0 picks a free port; print the one we actually got
+    let addr = listener.local_addr().expect("bound address");
+    println!("🚀 Server running on http://{}", addr);
+    println!("📋 Try these endpoints:\n   GET    /health\n   GET    /users?page=1&per_page=20&q=ada\n   POST   /users\n   GET    /users/<id>\n   PUT    /users/<id>\n   PATCH  /users/<id>\n   DELETE /users/<id>\n   POST   /users/import\n   GET    /users/export\n   GET    /users/events (SSE)\n   GET    /ws (WebSocket)");
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown({
+            let state = state.clone();
+            async move {
+                shutdown_signal().await;
+                // Event streams never end on their own
+                state.close_streams();
+            }
+        })
+        .await
+        .expect("server error");
 
-// GET /health
-async fn health() -> impl IntoResponse {
-    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+    // In-flight requests have finished: fold the log so the next start only reads the snapshot
+    if let Err(e) = compact(&state) {
+        eprintln!("⚠️  Final compaction failed (the log is kept): {}", e);
+    }
+    println!("👋 Server stopped");
 }
 
-// GET /
-async fn index() -> impl IntoResponse {
-    (StatusCode::OK, Json(serde_json::json!({
-        "message": "Rust Web Server 01",
-        "endpoints": [
-            "/health",
-            "/users (POST)",
-            "/users/<id> (GET)"
-        ]
-    })))
+// --addr wins over BIND_ADDR, which wins over the default
+fn listen_addr() -> Result<SocketAddr, String> {
+    let mut addr = std::env::var("BIND_ADDR").ok();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = Some(args.next().ok_or("--addr needs a value")?),
+            _ => match arg.strip_prefix("--addr=") {
+                Some(value) => addr = Some(value.to_string()),
+                None => return Err(format!("Unknown argument '{}'", arg)),
+            },
+        }
+    }
+    // 🔄 Shadowing: Option<String> -> SocketAddr
+    let addr = addr.unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    addr.parse().map_err(|_| format!("Invalid listen address '{}' (expected HOST:PORT, e.g. {})", addr, DEFAULT_ADDR))
 }
 
-// POST /users
-async fn create_user(
-    State(database): State<Database>,
-    Json(request): Json<CreateUserRequest>, // 🏠 Ownership: take ownership of request body
-) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
-    // 👥 Borrowing: validate via &str
-    let email = validate_email(&request.email)
-        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })))?;
-
-    // 🔄 Shadowing: transform request fields into final types
-    let id = Uuid::new_v4(); // generate ID
-
-    // maybe trim name as a transform
-    let name = request.name.trim().to_string();
-
-    let user = User {
-        id,
-        name,
-        email: email.to_string(),
+async fn shutdown_signal() {
+    // Wait for either Ctrl+C or a SIGTERM (Unix).
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("failed to install Ctrl+C handler");
     };
 
-    // 👥 Borrowing DB for insertion
-    let mut db = database
-        .lock()
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error: "Failed to lock database".into() })))?;
-
-    // Insert moves user into the DB (ownership transfer inside HashMap)
-    db.insert(user.id, user.clone());
-
-    // 🏠 Ownership: move user to response
-    Ok((StatusCode::CREATED, Json(user)))
-}
-
-// GET /users/:id
-async fn get_user(
-    State(database): State<Database>,
-    Path(id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
-    // 🔄 Shadowing: String -> Uuid
-    let id = Uuid::parse_str(&id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: "Invalid UUID".into() })))?;
-
-    // 👥 Borrowing DB for read (limit lock scope strictly)
-    let user_opt = {
-        let db = database
-            .lock()
-            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error: "Failed to lock database".into() })))?;
-        db.get(&id).cloned()
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = signal(SignalKind::terminate()).expect("failed to install signal handler");
+        term.recv().await;
     };
 
-    match user_opt {
-        Some(user) => Ok((StatusCode::OK, Json(user))),
-        None => Err((StatusCode::NOT_FOUND, Json(ErrorResponse { error: "User not found".into() })))
-    }
-}
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-// Validation using borrowing and Result
-fn validate_email(email: &str) -> Result<&str, String> {
-    // Very basic validation for demo
-    if email.trim().is_empty() {
-        return Err("Email cannot be empty".into());
-    }
-    if !email.contains('@') {
-        return Err("Email must contain '@'".into());
-    }
-    Ok(email)
+    tokio::select! { _ = ctrl_c => {}, _ = terminate => {}, }
+    println!("🛑 Shutdown requested, draining in-flight requests");
 }
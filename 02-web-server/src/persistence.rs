@@ -0,0 +1,296 @@
+// Optional file persistence for the in-memory user map.
+//
+// Every mutation is appended to `users.wal` as one JSON line and fsynced *before* the
+// store applies it, so an acknowledged write survives a crash. Compaction writes the
+// whole map to `users.snapshot.json` (temp file + fsync + atomic rename) and then empties
+// the log. On startup the snapshot is loaded and the log replayed on top of it; replaying an
+// upsert or delete twice is harmless, so a crash between the rename and the truncate is fine.
+//
+// Appends and compactions block on the disk; the server runs them on tokio's blocking pool
+// (see `AppState::write`).
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{store::Change, User};
+
+const SNAPSHOT_FILE: &str = "users.snapshot.json";
+const WAL_FILE: &str = "users.wal";
+
+// One line of the write-ahead log, e.g. {"op":"delete","id":"..."}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Mutation {
+    Upsert(User),
+    Delete { id: Uuid },
+}
+
+impl From<&Change> for Mutation {
+    fn from(change: &Change) -> Self {
+        match change {
+            Change::Created(user) | Change::Updated(user) => Mutation::Upsert(user.clone()),
+            Change::Deleted(user) => Mutation::Delete { id: user.id },
+        }
+    }
+}
+
+impl Mutation {
+    fn apply(self, users: &mut HashMap<Uuid, User>) {
+        match self {
+            Mutation::Upsert(user) => {
+                users.insert(user.id, user);
+            }
+            Mutation::Delete { id } => {
+                users.remove(&id);
+            }
+        }
+    }
+}
+
+pub struct Wal {
+    dir: PathBuf,
+    file: File,
+    // Length of the log up to its last complete record
+    len: u64,
+    // Records appended since the last compaction
+    pending: usize,
+}
+
+impl Wal {
+    // Open (or create) the files in `dir` and rebuild the map they describe
+    pub fn open(dir: &Path) -> io::Result<(Self, HashMap<Uuid, User>)> {
+        fs::create_dir_all(dir)?;
+        let mut users = load_snapshot(&dir.join(SNAPSHOT_FILE))?;
+
+        let path = dir.join(WAL_FILE);
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let (len, pending) = replay(&bytes, &mut users)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+        if len < bytes.len() as u64 {
+            // 🔄 A torn or corrupt tail (a crash mid-append): nothing after it was acknowledged, so drop it
+            let what = if bytes[len as usize..].contains(&b'\n') { "a corrupt tail" } else { "an incomplete final record" };
+            eprintln!("⚠️  {}: dropping {} bytes of {} after byte {}", path.display(), bytes.len() as u64 - len, what, len);
+            file.set_len(len)?;
+            file.sync_all()?;
+        }
+
+        Ok((Self { dir: dir.to_path_buf(), file, len, pending }, users))
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    // Append records with a single fsync (one record per request, a batch per import chunk)
+    pub fn append(&mut self, mutations: &[Mutation]) -> io::Result<()> {
+        if mutations.is_empty() {
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        for mutation in mutations {
+            serde_json::to_writer(&mut lines, mutation)?;
+            lines.push(b'\n');
+        }
+        let written = self.file.write_all(&lines).and_then(|_| self.file.sync_data());
+        if let Err(e) = written {
+            // Cut off a partial record so later appends don't land behind garbage
+            let _ = self.file.set_len(self.len);
+            return Err(e);
+        }
+        self.len += lines.len() as u64;
+        self.pending += mutations.len();
+        Ok(())
+    }
+
+    // Replace the snapshot with `users` and empty the log
+    pub fn compact<'a>(&mut self, users: impl IntoIterator<Item = &'a User>) -> io::Result<()> {
+        let users: Vec<&User> = users.into_iter().collect();
+        let tmp = self.dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+        {
+            let mut file = File::create(&tmp)?;
+            serde_json::to_writer(&mut file, &users)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, self.dir.join(SNAPSHOT_FILE))?;
+        sync_dir(&self.dir)?;
+
+        self.file.set_len(0)?;
+        self.file.sync_all()?;
+        self.len = 0;
+        self.pending = 0;
+        Ok(())
+    }
+}
+
+fn load_snapshot(path: &Path) -> io::Result<HashMap<Uuid, User>> {
+    let users: Vec<User> = match fs::read(path) {
+        // The snapshot is only ever replaced atomically, so a bad one is a real error
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    Ok(users.into_iter().map(|u| (u.id, u)).collect())
+}
+
+// Apply every complete record; returns the byte length they cover and how many there were.
+// Replay stops at a last line without its newline (a torn append) and at a malformed record
+// that no valid record follows (a corrupt tail), leaving the rest to be truncated. A malformed
+// record *before* a valid one is an error: truncating there would throw away acknowledged writes.
+fn replay(bytes: &[u8], users: &mut HashMap<Uuid, User>) -> Result<(u64, usize), String> {
+    let mut len = 0;
+    let mut count = 0;
+    let mut lines = bytes.split_inclusive(|&b| b == b'\n');
+    while let Some(line) = lines.next() {
+        let Some(record) = line.strip_suffix(b"\n") else { break };
+        let mutation = match serde_json::from_slice::<Mutation>(record) {
+            Ok(mutation) => mutation,
+            Err(e) => {
+                let valid_after = lines
+                    .filter_map(|rest| rest.strip_suffix(b"\n"))
+                    .any(|rest| serde_json::from_slice::<Mutation>(rest).is_ok());
+                if valid_after {
+                    return Err(format!("malformed record {} at byte {}: {}", count + 1, len, e));
+                }
+                break;
+            }
+        };
+        mutation.apply(users);
+        len += line.len() as u64;
+        count += 1;
+    }
+    Ok((len, count))
+}
+
+// Make the rename itself durable
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(name: &str) -> User {
+        User { id: Uuid::new_v4(), name: name.to_string(), email: format!("{}@x.io", name.to_lowercase()) }
+    }
+
+    fn append_raw(dir: &Path, bytes: &[u8]) {
+        OpenOptions::new().append(true).open(dir.join(WAL_FILE)).unwrap().write_all(bytes).unwrap();
+    }
+
+    fn wal_len(dir: &Path) -> u64 {
+        fs::metadata(dir.join(WAL_FILE)).unwrap().len()
+    }
+
+    #[test]
+    fn reopening_replays_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ada, bob) = (user("Ada"), user("Bob"));
+        {
+            let (mut wal, users) = Wal::open(dir.path()).unwrap();
+            assert!(users.is_empty());
+            wal.append(&[Mutation::Upsert(ada.clone()), Mutation::Upsert(bob.clone())]).unwrap();
+            wal.append(&[Mutation::Delete { id: ada.id }]).unwrap();
+        }
+        let (wal, users) = Wal::open(dir.path()).unwrap();
+        assert_eq!(wal.pending(), 3);
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[&bob.id].name, "Bob");
+    }
+
+    #[test]
+    fn a_torn_final_record_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let ada = user("Ada");
+        {
+            let (mut wal, _) = Wal::open(dir.path()).unwrap();
+            wal.append(&[Mutation::Upsert(ada.clone())]).unwrap();
+        }
+        let complete = wal_len(dir.path());
+        append_raw(dir.path(), b"{\"op\":\"delete\",\"id\":\"");
+
+        let (mut wal, users) = Wal::open(dir.path()).unwrap();
+        assert!(users.contains_key(&ada.id));
+        assert_eq!(wal_len(dir.path()), complete);
+
+        // Later appends start on a clean line
+        wal.append(&[Mutation::Upsert(user("Bob"))]).unwrap();
+        drop(wal);
+        let (_, users) = Wal::open(dir.path()).unwrap();
+        assert_eq!(users.len(), 2);
+    }
+
+    #[test]
+    fn a_corrupt_tail_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let ada = user("Ada");
+        {
+            let (mut wal, _) = Wal::open(dir.path()).unwrap();
+            wal.append(&[Mutation::Upsert(ada.clone())]).unwrap();
+        }
+        let complete = wal_len(dir.path());
+        // Garbage that happens to end in a newline, then a torn record
+        append_raw(dir.path(), b"{\"op\":\"upsert\",\x00\x00\nnot json\n{\"op\"");
+
+        let (wal, users) = Wal::open(dir.path()).unwrap();
+        assert_eq!(wal.pending(), 1);
+        assert!(users.contains_key(&ada.id));
+        assert_eq!(wal_len(dir.path()), complete);
+    }
+
+    #[test]
+    fn a_malformed_record_mid_log_fails_to_open_and_keeps_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let (mut wal, _) = Wal::open(dir.path()).unwrap();
+            wal.append(&[Mutation::Upsert(user("Ada"))]).unwrap();
+        }
+        // A corrupt record with a valid one after it is not a torn write
+        append_raw(dir.path(), b"not json\n");
+        let bob = serde_json::to_string(&Mutation::Upsert(user("Bob"))).unwrap();
+        append_raw(dir.path(), format!("{}\n", bob).as_bytes());
+        let before = wal_len(dir.path());
+
+        let err = Wal::open(dir.path()).err().expect("corrupt log must not open");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("malformed record 2"), "{}", err);
+        assert_eq!(wal_len(dir.path()), before);
+    }
+
+    #[test]
+    fn compaction_folds_the_log_into_the_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ada, bob, carol) = (user("Ada"), user("Bob"), user("Carol"));
+        {
+            let (mut wal, mut users) = Wal::open(dir.path()).unwrap();
+            let mutations = [Mutation::Upsert(ada.clone()), Mutation::Upsert(bob.clone()), Mutation::Delete { id: ada.id }];
+            wal.append(&mutations).unwrap();
+            for mutation in mutations {
+                mutation.apply(&mut users);
+            }
+            wal.compact(users.values()).unwrap();
+            assert_eq!((wal.pending(), wal_len(dir.path())), (0, 0));
+            wal.append(&[Mutation::Upsert(carol.clone())]).unwrap();
+        }
+        let (wal, users) = Wal::open(dir.path()).unwrap();
+        assert_eq!(wal.pending(), 1);
+        assert_eq!(users.len(), 2);
+        assert!(users.contains_key(&bob.id) && users.contains_key(&carol.id));
+    }
+}
@@ -0,0 +1,440 @@
+// User storage behind a small trait, with two implementations:
+// - `ShardedStore` (used by the server): users and the email index are split over
+//   `RwLock`ed shards, so requests for different users rarely wait on each other and
+//   reads never wait on other reads.
+// - `MutexStore`: the original single `Mutex` over both maps, kept as the baseline for
+//   `benches/store.rs`.
+//
+// Writes go through a commit hook, called while the store holds the locks for the change
+// and before it is applied. The server uses it to append to the write-ahead log and publish
+// the event, so both see changes to a user in the order they happen; an error from the
+// hook cancels the change.
+//
+// A panic while a lock is held poisons it. The stores recover the guard instead of failing
+// every later request: a change is only applied after all its checks and its commit, so a
+// panic can't leave half of one behind.
+
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    hash::{BuildHasher, RandomState},
+    sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::{api_error, email_taken, ApiError, User};
+
+const SHARDS: usize = 16;
+
+// A change the store is about to make
+#[derive(Debug, Clone)]
+pub enum Change {
+    Created(User),
+    Updated(User),
+    // The user as it was
+    Deleted(User),
+}
+
+// Called with the changes before they are applied; an error cancels them
+pub type Commit<'a> = &'a mut dyn FnMut(&[Change]) -> Result<(), ApiError>;
+
+#[derive(Debug)]
+pub enum StoreError {
+    NotFound,
+    EmailTaken,
+    // The commit hook refused the change
+    Commit(ApiError),
+}
+
+impl From<StoreError> for ApiError {
+    fn from(error: StoreError) -> Self {
+        match error {
+            StoreError::NotFound => api_error(StatusCode::NOT_FOUND, "User not found"),
+            StoreError::EmailTaken => email_taken(),
+            StoreError::Commit(error) => error,
+        }
+    }
+}
+
+pub trait UserStore: Send + Sync {
+    fn get(&self, id: &Uuid) -> Option<User>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Visit every user. Not a point-in-time view: writes may land between shards.
+    fn for_each(&self, visit: &mut dyn FnMut(&User));
+
+    // Call `f` with every user while no write can commit (the compaction snapshot)
+    fn with_all(&self, f: &mut dyn FnMut(&mut dyn Iterator<Item = &User>));
+
+    // Add a user whose email is free
+    fn insert(&self, user: User, commit: Commit) -> Result<(), StoreError>;
+
+    // Add the users whose emails are free, with one commit; `true` for each one created
+    fn insert_many(&self, users: Vec<User>, commit: Commit) -> Result<Vec<bool>, StoreError>;
+
+    // Change a user with `edit` and return the result
+    fn update(&self, id: &Uuid, edit: &dyn Fn(&mut User), commit: Commit) -> Result<User, StoreError>;
+
+    fn remove(&self, id: &Uuid, commit: Commit) -> Result<User, StoreError>;
+}
+
+// The index key ignores case and surrounding spaces; the stored email keeps what was sent
+pub fn email_key(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn lock<T>(lock: &Mutex<T>) -> MutexGuard<'_, T> {
+    lock.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Lock order, to rule out deadlocks: user shards, then email shards (each in ascending
+// index order), then whatever the commit hook locks.
+pub struct ShardedStore {
+    users: Vec<RwLock<HashMap<Uuid, User>>>,
+    emails: Vec<RwLock<HashMap<String, Uuid>>>,
+    hasher: RandomState,
+}
+
+impl Default for ShardedStore {
+    fn default() -> Self {
+        Self {
+            users: (0..SHARDS).map(|_| RwLock::default()).collect(),
+            emails: (0..SHARDS).map(|_| RwLock::default()).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl ShardedStore {
+    // Load users read from disk; emails saved before they had to be unique keep their
+    // first owner in the index
+    pub fn from_users(users: impl IntoIterator<Item = User>) -> Self {
+        let store = Self::default();
+        for user in users {
+            let key = email_key(&user.email);
+            match write(&store.emails[store.email_shard(&key)]).entry(key) {
+                Entry::Occupied(_) => eprintln!("⚠️  Duplicate email {} (user {})", user.email, user.id),
+                Entry::Vacant(slot) => {
+                    slot.insert(user.id);
+                }
+            }
+            write(&store.users[store.user_shard(&user.id)]).insert(user.id, user);
+        }
+        store
+    }
+
+    // Ids are random already (v4), so their low bits pick the shard without hashing
+    fn user_shard(&self, id: &Uuid) -> usize {
+        id.as_u128() as usize % SHARDS
+    }
+
+    fn email_shard(&self, key: &str) -> usize {
+        self.hasher.hash_one(key) as usize % SHARDS
+    }
+}
+
+impl UserStore for ShardedStore {
+    fn get(&self, id: &Uuid) -> Option<User> {
+        read(&self.users[self.user_shard(id)]).get(id).cloned()
+    }
+
+    fn len(&self) -> usize {
+        self.users.iter().map(|shard| read(shard).len()).sum()
+    }
+
+    fn for_each(&self, visit: &mut dyn FnMut(&User)) {
+        for shard in &self.users {
+            read(shard).values().for_each(&mut *visit);
+        }
+    }
+
+    fn with_all(&self, f: &mut dyn FnMut(&mut dyn Iterator<Item = &User>)) {
+        // Writers hold their user shard until they have committed, so holding all of them
+        // means every committed change is in here and no other can commit meanwhile
+        let shards: Vec<_> = self.users.iter().map(read).collect();
+        f(&mut shards.iter().flat_map(|shard| shard.values()));
+    }
+
+    fn insert(&self, user: User, commit: Commit) -> Result<(), StoreError> {
+        let key = email_key(&user.email);
+        let mut users = write(&self.users[self.user_shard(&user.id)]);
+        let mut emails = write(&self.emails[self.email_shard(&key)]);
+        if emails.contains_key(&key) {
+            return Err(StoreError::EmailTaken);
+        }
+        commit(&[Change::Created(user.clone())]).map_err(StoreError::Commit)?;
+        emails.insert(key, user.id);
+        users.insert(user.id, user);
+        Ok(())
+    }
+
+    fn insert_many(&self, users: Vec<User>, commit: Commit) -> Result<Vec<bool>, StoreError> {
+        // A bulk operation: take every shard (in lock order) rather than sorting out which
+        let mut shards: Vec<_> = self.users.iter().map(write).collect();
+        let mut emails: Vec<_> = self.emails.iter().map(write).collect();
+        let mut claimed = HashSet::new();
+        let created: Vec<bool> = users
+            .iter()
+            .map(|user| {
+                let key = email_key(&user.email);
+                !emails[self.email_shard(&key)].contains_key(&key) && claimed.insert(key)
+            })
+            .collect();
+        let changes: Vec<Change> = users
+            .iter()
+            .zip(&created)
+            .filter(|(_, created)| **created)
+            .map(|(user, _)| Change::Created(user.clone()))
+            .collect();
+        commit(&changes).map_err(StoreError::Commit)?;
+        for (user, _) in users.into_iter().zip(&created).filter(|(_, created)| **created) {
+            let key = email_key(&user.email);
+            emails[self.email_shard(&key)].insert(key, user.id);
+            shards[self.user_shard(&user.id)].insert(user.id, user);
+        }
+        Ok(created)
+    }
+
+    fn update(&self, id: &Uuid, edit: &dyn Fn(&mut User), commit: Commit) -> Result<User, StoreError> {
+        let mut users = write(&self.users[self.user_shard(id)]);
+        let current = users.get(id).ok_or(StoreError::NotFound)?;
+        let old_key = email_key(&current.email);
+        let mut updated = current.clone();
+        edit(&mut updated);
+        updated.id = *id;
+        let new_key = email_key(&updated.email);
+
+        let (old_shard, new_shard) = (self.email_shard(&old_key), self.email_shard(&new_key));
+        let low = old_shard.min(new_shard);
+        let mut first = write(&self.emails[low]);
+        let mut second = (old_shard != new_shard).then(|| write(&self.emails[old_shard.max(new_shard)]));
+
+        let owner = pick(low, &mut first, second.as_deref_mut(), new_shard).get(&new_key);
+        if owner.is_some_and(|owner| owner != id) {
+            return Err(StoreError::EmailTaken);
+        }
+        commit(&[Change::Updated(updated.clone())]).map_err(StoreError::Commit)?;
+        if old_key != new_key {
+            let old_index = pick(low, &mut first, second.as_deref_mut(), old_shard);
+            if old_index.get(&old_key) == Some(id) {
+                old_index.remove(&old_key);
+            }
+            pick(low, &mut first, second.as_deref_mut(), new_shard).insert(new_key, *id);
+        }
+        users.insert(*id, updated.clone());
+        Ok(updated)
+    }
+
+    fn remove(&self, id: &Uuid, commit: Commit) -> Result<User, StoreError> {
+        let mut users = write(&self.users[self.user_shard(id)]);
+        let current = users.get(id).ok_or(StoreError::NotFound)?;
+        let key = email_key(&current.email);
+        let mut emails = write(&self.emails[self.email_shard(&key)]);
+        commit(&[Change::Deleted(current.clone())]).map_err(StoreError::Commit)?;
+        if emails.get(&key) == Some(id) {
+            emails.remove(&key);
+        }
+        users.remove(id).ok_or(StoreError::NotFound)
+    }
+}
+
+// One of the (one or two) email shards `update` locked, by shard number
+fn pick<'a>(
+    low: usize,
+    first: &'a mut HashMap<String, Uuid>,
+    second: Option<&'a mut HashMap<String, Uuid>>,
+    shard: usize,
+) -> &'a mut HashMap<String, Uuid> {
+    match second {
+        Some(second) if shard != low => second,
+        _ => first,
+    }
+}
+
+// Users by id plus the email index, behind one lock
+#[derive(Debug, Default)]
+struct Maps {
+    users: HashMap<Uuid, User>,
+    by_email: HashMap<String, Uuid>,
+}
+
+#[derive(Debug, Default)]
+pub struct MutexStore {
+    maps: Mutex<Maps>,
+}
+
+impl MutexStore {
+    pub fn from_users(users: impl IntoIterator<Item = User>) -> Self {
+        let mut maps = Maps::default();
+        for user in users {
+            maps.by_email.entry(email_key(&user.email)).or_insert(user.id);
+            maps.users.insert(user.id, user);
+        }
+        Self { maps: Mutex::new(maps) }
+    }
+}
+
+impl UserStore for MutexStore {
+    fn get(&self, id: &Uuid) -> Option<User> {
+        lock(&self.maps).users.get(id).cloned()
+    }
+
+    fn len(&self) -> usize {
+        lock(&self.maps).users.len()
+    }
+
+    fn for_each(&self, visit: &mut dyn FnMut(&User)) {
+        lock(&self.maps).users.values().for_each(visit);
+    }
+
+    fn with_all(&self, f: &mut dyn FnMut(&mut dyn Iterator<Item = &User>)) {
+        f(&mut lock(&self.maps).users.values());
+    }
+
+    fn insert(&self, user: User, commit: Commit) -> Result<(), StoreError> {
+        let mut maps = lock(&self.maps);
+        let key = email_key(&user.email);
+        if maps.by_email.contains_key(&key) {
+            return Err(StoreError::EmailTaken);
+        }
+        commit(&[Change::Created(user.clone())]).map_err(StoreError::Commit)?;
+        maps.by_email.insert(key, user.id);
+        maps.users.insert(user.id, user);
+        Ok(())
+    }
+
+    fn insert_many(&self, users: Vec<User>, commit: Commit) -> Result<Vec<bool>, StoreError> {
+        let mut maps = lock(&self.maps);
+        let mut claimed = HashSet::new();
+        let created: Vec<bool> = users
+            .iter()
+            .map(|user| {
+                let key = email_key(&user.email);
+                !maps.by_email.contains_key(&key) && claimed.insert(key)
+            })
+            .collect();
+        let changes: Vec<Change> = users
+            .iter()
+            .zip(&created)
+            .filter(|(_, created)| **created)
+            .map(|(user, _)| Change::Created(user.clone()))
+            .collect();
+        commit(&changes).map_err(StoreError::Commit)?;
+        for (user, _) in users.into_iter().zip(&created).filter(|(_, created)| **created) {
+            maps.by_email.insert(email_key(&user.email), user.id);
+            maps.users.insert(user.id, user);
+        }
+        Ok(created)
+    }
+
+    fn update(&self, id: &Uuid, edit: &dyn Fn(&mut User), commit: Commit) -> Result<User, StoreError> {
+        let mut maps = lock(&self.maps);
+        let current = maps.users.get(id).ok_or(StoreError::NotFound)?;
+        let old_key = email_key(&current.email);
+        let mut updated = current.clone();
+        edit(&mut updated);
+        updated.id = *id;
+        let new_key = email_key(&updated.email);
+        if maps.by_email.get(&new_key).is_some_and(|owner| owner != id) {
+            return Err(StoreError::EmailTaken);
+        }
+        commit(&[Change::Updated(updated.clone())]).map_err(StoreError::Commit)?;
+        if old_key != new_key {
+            if maps.by_email.get(&old_key) == Some(id) {
+                maps.by_email.remove(&old_key);
+            }
+            maps.by_email.insert(new_key, *id);
+        }
+        maps.users.insert(*id, updated.clone());
+        Ok(updated)
+    }
+
+    fn remove(&self, id: &Uuid, commit: Commit) -> Result<User, StoreError> {
+        let mut maps = lock(&self.maps);
+        let current = maps.users.get(id).ok_or(StoreError::NotFound)?;
+        commit(&[Change::Deleted(current.clone())]).map_err(StoreError::Commit)?;
+        let user = maps.users.remove(id).ok_or(StoreError::NotFound)?;
+        let key = email_key(&user.email);
+        if maps.by_email.get(&key) == Some(id) {
+            maps.by_email.remove(&key);
+        }
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    fn user(email: &str) -> User {
+        User { id: Uuid::new_v4(), name: "n".into(), email: email.into() }
+    }
+
+    fn no_hook(_: &[Change]) -> Result<(), ApiError> {
+        Ok(())
+    }
+
+    fn keeps_emails_unique(store: &dyn UserStore) {
+        let ada = user("ada@x.io");
+        store.insert(ada.clone(), &mut no_hook).unwrap();
+        assert!(matches!(store.insert(user(" ADA@x.io"), &mut no_hook), Err(StoreError::EmailTaken)));
+
+        let bob = user("bob@x.io");
+        let created = store.insert_many(vec![bob.clone(), user("Ada@X.io"), user("BOB@x.io")], &mut no_hook).unwrap();
+        assert_eq!(created, [true, false, false]);
+
+        let take_ada = |u: &mut User| u.email = "ada@X.IO".into();
+        assert!(matches!(store.update(&bob.id, &take_ada, &mut no_hook), Err(StoreError::EmailTaken)));
+        // Moving to a new address frees the old one
+        let moved = store.update(&ada.id, &|u| u.email = "ada@new.io".into(), &mut no_hook).unwrap();
+        assert_eq!(moved.email, "ada@new.io");
+        assert_eq!(store.update(&bob.id, &take_ada, &mut no_hook).unwrap().email, "ada@X.IO");
+
+        // A refused commit leaves nothing behind
+        let mut refuse = |_: &[Change]| Err(api_error(StatusCode::INTERNAL_SERVER_ERROR, "disk full"));
+        assert!(matches!(store.remove(&ada.id, &mut refuse), Err(StoreError::Commit(_))));
+        assert!(matches!(store.insert(user("carol@x.io"), &mut refuse), Err(StoreError::Commit(_))));
+        assert_eq!(store.len(), 2);
+        store.insert(user("carol@x.io"), &mut no_hook).unwrap();
+
+        assert_eq!(store.remove(&ada.id, &mut no_hook).unwrap().email, "ada@new.io");
+        assert!(matches!(store.remove(&ada.id, &mut no_hook), Err(StoreError::NotFound)));
+        store.insert(user("ada@new.io"), &mut no_hook).unwrap();
+        let mut count = 0;
+        store.with_all(&mut |users| count = users.count());
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn both_stores_keep_emails_unique() {
+        keeps_emails_unique(&ShardedStore::default());
+        keeps_emails_unique(&MutexStore::default());
+    }
+
+    #[test]
+    fn a_panic_under_the_lock_does_not_break_the_store() {
+        let store = ShardedStore::default();
+        let ada = user("ada@x.io");
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _ = store.insert(ada.clone(), &mut |_| panic!("hook failed"));
+        }));
+        assert!(result.is_err());
+        store.insert(ada.clone(), &mut no_hook).unwrap();
+        assert_eq!(store.get(&ada.id).unwrap().email, "ada@x.io");
+    }
+}
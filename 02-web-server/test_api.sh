@@ -1,13 +1,25 @@
 #!/usr/bin/env bash
 set -euo pipefail
 
-BASE_URL="http://127.0.0.1:3000"
+BASE_URL="${BASE_URL:-http://127.0.0.1:3000}"
+# Every request sends X-Request-Id: <run id>-<step>; grep the server log for the run id
+RUN_ID="test-api-$(date +%s)"
+# The counter lives in a file because pipes and $(...) run req in a subshell
+COUNTER=$(mktemp)
+trap 'rm -f "$COUNTER"' EXIT
+echo 0 > "$COUNTER"
+req() {
+  local step=$(( $(cat "$COUNTER") + 1 ))
+  echo "$step" > "$COUNTER"
+  curl -sS -H "X-Request-Id: ${RUN_ID}-${step}" "$@"
+}
+echo "Run id: ${RUN_ID}"
 
 echo "1) Health check"
-curl -sS ${BASE_URL}/health | jq . || true
+req ${BASE_URL}/health | jq . || true
 
 echo "\n2) Create user"
-CREATE_RES=$(curl -sS -X POST ${BASE_URL}/users \
+CREATE_RES=$(req -X POST ${BASE_URL}/users \
   -H 'Content-Type: application/json' \
   -d '{"name":"Ada Lovelace","email":"ada@lovelace.org"}')
 
@@ -15,8 +27,31 @@ echo "$CREATE_RES" | jq . || true
 USER_ID=$(echo "$CREATE_RES" | jq -r .id)
 
 echo "\n3) Get user by id"
-curl -sS ${BASE_URL}/users/${USER_ID} | jq . || true
+req ${BASE_URL}/users/${USER_ID} | jq . || true
 
 echo "\n4) Error: invalid email"
-curl -sS -X POST ${BASE_URL}/users -H 'Content-Type: application/json' -d '{"name":"x","email":"invalid"}' | jq . || true
+req -X POST ${BASE_URL}/users -H 'Content-Type: application/json' -d '{"name":"x","email":"invalid"}' | jq . || true
 
+
+echo "\n5) List and search users"
+req "${BASE_URL}/users?page=1&per_page=10" | jq . || true
+req "${BASE_URL}/users?q=lovelace" | jq . || true
+
+echo "\n6) Update user (PATCH, then PUT)"
+req -X PATCH ${BASE_URL}/users/${USER_ID} -H 'Content-Type: application/json' -d '{"name":"Augusta Ada King"}' | jq . || true
+req -X PUT ${BASE_URL}/users/${USER_ID} -H 'Content-Type: application/json' -d '{"name":"Ada King","email":"ada@king.org"}' | jq . || true
+
+echo "\n7) Error: update with invalid email"
+req -X PATCH ${BASE_URL}/users/${USER_ID} -H 'Content-Type: application/json' -d '{"email":"invalid"}' | jq . || true
+
+echo "\n8) Error: duplicate email, any case (409)"
+req -X POST ${BASE_URL}/users -H 'Content-Type: application/json' -d '{"name":"Copy","email":"ADA@king.org"}' | jq . || true
+
+echo "\n9) Bulk import (CSV) and export (NDJSON)"
+printf 'name,email\nGrace Hopper,grace@navy.mil\nNo Email,nope\nCopy,ADA@king.org\n' \
+  | req -X POST ${BASE_URL}/users/import -H 'Content-Type: text/csv' --data-binary @- | jq . || true
+req ${BASE_URL}/users/export -H 'Accept: application/x-ndjson' || true
+
+echo "\n10) Delete user, then get it (404)"
+req -o /dev/null -w "%{http_code}\n" -X DELETE ${BASE_URL}/users/${USER_ID}
+req ${BASE_URL}/users/${USER_ID} | jq . || true
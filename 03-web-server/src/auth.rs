@@ -1,5 +1,5 @@
-//! Authentication service providing password hashing (bcrypt) and JWT (HS256).
-//! coDemonstrates async traits and offloading CPU-bound work via spawn_blocking.
+//! Authentication service providing password hashing (bcrypt), short-lived JWTs (HS256) and refresh tokens.
+//! Demonstrates async traits and offloading CPU-bound work via spawn_blocking.
 
 use std::{sync::Arc, time::{SystemTime, UNIX_EPOCH}};
 
@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 use tokio::task;
 use uuid::Uuid;
 
-use crate::models::{AppError};
+use crate::{models::{AppError, TokenPair}, refresh::{InMemoryRefreshStore, RefreshTokens}};
 
 /// JWT claims payload; minimal for demo, add roles/permissions as needed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +31,12 @@ pub trait AuthService: Send + Sync {
     async fn verify_password(&self, password: String, hash: String) -> Result<bool, AppError>;
     async fn generate_token(&self, user_id: Uuid) -> Result<String, AppError>;
     async fn validate_token(&self, token: &str) -> Result<Claims, AppError>;
+    /// Access token plus the first refresh token of a new family (on login).
+    async fn issue_tokens(&self, user_id: Uuid) -> Result<TokenPair, AppError>;
+    /// Exchange a refresh token for a new pair; returns the user it belongs to.
+    async fn refresh(&self, refresh_token: &str) -> Result<(Uuid, TokenPair), AppError>;
+    /// End the family `refresh_token` belongs to, so neither it nor any later token can be used.
+    async fn revoke_refresh(&self, refresh_token: &str) -> Result<(), AppError>;
     async fn user_id_from_token(&self, token: &str) -> Result<Uuid, AppError> {
         let claims = self.validate_token(token).await?;
         Uuid::parse_str(&claims.sub).map_err(|e| AppError::Parse(e.to_string()))
@@ -42,12 +48,19 @@ pub trait AuthService: Send + Sync {
 pub struct JwtAuthService {
     encoding: EncodingKey,
     decoding: DecodingKey,
-    expiry_hours: i64,
+    access_ttl: Duration,
+    refresh: RefreshTokens,
 }
 
 impl JwtAuthService {
-    pub fn new(secret: &str, expiry_hours: i64) -> Self {
-        Self { encoding: EncodingKey::from_secret(secret.as_bytes()), decoding: DecodingKey::from_secret(secret.as_bytes()), expiry_hours }
+    pub fn new(secret: &str, access_ttl: Duration, refresh_ttl: Duration) -> Self {
+        let refresh = RefreshTokens::new(Arc::new(InMemoryRefreshStore::new()), refresh_ttl);
+        Self { encoding: EncodingKey::from_secret(secret.as_bytes()), decoding: DecodingKey::from_secret(secret.as_bytes()), access_ttl, refresh }
+    }
+
+    async fn token_pair(&self, user_id: Uuid, refresh_token: String) -> Result<TokenPair, AppError> {
+        let token = self.generate_token(user_id).await?;
+        Ok(TokenPair { token, refresh_token, expires_in: self.access_ttl.num_seconds() })
     }
 
     fn now_secs() -> usize { SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as usize }
@@ -68,7 +81,7 @@ impl AuthService for JwtAuthService {
 
     async fn generate_token(&self, user_id: Uuid) -> Result<String, AppError> {
         let iat = Self::now_secs();
-        let exp = (Utc::now() + Duration::hours(self.expiry_hours)).timestamp() as usize;
+        let exp = (Utc::now() + self.access_ttl).timestamp() as usize;
         let claims = Claims { sub: user_id.to_string(), iat, exp };
         let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)?;
         Ok(token)
@@ -80,6 +93,20 @@ impl AuthService for JwtAuthService {
         let data = decode::<Claims>(token, &self.decoding, &validation)?;
         Ok(data.claims)
     }
+
+    async fn issue_tokens(&self, user_id: Uuid) -> Result<TokenPair, AppError> {
+        let refresh_token = self.refresh.issue(user_id).await?;
+        self.token_pair(user_id, refresh_token).await
+    }
+
+    async fn refresh(&self, refresh_token: &str) -> Result<(Uuid, TokenPair), AppError> {
+        let (user_id, refresh_token) = self.refresh.rotate(refresh_token).await?;
+        Ok((user_id, self.token_pair(user_id, refresh_token).await?))
+    }
+
+    async fn revoke_refresh(&self, refresh_token: &str) -> Result<(), AppError> {
+        self.refresh.revoke(refresh_token).await
+    }
 }
 
 /// Extract bearer token from Authorization header.
@@ -91,9 +118,11 @@ pub fn bearer_from_headers(headers: &HeaderMap) -> Option<String> {
 mod tests {
     use super::*;
 
+    fn service() -> JwtAuthService { JwtAuthService::new("secret", Duration::minutes(15), Duration::days(1)) }
+
     #[tokio::test]
     async fn token_round_trip() {
-        let svc = JwtAuthService::new("secret", 1);
+        let svc = service();
         let uid = Uuid::new_v4();
         let token = svc.generate_token(uid).await.unwrap();
         let claims = svc.validate_token(&token).await.unwrap();
@@ -102,10 +131,18 @@ mod tests {
 
     #[tokio::test]
     async fn password_hash_verify() {
-        let svc = JwtAuthService::new("secret", 1);
+        let svc = service();
         let pwd = "Password1".to_string();
         let hash = svc.hash_password(pwd.clone()).await.unwrap();
         let ok = svc.verify_password(pwd, hash).await.unwrap();
         assert!(ok);
     }
+
+    #[tokio::test]
+    async fn a_revoked_refresh_token_cannot_be_rotated() {
+        let svc = service();
+        let login = svc.issue_tokens(Uuid::new_v4()).await.unwrap();
+        svc.revoke_refresh(&login.refresh_token).await.unwrap();
+        assert!(matches!(svc.refresh(&login.refresh_token).await, Err(AppError::Unauthorized(_))));
+    }
 }
@@ -9,7 +9,7 @@ use tokio::sync::Semaphore;
 use tower_http::trace::TraceLayer;
 use uuid::Uuid;
 
-use crate::{auth::{bearer_from_headers, AuthService}, models::{AppError, Paginated, RegisterRequest, LoginRequest, User, UserResponse, UserStatus, ApiResponse, now, generate_demo_verification_code}, repository::{ListOptions, UserRepository}};
+use crate::{auth::{bearer_from_headers, AuthService}, models::{AppError, Paginated, RegisterRequest, LoginRequest, RefreshRequest, User, UserResponse, UserStatus, ApiResponse, now, generate_demo_verification_code}, repository::{ListOptions, UserRepository}};
 
 /// Application state shared between handlers.
 #[derive(Clone)]
@@ -25,6 +25,7 @@ pub fn app(state: AppState) -> Router {
     let auth_routes = Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/refresh", post(refresh))
         .route("/me", get(me));
 
     let user_routes = Router::new()
@@ -41,7 +42,7 @@ pub fn app(state: AppState) -> Router {
 }
 
 #[derive(Debug, Deserialize)]
-struct PaginationQuery {
+pub struct PaginationQuery {
     page: Option<u32>,
     per_page: Option<u32>,
 }
@@ -75,8 +76,20 @@ pub async fn login(State(state): State<AppState>, Json(payload): Json<LoginReque
     let ok = state.auth.verify_password(payload.password, user.password_hash.clone()).await?;
     if !ok { return Err(AppError::Unauthorized("invalid credentials".into())); }
     // In a real app, you might disallow login if suspended/pending.
-    let token = state.auth.generate_token(user.id).await?;
-    Ok(Json(serde_json::json!({ "token": token })).into_response())
+    let tokens = state.auth.issue_tokens(user.id).await?;
+    Ok(Json(tokens))
+}
+
+/// POST /auth/refresh
+#[debug_handler]
+pub async fn refresh(State(state): State<AppState>, Json(payload): Json<RefreshRequest>) -> Result<impl IntoResponse, AppError> {
+    let (user_id, tokens) = state.auth.refresh(&payload.refresh_token).await?;
+    // A deleted account can't keep minting tokens: the pair just issued is never handed out, so end its family.
+    if state.repo.find_by_id(user_id).await.is_err() {
+        state.auth.revoke_refresh(&tokens.refresh_token).await?;
+        return Err(AppError::Unauthorized("invalid refresh token".into()));
+    }
+    Ok(Json(tokens))
 }
 
 /// Helper: extract current user from Authorization header.
@@ -2,4 +2,5 @@
 pub mod models;
 pub mod repository;
 pub mod auth;
+pub mod refresh;
 pub mod handlers;
@@ -4,6 +4,7 @@
 use std::{net::SocketAddr, sync::Arc};
 
 use axum::Router;
+use chrono::Duration;
 use tower_http::{cors::{Any, CorsLayer}, compression::CompressionLayer, trace::TraceLayer};
 use tracing_subscriber::{fmt, EnvFilter};
 
@@ -11,11 +12,16 @@ use web_server_03::auth::JwtAuthService;
 use web_server_03::handlers::{app, AppState};
 use web_server_03::repository::RepositoryFactory;
 
+// Access tokens are meant to be short-lived; past a year a "session" is effectively permanent.
+const MAX_ACCESS_TTL_MINUTES: i64 = 24 * 60;
+const MAX_REFRESH_TTL_DAYS: i64 = 365;
+
 #[derive(Clone, Debug)]
 struct Config {
     port: u16,
     jwt_secret: String,
-    jwt_exp_hours: i64,
+    access_ttl_minutes: i64,
+    refresh_ttl_days: i64,
     cors_allow_localhost: bool,
     max_page_size: u32,
     batch_limit: usize,
@@ -25,11 +31,29 @@ impl Config {
     fn from_env() -> Result<Self, String> {
         let port = std::env::var("PORT").ok().and_then(|s| s.parse::<u16>().ok()).unwrap_or(8080);
         let jwt_secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET env var is required".to_string())?;
-        let jwt_exp_hours = std::env::var("JWT_EXP_HOURS").ok().and_then(|s| s.parse::<i64>().ok()).unwrap_or(24);
+        if std::env::var_os("JWT_EXP_HOURS").is_some() {
+            tracing::warn!("JWT_EXP_HOURS is no longer used; set JWT_ACCESS_TTL_MINUTES and JWT_REFRESH_TTL_DAYS instead");
+        }
+        let access_ttl_minutes = ttl_from_env("JWT_ACCESS_TTL_MINUTES", 15, MAX_ACCESS_TTL_MINUTES)?;
+        let refresh_ttl_days = ttl_from_env("JWT_REFRESH_TTL_DAYS", 30, MAX_REFRESH_TTL_DAYS)?;
         let cors_allow_localhost = true; // sensible default for dev
         let max_page_size = 100;
         let batch_limit = std::env::var("BATCH_LIMIT").ok().and_then(|s| s.parse::<usize>().ok()).unwrap_or(8);
-        Ok(Self { port, jwt_secret, jwt_exp_hours, cors_allow_localhost, max_page_size, batch_limit })
+        Ok(Self { port, jwt_secret, access_ttl_minutes, refresh_ttl_days, cors_allow_localhost, max_page_size, batch_limit })
+    }
+}
+
+// Unset means the default. Anything but a whole number in 1..=max is rejected: a zero or negative TTL
+// would issue tokens that are born expired, and a huge one overflows chrono::Duration.
+fn ttl_from_env(name: &str, default: i64, max: i64) -> Result<i64, String> {
+    match std::env::var(name) {
+        Err(_) => Ok(default),
+        Ok(raw) => raw
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .filter(|v| (1..=max).contains(v))
+            .ok_or_else(|| format!("{} must be a whole number from 1 to {}, got '{}'", name, max, raw)),
     }
 }
 
@@ -49,7 +73,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Dependency injection: repository and auth service.
     let repo = RepositoryFactory::in_memory();
-    let auth = Arc::new(JwtAuthService::new(&cfg.jwt_secret, cfg.jwt_exp_hours)) as Arc<dyn web_server_03::auth::AuthService>;
+    let auth = JwtAuthService::new(&cfg.jwt_secret, Duration::minutes(cfg.access_ttl_minutes), Duration::days(cfg.refresh_ttl_days));
+    let auth = Arc::new(auth) as Arc<dyn web_server_03::auth::AuthService>;
 
     let state = AppState { repo, auth, max_page_size: cfg.max_page_size, batch_limit: cfg.batch_limit };
 
@@ -5,8 +5,6 @@
 //! - thiserror for ergonomic error types
 //! - Implementations of Axum IntoResponse for type-safe HTTP responses
 
-use std::fmt;
-
 use axum::{http::StatusCode, response::{IntoResponse, Response}};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
@@ -181,6 +179,22 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+/// Issued on login and on every refresh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair {
+    /// Short-lived access token (JWT) for the Authorization header.
+    pub token: String,
+    /// Opaque, single-use token for POST /auth/refresh.
+    pub refresh_token: String,
+    /// Seconds until `token` expires.
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: Uuid,
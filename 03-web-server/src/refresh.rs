@@ -0,0 +1,223 @@
+//! Opaque refresh tokens with rotation and reuse detection.
+//! Every login starts a token family. Each refresh uses up the presented token and issues the next
+//! one in the same family. Presenting a used token again means it was copied, so the whole family
+//! is revoked and the user has to log in again. A family ends at a deadline fixed at login, so
+//! rotating a stolen token can't keep it alive forever.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::models::AppError;
+
+/// Who a refresh token was issued to, and the family it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshRecord {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+}
+
+/// Outcome of using up a refresh token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Claim {
+    /// First use; the caller may issue the next token.
+    Fresh(RefreshRecord),
+    /// Already used before: a replay.
+    Reused(RefreshRecord),
+    /// Never issued, or expired.
+    Unknown,
+}
+
+/// Storage for refresh tokens, keyed by the SHA-256 of the token so a leaked store can't be replayed.
+#[async_trait]
+pub trait RefreshStore: Send + Sync {
+    /// Start a family that ends `ttl` from now, however often its tokens are rotated.
+    async fn create_family(&self, family_id: Uuid, ttl: Duration) -> Result<(), AppError>;
+    /// Store a token that expires after `ttl` or at its family's deadline, whichever comes first.
+    async fn insert(&self, token_hash: &str, record: RefreshRecord, ttl: Duration) -> Result<(), AppError>;
+    /// Mark a token as used. Must be atomic so two concurrent uses can't both see `Fresh`.
+    async fn claim(&self, token_hash: &str) -> Result<Claim, AppError>;
+    async fn family_active(&self, family_id: Uuid) -> Result<bool, AppError>;
+    async fn revoke_family(&self, family_id: Uuid) -> Result<(), AppError>;
+}
+
+struct Entry {
+    record: RefreshRecord,
+    used: bool,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Tokens {
+    entries: HashMap<String, Entry>,
+    families: HashMap<Uuid, DateTime<Utc>>,
+}
+
+/// In-memory refresh store; state is lost on restart, which logs everyone out.
+#[derive(Default)]
+pub struct InMemoryRefreshStore {
+    inner: Mutex<Tokens>,
+}
+
+impl InMemoryRefreshStore {
+    pub fn new() -> Self { Self::default() }
+}
+
+#[async_trait]
+impl RefreshStore for InMemoryRefreshStore {
+    async fn create_family(&self, family_id: Uuid, ttl: Duration) -> Result<(), AppError> {
+        let mut tokens = self.inner.lock().await;
+        // Logins are rare enough to sweep expired entries here instead of in a background task.
+        let now = Utc::now();
+        tokens.entries.retain(|_, e| e.expires_at > now);
+        tokens.families.retain(|_, expires_at| *expires_at > now);
+        tokens.families.insert(family_id, now + ttl);
+        Ok(())
+    }
+
+    async fn insert(&self, token_hash: &str, record: RefreshRecord, ttl: Duration) -> Result<(), AppError> {
+        let mut tokens = self.inner.lock().await;
+        let mut expires_at = Utc::now() + ttl;
+        if let Some(deadline) = tokens.families.get(&record.family_id) {
+            expires_at = expires_at.min(*deadline);
+        }
+        tokens.entries.insert(token_hash.to_string(), Entry { record, used: false, expires_at });
+        Ok(())
+    }
+
+    async fn claim(&self, token_hash: &str) -> Result<Claim, AppError> {
+        let mut tokens = self.inner.lock().await;
+        match tokens.entries.get_mut(token_hash) {
+            Some(entry) if entry.expires_at <= Utc::now() => Ok(Claim::Unknown),
+            Some(entry) if entry.used => Ok(Claim::Reused(entry.record)),
+            Some(entry) => {
+                entry.used = true;
+                Ok(Claim::Fresh(entry.record))
+            }
+            None => Ok(Claim::Unknown),
+        }
+    }
+
+    async fn family_active(&self, family_id: Uuid) -> Result<bool, AppError> {
+        let tokens = self.inner.lock().await;
+        Ok(tokens.families.get(&family_id).is_some_and(|expires_at| *expires_at > Utc::now()))
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> Result<(), AppError> {
+        self.inner.lock().await.families.remove(&family_id);
+        Ok(())
+    }
+}
+
+/// Issues and rotates refresh tokens on top of a [`RefreshStore`].
+#[derive(Clone)]
+pub struct RefreshTokens {
+    store: Arc<dyn RefreshStore>,
+    ttl: Duration,
+}
+
+impl RefreshTokens {
+    pub fn new(store: Arc<dyn RefreshStore>, ttl: Duration) -> Self { Self { store, ttl } }
+
+    /// Start a new family for `user_id` and return its first token.
+    pub async fn issue(&self, user_id: Uuid) -> Result<String, AppError> {
+        let record = RefreshRecord { user_id, family_id: Uuid::new_v4() };
+        self.store.create_family(record.family_id, self.ttl).await?;
+        self.insert(record).await
+    }
+
+    /// Use up `token` and return its user and the next token of the family.
+    pub async fn rotate(&self, token: &str) -> Result<(Uuid, String), AppError> {
+        match self.store.claim(&hash_token(token)).await? {
+            Claim::Unknown => Err(AppError::Unauthorized("invalid refresh token".into())),
+            Claim::Reused(record) => {
+                tracing::warn!(user_id = %record.user_id, family_id = %record.family_id, "refresh token reused; revoking its family");
+                self.store.revoke_family(record.family_id).await?;
+                Err(AppError::Unauthorized("refresh token already used; log in again".into()))
+            }
+            Claim::Fresh(record) => {
+                if !self.store.family_active(record.family_id).await? {
+                    return Err(AppError::Unauthorized("refresh token revoked".into()));
+                }
+                Ok((record.user_id, self.insert(record).await?))
+            }
+        }
+    }
+
+    /// Use up `token` and end its family; unknown tokens are ignored.
+    pub async fn revoke(&self, token: &str) -> Result<(), AppError> {
+        match self.store.claim(&hash_token(token)).await? {
+            Claim::Fresh(record) | Claim::Reused(record) => self.store.revoke_family(record.family_id).await,
+            Claim::Unknown => Ok(()),
+        }
+    }
+
+    async fn insert(&self, record: RefreshRecord) -> Result<String, AppError> {
+        // Two v4 UUIDs: 244 random bits, as 64 hex characters.
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        self.store.insert(&hash_token(&token), record, self.ttl).await?;
+        Ok(token)
+    }
+}
+
+fn hash_token(token: &str) -> String { format!("{:x}", Sha256::digest(token.as_bytes())) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> RefreshTokens { RefreshTokens::new(Arc::new(InMemoryRefreshStore::new()), Duration::days(1)) }
+
+    #[tokio::test]
+    async fn rotation_issues_a_new_token_and_uses_up_the_old_one() {
+        let tokens = tokens();
+        let uid = Uuid::new_v4();
+        let first = tokens.issue(uid).await.unwrap();
+        let (user_id, second) = tokens.rotate(&first).await.unwrap();
+        assert_eq!(user_id, uid);
+        assert_ne!(first, second);
+        let (_, third) = tokens.rotate(&second).await.unwrap();
+        assert!(tokens.rotate("not-a-token").await.is_err());
+        assert!(tokens.rotate(&third).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reuse_revokes_the_whole_family_but_not_others() {
+        let tokens = tokens();
+        let uid = Uuid::new_v4();
+        let stolen = tokens.issue(uid).await.unwrap();
+        let other_device = tokens.issue(uid).await.unwrap();
+        let (_, current) = tokens.rotate(&stolen).await.unwrap();
+
+        assert!(matches!(tokens.rotate(&stolen).await, Err(AppError::Unauthorized(_))));
+        // The legitimate holder's newer token died with the family...
+        assert!(matches!(tokens.rotate(&current).await, Err(AppError::Unauthorized(_))));
+        // ...while the session on another device is untouched.
+        assert!(tokens.rotate(&other_device).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn rotation_never_outlives_the_family_deadline() {
+        let store = InMemoryRefreshStore::new();
+        let record = RefreshRecord { user_id: Uuid::new_v4(), family_id: Uuid::new_v4() };
+        store.create_family(record.family_id, Duration::hours(1)).await.unwrap();
+        let deadline = store.inner.lock().await.families[&record.family_id];
+
+        // However late a rotation happens, the new token is capped at the login-time deadline
+        store.insert("next", record, Duration::days(30)).await.unwrap();
+        let tokens = store.inner.lock().await;
+        assert_eq!(tokens.families[&record.family_id], deadline);
+        assert_eq!(tokens.entries["next"].expires_at, deadline);
+    }
+
+    #[tokio::test]
+    async fn expired_tokens_are_unknown() {
+        let tokens = RefreshTokens::new(Arc::new(InMemoryRefreshStore::new()), Duration::zero());
+        let token = tokens.issue(Uuid::new_v4()).await.unwrap();
+        assert!(tokens.rotate(&token).await.is_err());
+    }
+}
@@ -7,7 +7,7 @@ use async_trait::async_trait;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::models::{now, AppError, User, UserStatus};
+use crate::models::{AppError, User, UserStatus};
 
 /// Paginated listing options.
 #[derive(Debug, Clone, Copy)]
@@ -107,8 +107,7 @@ impl UserRepository for InMemoryUserRepository {
 
     async fn stats(&self) -> Result<UserStats, AppError> {
         let map = self.inner.read().await;
-        let mut stats = UserStats::default();
-        stats.total = map.len();
+        let mut stats = UserStats { total: map.len(), ..Default::default() };
         for u in map.values() {
             match &u.status {
                 UserStatus::Active => stats.active += 1,
@@ -150,7 +149,7 @@ impl RepositoryFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::UserStatus;
+    use crate::models::{now, UserStatus};
 
     #[tokio::test]
     async fn in_memory_crud_and_stats() {
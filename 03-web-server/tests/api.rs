@@ -1,5 +1,6 @@
 use axum::{body::{self, Body}, http::{Request, StatusCode}, Router};
-use serde_json::json;
+use chrono::Duration;
+use serde_json::{json, Value};
 use std::sync::Arc;
 use tower::util::ServiceExt; // for `oneshot`
 
@@ -11,7 +12,7 @@ use web_server_03::repository::RepositoryFactory;
 async fn register_login_me_flow() {
     // Arrange: state with in-memory repo and deterministic JWT secret.
     let repo = RepositoryFactory::in_memory();
-    let auth = Arc::new(JwtAuthService::new("testsecret", 24)) as Arc<dyn AuthService>;
+    let auth = Arc::new(JwtAuthService::new("testsecret", Duration::minutes(15), Duration::days(30))) as Arc<dyn AuthService>;
     let state = AppState { repo, auth, max_page_size: 100, batch_limit: 4 };
     let app: Router = app(state.clone());
 
@@ -31,7 +32,7 @@ async fn register_login_me_flow() {
 
     // Login
     let payload = json!({ "email": "test@example.com", "password": "Password1" });
-    let mut resp = app
+    let resp = app
         .clone()
         .oneshot(
             Request::post("/auth/login")
@@ -59,3 +60,65 @@ async fn register_login_me_flow() {
         .unwrap();
     assert_eq!(resp.status(), StatusCode::OK);
 }
+
+async fn post_json(app: &Router, uri: &str, payload: Value) -> (StatusCode, Value) {
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::post(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body_bytes = body::to_bytes(resp.into_body(), 1024 * 1024).await.unwrap();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn refresh_rotates_and_reuse_revokes_the_family() {
+    let repo = RepositoryFactory::in_memory();
+    let auth = Arc::new(JwtAuthService::new("testsecret", Duration::minutes(15), Duration::days(30))) as Arc<dyn AuthService>;
+    let app: Router = app(AppState { repo, auth, max_page_size: 100, batch_limit: 4 });
+
+    let credentials = json!({ "email": "refresh@example.com", "password": "Password1" });
+    let (status, _) = post_json(&app, "/auth/register", credentials.clone()).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, login) = post_json(&app, "/auth/login", credentials).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(login["expires_in"], 15 * 60);
+    let first = login["refresh_token"].clone();
+
+    // Each refresh hands out a new refresh token
+    let (status, rotated) = post_json(&app, "/auth/refresh", json!({ "refresh_token": first })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(rotated["token"].is_string());
+    let second = rotated["refresh_token"].clone();
+    assert_ne!(first, second);
+
+    // Replaying the used token is rejected and takes the newer one down with it
+    let (status, _) = post_json(&app, "/auth/refresh", json!({ "refresh_token": first })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = post_json(&app, "/auth/refresh", json!({ "refresh_token": second })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn a_deleted_user_cannot_refresh() {
+    let repo = RepositoryFactory::in_memory();
+    let auth = Arc::new(JwtAuthService::new("testsecret", Duration::minutes(15), Duration::days(30))) as Arc<dyn AuthService>;
+    let app: Router = app(AppState { repo: repo.clone(), auth, max_page_size: 100, batch_limit: 4 });
+
+    let credentials = json!({ "email": "gone@example.com", "password": "Password1" });
+    let (status, _) = post_json(&app, "/auth/register", credentials.clone()).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, login) = post_json(&app, "/auth/login", credentials).await;
+    let user = repo.find_by_email("gone@example.com").await.unwrap();
+    repo.delete(user.id).await.unwrap();
+
+    let (status, body) = post_json(&app, "/auth/refresh", json!({ "refresh_token": login["refresh_token"] })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body.get("refresh_token").is_none());
+}
@@ -2,14 +2,17 @@ use std::{sync::Arc, time::{SystemTime, UNIX_EPOCH}};
 use async_trait::async_trait;
 use axum::http::HeaderMap;
 use bcrypt::{hash, verify, DEFAULT_COST};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use tokio::task;
 use uuid::Uuid;
 
-use crate::models::AppError;
+use crate::{
+    models::{AppError, ClientInfo, TokenPair},
+    refresh::{Claim, InMemoryRefreshStore, RedisRefreshStore, RefreshRecord, RefreshStore, RefreshTokens},
+    sessions::{InMemorySessionStore, RedisSessionStore, Session, SessionStore},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
@@ -23,9 +26,17 @@ pub struct Claims {
 pub trait AuthService: Send + Sync {
     async fn hash_password(&self, password: String) -> Result<String, AppError>;
     async fn verify_password(&self, password: String, hash: String) -> Result<bool, AppError>;
-    async fn generate_token(&self, user_id: Uuid) -> Result<String, AppError>;
     async fn validate_token(&self, token: &str) -> Result<Claims, AppError>;
+    // Ends the session the access token belongs to, refresh token included; the token may have expired
     async fn logout(&self, token: &str) -> Result<(), AppError>;
+    // Access token plus the first refresh token of a new session (login)
+    async fn issue_tokens(&self, user_id: Uuid, client: ClientInfo) -> Result<TokenPair, AppError>;
+    // Exchange a refresh token for a new pair; returns the user it belongs to
+    async fn refresh(&self, refresh_token: &str) -> Result<(Uuid, TokenPair), AppError>;
+    async fn sessions(&self, user_id: Uuid) -> Result<Vec<Session>, AppError>;
+    async fn revoke_session(&self, user_id: Uuid, jti: &str) -> Result<(), AppError>;
+    // Log out everywhere; returns how many sessions were ended
+    async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<usize, AppError>;
     async fn user_id_from_token(&self, token: &str) -> Result<Uuid, AppError> {
         let claims = self.validate_token(token).await?;
         Uuid::parse_str(&claims.sub).map_err(|e| AppError::Parse(e.to_string()))
@@ -36,14 +47,39 @@ pub trait AuthService: Send + Sync {
 pub struct HybridAuthService {
     encoding: EncodingKey,
     decoding: DecodingKey,
-    expiry_hours: i64,
-    redis: Option<redis::Client>,
+    access_ttl: Duration,
+    sessions: Arc<dyn SessionStore>,
+    refresh: RefreshTokens,
 }
 impl HybridAuthService {
-    pub fn new(secret: &str, expiry_hours: i64, redis: Option<redis::Client>) -> Self {
-        Self { encoding: EncodingKey::from_secret(secret.as_bytes()), decoding: DecodingKey::from_secret(secret.as_bytes()), expiry_hours, redis }
+    pub fn new(secret: &str, access_ttl: Duration, refresh_ttl: Duration, redis: Option<redis::Client>) -> Self {
+        // Without Redis, sessions and refresh tokens live in this process and die with it
+        let (sessions, refresh): (Arc<dyn SessionStore>, Arc<dyn RefreshStore>) = match redis {
+            Some(client) => (Arc::new(RedisSessionStore::new(client.clone())), Arc::new(RedisRefreshStore::new(client))),
+            None => (Arc::new(InMemorySessionStore::new()), Arc::new(InMemoryRefreshStore::new())),
+        };
+        Self { encoding: EncodingKey::from_secret(secret.as_bytes()), decoding: DecodingKey::from_secret(secret.as_bytes()), access_ttl, sessions, refresh: RefreshTokens::new(refresh, refresh_ttl) }
     }
     fn now_secs() -> usize { SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as usize }
+    // Signs an access token with a fresh jti; the caller whitelists it through the session store
+    fn sign(&self, user_id: Uuid) -> Result<(String, String), AppError> {
+        let iat = Self::now_secs();
+        let exp = (Utc::now() + self.access_ttl).timestamp() as usize;
+        let jti = Uuid::new_v4().to_string();
+        let claims = Claims { sub: user_id.to_string(), iat, exp, jti: jti.clone() };
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)?;
+        Ok((token, jti))
+    }
+    // Signs the session's next access token and records the session under its jti; the session keeps
+    // its login time and the family's deadline across refreshes
+    async fn sign_session(&self, user_id: Uuid, family_id: Uuid, client: ClientInfo, issued_at: DateTime<Utc>, expires_at: DateTime<Utc>) -> Result<(String, RefreshRecord), AppError> {
+        let (token, jti) = self.sign(user_id)?;
+        let session = Session { jti: jti.clone(), family_id, device: client.device, ip: client.ip, issued_at, expires_at };
+        self.sessions.add(user_id, &session, self.access_ttl).await?;
+        Ok((token, RefreshRecord { user_id, family_id, jti }))
+    }
+    fn token_pair(&self, token: String, refresh_token: String) -> TokenPair { TokenPair { token, refresh_token, expires_in: self.access_ttl.num_seconds() } }
+    async fn end(&self, session: &Session) -> Result<(), AppError> { self.refresh.revoke_family(session.family_id).await }
 }
 
 #[async_trait]
@@ -56,46 +92,124 @@ impl AuthService for HybridAuthService {
         let ok = task::spawn_blocking(move || verify(password, &hash_value)).await.map_err(|e| AppError::Bcrypt(e.to_string()))??;
         Ok(ok)
     }
-    async fn generate_token(&self, user_id: Uuid) -> Result<String, AppError> {
-        let iat = Self::now_secs();
-        let exp = (Utc::now() + Duration::hours(self.expiry_hours)).timestamp() as usize;
-        let jti = Uuid::new_v4().to_string();
-        let claims = Claims { sub: user_id.to_string(), iat, exp, jti: jti.clone() };
-        let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)?;
-        // whitelist jti in Redis with TTL
-        let ttl_secs_i64 = (exp as i64 - iat as i64).max(0);
-        let ttl: u64 = ttl_secs_i64.try_into().unwrap_or(0);
-        if let Some(client) = &self.redis {
-            let key = format!("jwt:{}", jti);
-            let mut conn = client.get_async_connection().await.map_err(|e| AppError::Repo(e.to_string()))?;
-            let _: () = conn.set_ex(key, "1", ttl).await.map_err(|e| AppError::Repo(e.to_string()))?;
-        }
-        Ok(token)
-    }
     async fn validate_token(&self, token: &str) -> Result<Claims, AppError> {
         let mut validation = Validation::new(Algorithm::HS256);
         validation.validate_exp = true;
         let data = decode::<Claims>(token, &self.decoding, &validation)?;
-        if let Some(client) = &self.redis {
-            let jti = data.claims.jti.clone();
-            let key = format!("jwt:{}", jti);
-            let mut conn = client.get_async_connection().await.map_err(|e| AppError::Repo(e.to_string()))?;
-            let exists: bool = conn.exists(key).await.map_err(|e| AppError::Repo(e.to_string()))?;
-            if !exists { return Err(AppError::Unauthorized("token revoked or expired".into())); }
-        }
+        if !self.sessions.is_active(&data.claims.jti).await? { return Err(AppError::Unauthorized("token revoked or expired".into())); }
         Ok(data.claims)
     }
     async fn logout(&self, token: &str) -> Result<(), AppError> {
-        if let Some(client) = &self.redis {
-            let data = decode::<Claims>(token, &self.decoding, &Validation::new(Algorithm::HS256))?;
-            let key = format!("jwt:{}", data.claims.jti);
-            let mut conn = client.get_async_connection().await.map_err(|e| AppError::Repo(e.to_string()))?;
-            let _: () = conn.del(key).await.map_err(|e| AppError::Repo(e.to_string()))?;
+        // An expired access token still ends its session: otherwise a client that logs out after the
+        // access token ran out would leave the refresh token alive. The signature is still checked,
+        // and a token whose session has moved on (refreshed or revoked) finds nothing
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_exp = false;
+        let claims = decode::<Claims>(token, &self.decoding, &validation).map_err(|_| AppError::Unauthorized("invalid token".into()))?.claims;
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|e| AppError::Parse(e.to_string()))?;
+        let session = self.sessions.remove(user_id, &claims.jti).await?.ok_or_else(|| AppError::Unauthorized("token revoked or expired".into()))?;
+        self.end(&session).await
+    }
+    async fn issue_tokens(&self, user_id: Uuid, client: ClientInfo) -> Result<TokenPair, AppError> {
+        let now = Utc::now();
+        let (token, record) = self.sign_session(user_id, Uuid::new_v4(), client, now, now + self.refresh.ttl()).await?;
+        let refresh_token = self.refresh.start(record).await?;
+        Ok(self.token_pair(token, refresh_token))
+    }
+    async fn refresh(&self, refresh_token: &str) -> Result<(Uuid, TokenPair), AppError> {
+        match self.refresh.claim(refresh_token).await? {
+            Claim::Unknown => Err(AppError::Unauthorized("invalid refresh token".into())),
+            Claim::Reused(record) => {
+                tracing::warn!(user_id = %record.user_id, family_id = %record.family_id, "refresh token reused; revoking its session");
+                self.refresh.revoke_family(record.family_id).await?;
+                // The session has moved on to a newer jti since this token was issued
+                let current = self.sessions.list(record.user_id).await?.into_iter().find(|s| s.family_id == record.family_id);
+                if let Some(session) = current { self.sessions.remove(record.user_id, &session.jti).await?; }
+                Err(AppError::Unauthorized("refresh token already used; log in again".into()))
+            }
+            Claim::Fresh(record) => {
+                if !self.refresh.family_active(record.family_id).await? { return Err(AppError::Unauthorized("refresh token revoked".into())); }
+                // Retire the access token issued with this refresh token; the session moves to the new one
+                let Some(old) = self.sessions.remove(record.user_id, &record.jti).await? else {
+                    return Err(AppError::Unauthorized("refresh token revoked".into()));
+                };
+                let client = ClientInfo { device: old.device, ip: old.ip };
+                let (token, next) = self.sign_session(record.user_id, record.family_id, client, old.issued_at, old.expires_at).await?;
+                let refresh_token = self.refresh.next(next).await?;
+                Ok((record.user_id, self.token_pair(token, refresh_token)))
+            }
         }
-        Ok(())
+    }
+    async fn sessions(&self, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        let mut sessions = self.sessions.list(user_id).await?;
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.issued_at));
+        Ok(sessions)
+    }
+    async fn revoke_session(&self, user_id: Uuid, jti: &str) -> Result<(), AppError> {
+        let session = self.sessions.remove(user_id, jti).await?.ok_or_else(|| AppError::NotFound("session".into()))?;
+        self.end(&session).await
+    }
+    async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<usize, AppError> {
+        let sessions = self.sessions.remove_all(user_id).await?;
+        for session in &sessions { self.end(session).await?; }
+        Ok(sessions.len())
     }
 }
 
 pub fn bearer_from_headers(headers: &HeaderMap) -> Option<String> {
     headers.get(axum::http::header::AUTHORIZATION).and_then(|h| h.to_str().ok()).and_then(|v| v.strip_prefix("Bearer ")).map(|s| s.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // No Redis: the in-memory session and refresh stores
+    fn service() -> HybridAuthService { HybridAuthService::new("test-secret-test-secret-test-secret", Duration::minutes(15), Duration::days(30), None) }
+    fn unauthorized<T>(result: Result<T, AppError>) -> bool { matches!(result, Err(AppError::Unauthorized(_))) }
+
+    #[tokio::test]
+    async fn logout_works_with_an_expired_access_token() {
+        // Past jsonwebtoken's 60s leeway, so the access token is born expired
+        let svc = HybridAuthService::new("test-secret-test-secret-test-secret", Duration::minutes(-5), Duration::days(30), None);
+        let uid = Uuid::new_v4();
+        let login = svc.issue_tokens(uid, ClientInfo::default()).await.unwrap();
+        assert!(svc.validate_token(&login.token).await.is_err());
+
+        svc.logout(&login.token).await.unwrap();
+        assert!(svc.sessions(uid).await.unwrap().is_empty());
+        assert!(unauthorized(svc.refresh(&login.refresh_token).await));
+        assert!(unauthorized(svc.logout(&login.token).await));
+        assert!(unauthorized(svc.logout("not-a-token").await));
+    }
+
+    #[tokio::test]
+    async fn refresh_rotates_both_tokens() {
+        let svc = service();
+        let uid = Uuid::new_v4();
+        let login = svc.issue_tokens(uid, ClientInfo::default()).await.unwrap();
+        assert_eq!(login.expires_in, 15 * 60);
+        let (user_id, rotated) = svc.refresh(&login.refresh_token).await.unwrap();
+        assert_eq!(user_id, uid);
+        assert_ne!(rotated.refresh_token, login.refresh_token);
+        assert_eq!(svc.user_id_from_token(&rotated.token).await.unwrap(), uid);
+        assert!(svc.refresh(&rotated.refresh_token).await.is_ok());
+        assert!(unauthorized(svc.refresh("not-a-token").await));
+    }
+
+    #[tokio::test]
+    async fn reuse_revokes_the_family_including_its_newest_token() {
+        let svc = service();
+        let uid = Uuid::new_v4();
+        let stolen = svc.issue_tokens(uid, ClientInfo::default()).await.unwrap();
+        let other_device = svc.issue_tokens(uid, ClientInfo::default()).await.unwrap();
+        let (_, current) = svc.refresh(&stolen.refresh_token).await.unwrap();
+
+        assert!(unauthorized(svc.refresh(&stolen.refresh_token).await));
+        // The legitimate holder's newer tokens died with the family...
+        assert!(unauthorized(svc.refresh(&current.refresh_token).await));
+        assert!(unauthorized(svc.validate_token(&current.token).await));
+        // ...while the session on another device is untouched
+        assert!(svc.refresh(&other_device.refresh_token).await.is_ok());
+    }
+}
@@ -1,12 +1,12 @@
-use std::sync::Arc;
-use axum::{debug_handler, extract::{Query, State}, http::StatusCode, response::IntoResponse, routing::{get, post}, Json, Router};
+use std::{net::{IpAddr, SocketAddr}, sync::Arc};
+use axum::{debug_handler, extract::{ConnectInfo, Path, Query, State}, http::{header, HeaderMap, StatusCode}, response::IntoResponse, routing::{delete, get, post}, Json, Router};
 use futures::future::join_all;
 use serde::Deserialize;
 use tokio::sync::Semaphore;
 use tower_http::trace::TraceLayer;
 use uuid::Uuid;
 
-use crate::{auth::{bearer_from_headers, AuthService}, models::{AppError, Paginated, RegisterRequest, LoginRequest, User, UserResponse, UserStatus, ApiResponse, now, generate_demo_verification_code}, repository::{ListOptions, UserRepository}};
+use crate::{auth::{bearer_from_headers, AuthService, Claims}, models::{AppError, ClientInfo, Paginated, RegisterRequest, LoginRequest, RefreshRequest, SessionResponse, User, UserResponse, UserStatus, ApiResponse, now, generate_demo_verification_code}, repository::{ListOptions, UserRepository}};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -14,6 +14,7 @@ pub struct AppState {
     pub auth: Arc<dyn AuthService>,
     pub max_page_size: u32,
     pub batch_limit: usize,
+    pub trusted_proxies: Arc<[IpAddr]>,
     pub db: Option<sqlx::PgPool>,
     pub redis: Option<redis::Client>,
 }
@@ -22,6 +23,10 @@ pub fn app(state: AppState) -> Router {
     let auth_routes = Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
+        .route("/sessions", get(list_sessions).delete(revoke_all_sessions))
+        .route("/sessions/:jti", delete(revoke_session))
         .route("/me", get(me));
 
     let user_routes = Router::new()
@@ -38,7 +43,7 @@ pub fn app(state: AppState) -> Router {
 }
 
 #[derive(Debug, Deserialize)]
-struct PaginationQuery { page: Option<u32>, per_page: Option<u32> }
+pub struct PaginationQuery { page: Option<u32>, per_page: Option<u32> }
 
 #[debug_handler]
 pub async fn register(State(state): State<AppState>, Json(payload): Json<RegisterRequest>) -> Result<impl IntoResponse, AppError> {
@@ -52,22 +57,94 @@ pub async fn register(State(state): State<AppState>, Json(payload): Json<Registe
 }
 
 #[debug_handler]
-pub async fn login(State(state): State<AppState>, Json(payload): Json<LoginRequest>) -> Result<impl IntoResponse, AppError> {
+pub async fn login(State(state): State<AppState>, peer: Option<ConnectInfo<SocketAddr>>, headers: HeaderMap, Json(payload): Json<LoginRequest>) -> Result<impl IntoResponse, AppError> {
     let user = state.repo.find_by_email(&payload.email).await.map_err(|_| AppError::Unauthorized("invalid credentials".into()))?;
     let ok = state.auth.verify_password(payload.password, user.password_hash.clone()).await?;
     if !ok { return Err(AppError::Unauthorized("invalid credentials".into())); }
-    let token = state.auth.generate_token(user.id).await?;
-    Ok(Json(serde_json::json!({ "token": token })).into_response())
+    let tokens = state.auth.issue_tokens(user.id, client_info(&headers, peer, &state.trusted_proxies)).await?;
+    Ok(Json(tokens))
 }
 
-async fn current_user_from_headers(state: &AppState, headers: &axum::http::HeaderMap) -> Result<User, AppError> {
+// Device is the User-Agent (display only). IP is the peer address; X-Forwarded-For is only believed
+// when the peer is a trusted proxy, and then the nearest hop that isn't one of our proxies is the client.
+// A hop that isn't an IP ends the chain we can vouch for, so the peer address is used instead
+fn client_info(headers: &HeaderMap, peer: Option<ConnectInfo<SocketAddr>>, trusted_proxies: &[IpAddr]) -> ClientInfo {
+    let header_str = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim).filter(|v| !v.is_empty());
+    let device = header_str(header::USER_AGENT.as_str()).map(|ua| ua.chars().take(200).collect());
+    let peer_ip = peer.map(|ConnectInfo(addr)| addr.ip());
+    let ip = match (peer_ip, header_str("x-forwarded-for")) {
+        (Some(peer_ip), Some(forwarded)) if trusted_proxies.contains(&peer_ip) => {
+            let mut client = peer_ip;
+            for hop in forwarded.split(',').rev() {
+                match hop.trim().parse::<IpAddr>() {
+                    Ok(hop) if trusted_proxies.contains(&hop) => client = hop,
+                    Ok(hop) => { client = hop; break }
+                    Err(_) => { client = peer_ip; break }
+                }
+            }
+            Some(client)
+        }
+        _ => peer_ip,
+    };
+    ClientInfo { device, ip: ip.map(|ip| ip.to_string()) }
+}
+
+#[debug_handler]
+pub async fn refresh(State(state): State<AppState>, Json(payload): Json<RefreshRequest>) -> Result<impl IntoResponse, AppError> {
+    let (user_id, tokens) = state.auth.refresh(&payload.refresh_token).await?;
+    // A deleted account can't keep minting tokens; the rotation already moved the session to the new
+    // pair, which is never handed out, so end the session and its refresh family
+    if state.repo.find_by_id(user_id).await.is_err() {
+        let claims = state.auth.validate_token(&tokens.token).await?;
+        state.auth.revoke_session(user_id, &claims.jti).await?;
+        return Err(AppError::Unauthorized("invalid refresh token".into()));
+    }
+    Ok(Json(tokens))
+}
+
+#[debug_handler]
+pub async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Result<impl IntoResponse, AppError> {
+    let token = bearer_from_headers(&headers).ok_or_else(|| AppError::Unauthorized("missing bearer token".into()))?;
+    state.auth.logout(&token).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn claims_from_headers(state: &AppState, headers: &HeaderMap) -> Result<(Uuid, Claims), AppError> {
+    let token = bearer_from_headers(headers).ok_or_else(|| AppError::Unauthorized("missing bearer token".into()))?;
+    let claims = state.auth.validate_token(&token).await?;
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|e| AppError::Parse(e.to_string()))?;
+    Ok((user_id, claims))
+}
+
+pub async fn list_sessions(State(state): State<AppState>, headers: HeaderMap) -> Result<impl IntoResponse, AppError> {
+    let (user_id, claims) = claims_from_headers(&state, &headers).await?;
+    let sessions = state.auth.sessions(user_id).await?;
+    let items: Vec<SessionResponse> = sessions.into_iter().map(|s| SessionResponse { current: s.jti == claims.jti, jti: s.jti, device: s.device, ip: s.ip, issued_at: s.issued_at, expires_at: s.expires_at }).collect();
+    Ok(Json(items))
+}
+
+pub async fn revoke_session(State(state): State<AppState>, headers: HeaderMap, Path(jti): Path<String>) -> Result<impl IntoResponse, AppError> {
+    let (user_id, _) = claims_from_headers(&state, &headers).await?;
+    // Only the caller's own sessions are found, so another user's jti is a 404
+    state.auth.revoke_session(user_id, &jti).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Log out everywhere, this session included
+pub async fn revoke_all_sessions(State(state): State<AppState>, headers: HeaderMap) -> Result<impl IntoResponse, AppError> {
+    let (user_id, _) = claims_from_headers(&state, &headers).await?;
+    let revoked = state.auth.revoke_all_sessions(user_id).await?;
+    Ok(Json(serde_json::json!({ "revoked": revoked })))
+}
+
+async fn current_user_from_headers(state: &AppState, headers: &HeaderMap) -> Result<User, AppError> {
     let token = bearer_from_headers(headers).ok_or_else(|| AppError::Unauthorized("missing bearer token".into()))?;
     let user_id = state.auth.user_id_from_token(&token).await?;
     let user = state.repo.find_by_id(user_id).await?;
     Ok(user)
 }
 
-pub async fn me(State(state): State<AppState>, headers: axum::http::HeaderMap) -> Result<impl IntoResponse, AppError> {
+pub async fn me(State(state): State<AppState>, headers: HeaderMap) -> Result<impl IntoResponse, AppError> {
     let user = current_user_from_headers(&state, &headers).await?;
     Ok(Json(UserResponse::from(user)))
 }
@@ -128,3 +205,37 @@ pub async fn health(State(state): State<AppState>) -> impl IntoResponse {
         (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({"status":"degraded","postgres": pg_ok, "redis": redis_ok })))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXY: [u8; 4] = [10, 0, 0, 1];
+
+    fn ip_of(forwarded: Option<&str>, peer: [u8; 4]) -> Option<String> {
+        let mut headers = HeaderMap::new();
+        if let Some(forwarded) = forwarded { headers.insert("x-forwarded-for", forwarded.parse().unwrap()); }
+        let trusted = [IpAddr::from(PROXY), IpAddr::from([10, 0, 0, 2])];
+        client_info(&headers, Some(ConnectInfo(SocketAddr::from((peer, 40000)))), &trusted).ip
+    }
+
+    #[test]
+    fn forwarded_for_is_only_believed_from_a_trusted_peer() {
+        assert_eq!(ip_of(Some("203.0.113.7"), PROXY).as_deref(), Some("203.0.113.7"));
+        assert_eq!(ip_of(Some("203.0.113.7"), [198, 51, 100, 9]).as_deref(), Some("198.51.100.9"));
+        assert_eq!(ip_of(None, PROXY).as_deref(), Some("10.0.0.1"));
+    }
+
+    #[test]
+    fn the_nearest_untrusted_hop_wins_over_a_spoofed_leftmost_one() {
+        assert_eq!(ip_of(Some("1.2.3.4, 203.0.113.7, 10.0.0.2"), PROXY).as_deref(), Some("203.0.113.7"));
+        // Only our own proxies in the chain: the leftmost one is as far back as we can see
+        assert_eq!(ip_of(Some("10.0.0.2, 10.0.0.1"), PROXY).as_deref(), Some("10.0.0.2"));
+    }
+
+    #[test]
+    fn an_unparseable_hop_falls_back_to_the_peer() {
+        assert_eq!(ip_of(Some("203.0.113.7, unknown, 10.0.0.2"), PROXY).as_deref(), Some("10.0.0.1"));
+        assert_eq!(ip_of(Some("203.0.113.7:5555"), PROXY).as_deref(), Some("10.0.0.1"));
+    }
+}
@@ -1,5 +1,7 @@
 pub mod models;
 pub mod repository;
 pub mod auth;
+pub mod refresh;
+pub mod sessions;
 pub mod handlers;
 pub mod config;
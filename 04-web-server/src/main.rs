@@ -1,5 +1,6 @@
 use std::{net::SocketAddr, sync::Arc};
 use axum::Router;
+use chrono::Duration;
 use tower_http::{cors::{Any, CorsLayer}, compression::CompressionLayer, trace::TraceLayer};
 use tracing_subscriber::EnvFilter;
 use sqlx::postgres::PgPoolOptions;
@@ -68,9 +69,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         RepositoryFactory::in_memory()
     };
 
-    let auth = Arc::new(HybridAuthService::new(&cfg.jwt.secret, cfg.jwt.expiry_hours, redis_client.clone())) as Arc<dyn AuthService>;
+    let access_ttl = Duration::minutes(cfg.jwt.access_ttl_minutes);
+    let refresh_ttl = Duration::days(cfg.jwt.refresh_ttl_days);
+    let auth = Arc::new(HybridAuthService::new(&cfg.jwt.secret, access_ttl, refresh_ttl, redis_client.clone())) as Arc<dyn AuthService>;
 
-    let state = AppState { repo, auth, max_page_size: cfg.max_page_size, batch_limit: cfg.batch_limit, db: pool.clone(), redis: redis_client.clone() };
+    let state = AppState { repo, auth, max_page_size: cfg.max_page_size, batch_limit: cfg.batch_limit, trusted_proxies: cfg.trusted_proxies.clone().into(), db: pool.clone(), redis: redis_client.clone() };
 
     let router: Router = app(state)
         .layer(CompressionLayer::new())
@@ -80,7 +83,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let addr = SocketAddr::from(([0, 0, 0, 0], cfg.server.port));
     tracing::info!("listening on {}", addr);
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, router).with_graceful_shutdown(shutdown_signal()).await?;
+    // ConnectInfo gives login the peer address for the session list
+    axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).with_graceful_shutdown(shutdown_signal()).await?;
     Ok(())
 }
 
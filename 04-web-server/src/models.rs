@@ -3,7 +3,6 @@ use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
-use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "status", rename_all = "snake_case")]
@@ -88,6 +87,17 @@ impl From<anyhow::Error> for AppError { fn from(e: anyhow::Error) -> Self { AppE
 pub struct RegisterRequest { pub email: String, pub password: String }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginRequest { pub email: String, pub password: String }
+// Issued on login and on every refresh; expires_in is the access token's lifetime in seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair { pub token: String, pub refresh_token: String, pub expires_in: i64 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshRequest { pub refresh_token: String }
+// Who is logging in, as far as the request tells; shown in the session list
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo { pub device: Option<String>, pub ip: Option<String> }
+// A session as listed to its owner; jti identifies it for DELETE /auth/sessions/:jti
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionResponse { pub jti: String, pub device: Option<String>, pub ip: Option<String>, pub issued_at: DateTime<Utc>, pub expires_at: DateTime<Utc>, pub current: bool }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse { pub id: Uuid, pub email: String, pub created_at: DateTime<Utc>, pub status: UserStatus }
 impl From<User> for UserResponse { fn from(u: User) -> Self { Self { id: u.id, email: u.email, created_at: u.created_at, status: u.status } } }
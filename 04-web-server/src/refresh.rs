@@ -0,0 +1,286 @@
+// Opaque refresh tokens with rotation and reuse detection.
+// Every login starts a token family; each refresh uses up the presented token and issues the next
+// one in the family. A used token presented again was copied, so its whole family is revoked.
+// A family ends at a deadline fixed at login; rotation never extends it.
+// Kept in Redis next to the jwt:{jti} whitelist, or in memory when Redis is unavailable:
+//   refresh:{sha256(token)}       hash {user, family, jti, used?}, TTL = refresh lifetime capped at the family's
+//   refresh_family:{family_id}    "1" while the family is alive, TTL = deadline; deleted on revocation
+// A family is one login session (see sessions.rs); jti is the access token issued with the refresh token.
+use std::{collections::HashMap, sync::Arc};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::models::AppError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshRecord { pub user_id: Uuid, pub family_id: Uuid, pub jti: String }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Claim {
+    // First use: the caller may issue the next token
+    Fresh(RefreshRecord),
+    // Used before: a replay
+    Reused(RefreshRecord),
+    // Never issued, or expired
+    Unknown,
+}
+
+#[async_trait]
+pub trait RefreshStore: Send + Sync {
+    // The family ends ttl from now, however often its tokens are rotated
+    async fn create_family(&self, family_id: Uuid, ttl: Duration) -> Result<(), AppError>;
+    // The token expires after ttl or at its family's deadline, whichever comes first
+    async fn insert(&self, token_hash: &str, record: RefreshRecord, ttl: Duration) -> Result<(), AppError>;
+    // Atomic, so two concurrent uses of one token can't both see Fresh
+    async fn claim(&self, token_hash: &str) -> Result<Claim, AppError>;
+    async fn family_active(&self, family_id: Uuid) -> Result<bool, AppError>;
+    async fn revoke_family(&self, family_id: Uuid) -> Result<(), AppError>;
+}
+
+fn token_key(token_hash: &str) -> String { format!("refresh:{}", token_hash) }
+fn family_key(family_id: Uuid) -> String { format!("refresh_family:{}", family_id) }
+fn redis_err(e: redis::RedisError) -> AppError { AppError::Repo(e.to_string()) }
+
+#[derive(Clone)]
+pub struct RedisRefreshStore { client: redis::Client }
+impl RedisRefreshStore { pub fn new(client: redis::Client) -> Self { Self { client } } }
+
+// Stores the token with its TTL capped at the family's remaining TTL; a missing family stores nothing
+const INSERT_SCRIPT: &str = r#"
+local family_ttl = redis.call('TTL', KEYS[2])
+if family_ttl <= 0 then return 0 end
+redis.call('HSET', KEYS[1], 'user', ARGV[1], 'family', ARGV[2], 'jti', ARGV[3])
+redis.call('EXPIRE', KEYS[1], math.min(tonumber(ARGV[4]), family_ttl))
+return 1
+"#;
+
+// Marks the token used and returns {state, user, family, jti}
+const CLAIM_SCRIPT: &str = r#"
+local fields = redis.call('HMGET', KEYS[1], 'user', 'family', 'jti', 'used')
+if not fields[1] then return {'unknown'} end
+if fields[4] then return {'reused', fields[1], fields[2], fields[3]} end
+redis.call('HSET', KEYS[1], 'used', '1')
+return {'fresh', fields[1], fields[2], fields[3]}
+"#;
+
+#[async_trait]
+impl RefreshStore for RedisRefreshStore {
+    async fn create_family(&self, family_id: Uuid, ttl: Duration) -> Result<(), AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        let _: () = conn.set_ex(family_key(family_id), "1", ttl.num_seconds().max(1) as u64).await.map_err(redis_err)?;
+        Ok(())
+    }
+    async fn insert(&self, token_hash: &str, record: RefreshRecord, ttl: Duration) -> Result<(), AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        let _: i64 = redis::Script::new(INSERT_SCRIPT)
+            .key(token_key(token_hash))
+            .key(family_key(record.family_id))
+            .arg(record.user_id.to_string())
+            .arg(record.family_id.to_string())
+            .arg(record.jti)
+            .arg(ttl.num_seconds().max(1))
+            .invoke_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        Ok(())
+    }
+    async fn claim(&self, token_hash: &str) -> Result<Claim, AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        let reply: Vec<String> = redis::Script::new(CLAIM_SCRIPT).key(token_key(token_hash)).invoke_async(&mut conn).await.map_err(redis_err)?;
+        let record = |user: &str, family: &str, jti: &str| -> Result<RefreshRecord, AppError> {
+            let parse = |s: &str| Uuid::parse_str(s).map_err(|e| AppError::Parse(e.to_string()));
+            Ok(RefreshRecord { user_id: parse(user)?, family_id: parse(family)?, jti: jti.to_string() })
+        };
+        match reply.as_slice() {
+            [state, user, family, jti] if state == "fresh" => Ok(Claim::Fresh(record(user, family, jti)?)),
+            [state, user, family, jti] if state == "reused" => Ok(Claim::Reused(record(user, family, jti)?)),
+            _ => Ok(Claim::Unknown),
+        }
+    }
+    async fn family_active(&self, family_id: Uuid) -> Result<bool, AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        conn.exists(family_key(family_id)).await.map_err(redis_err)
+    }
+    async fn revoke_family(&self, family_id: Uuid) -> Result<(), AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        let _: () = conn.del(family_key(family_id)).await.map_err(redis_err)?;
+        Ok(())
+    }
+}
+
+struct Entry { record: RefreshRecord, used: bool, expires_at: DateTime<Utc> }
+
+#[derive(Default)]
+struct Tokens { entries: HashMap<String, Entry>, families: HashMap<Uuid, DateTime<Utc>> }
+
+// Fallback when Redis is down; lost on restart, which logs everyone out
+#[derive(Default)]
+pub struct InMemoryRefreshStore { inner: Mutex<Tokens> }
+impl InMemoryRefreshStore { pub fn new() -> Self { Self::default() } }
+
+#[async_trait]
+impl RefreshStore for InMemoryRefreshStore {
+    async fn create_family(&self, family_id: Uuid, ttl: Duration) -> Result<(), AppError> {
+        let mut tokens = self.inner.lock().await;
+        // Sweep expired entries on login instead of running a background task
+        let now = Utc::now();
+        tokens.entries.retain(|_, e| e.expires_at > now);
+        tokens.families.retain(|_, expires_at| *expires_at > now);
+        tokens.families.insert(family_id, now + ttl);
+        Ok(())
+    }
+    async fn insert(&self, token_hash: &str, record: RefreshRecord, ttl: Duration) -> Result<(), AppError> {
+        let mut tokens = self.inner.lock().await;
+        let mut expires_at = Utc::now() + ttl;
+        if let Some(deadline) = tokens.families.get(&record.family_id) { expires_at = expires_at.min(*deadline); }
+        tokens.entries.insert(token_hash.to_string(), Entry { record, used: false, expires_at });
+        Ok(())
+    }
+    async fn claim(&self, token_hash: &str) -> Result<Claim, AppError> {
+        let mut tokens = self.inner.lock().await;
+        match tokens.entries.get_mut(token_hash) {
+            Some(entry) if entry.expires_at <= Utc::now() => Ok(Claim::Unknown),
+            Some(entry) if entry.used => Ok(Claim::Reused(entry.record.clone())),
+            Some(entry) => { entry.used = true; Ok(Claim::Fresh(entry.record.clone())) }
+            None => Ok(Claim::Unknown),
+        }
+    }
+    async fn family_active(&self, family_id: Uuid) -> Result<bool, AppError> {
+        let tokens = self.inner.lock().await;
+        Ok(tokens.families.get(&family_id).is_some_and(|expires_at| *expires_at > Utc::now()))
+    }
+    async fn revoke_family(&self, family_id: Uuid) -> Result<(), AppError> {
+        self.inner.lock().await.families.remove(&family_id);
+        Ok(())
+    }
+}
+
+// Issues refresh tokens on top of a RefreshStore; rotation and reuse handling live in auth.rs,
+// which also has to move or end the family's session
+#[derive(Clone)]
+pub struct RefreshTokens { store: Arc<dyn RefreshStore>, ttl: Duration }
+impl RefreshTokens {
+    pub fn new(store: Arc<dyn RefreshStore>, ttl: Duration) -> Self { Self { store, ttl } }
+
+    pub fn ttl(&self) -> Duration { self.ttl }
+
+    // Start record's family and return its first token
+    pub async fn start(&self, record: RefreshRecord) -> Result<String, AppError> {
+        self.store.create_family(record.family_id, self.ttl).await?;
+        self.next(record).await
+    }
+
+    // The next token of record's family
+    pub async fn next(&self, record: RefreshRecord) -> Result<String, AppError> {
+        // Two v4 UUIDs: 244 random bits as 64 hex characters
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        self.store.insert(&hash_token(&token), record, self.ttl).await?;
+        Ok(token)
+    }
+
+    pub async fn claim(&self, token: &str) -> Result<Claim, AppError> { self.store.claim(&hash_token(token)).await }
+    pub async fn family_active(&self, family_id: Uuid) -> Result<bool, AppError> { self.store.family_active(family_id).await }
+    pub async fn revoke_family(&self, family_id: Uuid) -> Result<(), AppError> { self.store.revoke_family(family_id).await }
+}
+
+// Only hashes are stored, so a dump of Redis can't be replayed
+fn hash_token(token: &str) -> String { format!("{:x}", Sha256::digest(token.as_bytes())) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(ttl: Duration) -> RefreshTokens { RefreshTokens::new(Arc::new(InMemoryRefreshStore::new()), ttl) }
+    fn record() -> RefreshRecord { RefreshRecord { user_id: Uuid::new_v4(), family_id: Uuid::new_v4(), jti: Uuid::new_v4().to_string() } }
+
+    // Redis tests run with `cargo test -- --ignored` and REDIS_URL set; they pass trivially without it
+    fn redis_store() -> Option<RedisRefreshStore> {
+        let url = std::env::var("REDIS_URL").ok()?;
+        Some(RedisRefreshStore::new(redis::Client::open(url).expect("REDIS_URL")))
+    }
+
+    async fn rotation_uses_up_each_token(tokens: RefreshTokens) {
+        let rec = record();
+        let first = tokens.start(rec.clone()).await.unwrap();
+        assert_eq!(tokens.claim(&first).await.unwrap(), Claim::Fresh(rec.clone()));
+        let second = tokens.next(rec.clone()).await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(tokens.claim(&first).await.unwrap(), Claim::Reused(rec.clone()));
+        assert_eq!(tokens.claim(&second).await.unwrap(), Claim::Fresh(rec));
+        assert_eq!(tokens.claim("not-a-token").await.unwrap(), Claim::Unknown);
+    }
+
+    async fn revoking_a_family_leaves_the_others(tokens: RefreshTokens) {
+        let (stolen, other) = (record(), record());
+        tokens.start(stolen.clone()).await.unwrap();
+        tokens.start(other.clone()).await.unwrap();
+        tokens.revoke_family(stolen.family_id).await.unwrap();
+        assert!(!tokens.family_active(stolen.family_id).await.unwrap());
+        assert!(tokens.family_active(other.family_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn rotation_uses_up_each_token_once() {
+        rotation_uses_up_each_token(tokens(Duration::days(1))).await;
+    }
+
+    #[tokio::test]
+    async fn revoking_a_family_leaves_others_alone() {
+        revoking_a_family_leaves_the_others(tokens(Duration::days(1))).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn redis_rotation_uses_up_each_token_once() {
+        let Some(store) = redis_store() else { return };
+        rotation_uses_up_each_token(RefreshTokens::new(Arc::new(store), Duration::days(1))).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn redis_revoking_a_family_leaves_others_alone() {
+        let Some(store) = redis_store() else { return };
+        revoking_a_family_leaves_the_others(RefreshTokens::new(Arc::new(store), Duration::days(1))).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn redis_tokens_never_outlive_their_family() {
+        let Some(store) = redis_store() else { return };
+        let rec = record();
+        store.create_family(rec.family_id, Duration::hours(1)).await.unwrap();
+        let (capped, orphan) = (format!("capped-{}", rec.family_id), format!("orphan-{}", rec.family_id));
+        store.insert(&capped, rec.clone(), Duration::days(30)).await.unwrap();
+        let mut conn = store.client.get_async_connection().await.unwrap();
+        let ttl: i64 = conn.ttl(token_key(&capped)).await.unwrap();
+        assert!((1..=3600).contains(&ttl), "token TTL {} outlives the family", ttl);
+
+        // A token of a revoked family is never stored
+        store.revoke_family(rec.family_id).await.unwrap();
+        store.insert(&orphan, rec, Duration::days(30)).await.unwrap();
+        assert_eq!(store.claim(&orphan).await.unwrap(), Claim::Unknown);
+    }
+
+    #[tokio::test]
+    async fn rotation_never_outlives_the_family_deadline() {
+        let store = InMemoryRefreshStore::new();
+        let rec = record();
+        store.create_family(rec.family_id, Duration::hours(1)).await.unwrap();
+        let deadline = store.inner.lock().await.families[&rec.family_id];
+        store.insert("next", rec.clone(), Duration::days(30)).await.unwrap();
+        let inner = store.inner.lock().await;
+        assert_eq!(inner.families[&rec.family_id], deadline);
+        assert_eq!(inner.entries["next"].expires_at, deadline);
+    }
+
+    #[tokio::test]
+    async fn expired_tokens_are_unknown() {
+        let tokens = tokens(Duration::zero());
+        let token = tokens.start(record()).await.unwrap();
+        assert_eq!(tokens.claim(&token).await.unwrap(), Claim::Unknown);
+    }
+}
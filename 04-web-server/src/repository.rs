@@ -243,8 +243,7 @@ impl UserRepository for InMemoryUserRepository {
     }
     async fn stats(&self) -> Result<UserStats, AppError> {
         let map = self.inner.read().await;
-        let mut s = UserStats::default();
-        s.total = map.len();
+        let mut s = UserStats { total: map.len(), ..Default::default() };
         for u in map.values() {
             match &u.status {
                 UserStatus::Active => s.active += 1,
@@ -0,0 +1,261 @@
+// Login sessions: the access-token whitelist plus a per-user index of active jtis.
+// A session starts at login and lasts as long as its refresh family; every refresh moves it to the
+// new access token's jti. In Redis, or in memory when Redis is unavailable:
+//   jwt:{jti}                  "1" while the access token may be used, TTL = exp - iat
+//   user_sessions:{user_id}    hash jti -> Session (JSON); the hash expires with the latest-ending session,
+//                              sessions that ran out earlier are dropped when listed
+use std::collections::HashMap;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::models::AppError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    // jti of the session's current access token
+    pub jti: String,
+    // Refresh family backing the session
+    pub family_id: Uuid,
+    // User-Agent at login
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    // Login time (kept across refreshes)
+    pub issued_at: DateTime<Utc>,
+    // When the refresh family ends (fixed at login)
+    pub expires_at: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    // Whitelist session.jti for access_ttl and index the session under user_id
+    async fn add(&self, user_id: Uuid, session: &Session, access_ttl: Duration) -> Result<(), AppError>;
+    async fn is_active(&self, jti: &str) -> Result<bool, AppError>;
+    async fn list(&self, user_id: Uuid) -> Result<Vec<Session>, AppError>;
+    // Un-whitelist jti and drop its session; None if user_id has no such session
+    async fn remove(&self, user_id: Uuid, jti: &str) -> Result<Option<Session>, AppError>;
+    async fn remove_all(&self, user_id: Uuid) -> Result<Vec<Session>, AppError>;
+}
+
+fn jwt_key(jti: &str) -> String { format!("jwt:{}", jti) }
+fn index_key(user_id: Uuid) -> String { format!("user_sessions:{}", user_id) }
+fn redis_err(e: redis::RedisError) -> AppError { AppError::Repo(e.to_string()) }
+fn parse(json: &str) -> Option<Session> { serde_json::from_str(json).ok() }
+
+// Whitelists the jti and indexes the session; the index TTL only ever grows, so it lasts as long as
+// the latest-ending session
+const ADD_SCRIPT: &str = r#"
+redis.call('SET', KEYS[1], '1', 'EX', ARGV[1])
+redis.call('HSET', KEYS[2], ARGV[2], ARGV[3])
+if redis.call('TTL', KEYS[2]) < tonumber(ARGV[4]) then redis.call('EXPIRE', KEYS[2], ARGV[4]) end
+return 1
+"#;
+
+// Reading the index and deleting what it names happen in one script, so a session added in between
+// can't lose its index entry while keeping its whitelist key
+const REMOVE_SCRIPT: &str = r#"
+local json = redis.call('HGET', KEYS[1], ARGV[1])
+if not json then return false end
+redis.call('HDEL', KEYS[1], ARGV[1])
+redis.call('DEL', KEYS[2])
+return json
+"#;
+
+// KEYS = [index, jwt key of each jti in ARGV]; only jtis still in the index are removed, so a session
+// another request ended or moved in the meantime is left alone. Returns the jtis removed
+const REMOVE_ALL_SCRIPT: &str = r#"
+local removed = {}
+for i = 1, #ARGV do
+  if redis.call('HDEL', KEYS[1], ARGV[i]) == 1 then
+    redis.call('DEL', KEYS[i + 1])
+    removed[#removed + 1] = ARGV[i]
+  end
+end
+return removed
+"#;
+
+#[derive(Clone)]
+pub struct RedisSessionStore { client: redis::Client }
+impl RedisSessionStore { pub fn new(client: redis::Client) -> Self { Self { client } } }
+
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn add(&self, user_id: Uuid, session: &Session, access_ttl: Duration) -> Result<(), AppError> {
+        let json = serde_json::to_string(session).map_err(|e| AppError::Unknown(e.to_string()))?;
+        let index_ttl = (session.expires_at - Utc::now()).num_seconds().max(1);
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        let _: i64 = redis::Script::new(ADD_SCRIPT)
+            .key(jwt_key(&session.jti))
+            .key(index_key(user_id))
+            .arg(access_ttl.num_seconds().max(1))
+            .arg(&session.jti)
+            .arg(json)
+            .arg(index_ttl)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        Ok(())
+    }
+    async fn is_active(&self, jti: &str) -> Result<bool, AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        conn.exists(jwt_key(jti)).await.map_err(redis_err)
+    }
+    async fn list(&self, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        let all: HashMap<String, String> = conn.hgetall(index_key(user_id)).await.map_err(redis_err)?;
+        let now = Utc::now();
+        let mut sessions = Vec::new();
+        let mut stale = Vec::new();
+        for (jti, json) in all {
+            match parse(&json) {
+                Some(session) if session.expires_at > now => sessions.push(session),
+                _ => stale.push(jti),
+            }
+        }
+        if !stale.is_empty() {
+            let _: () = conn.hdel(index_key(user_id), stale).await.map_err(redis_err)?;
+        }
+        Ok(sessions)
+    }
+    async fn remove(&self, user_id: Uuid, jti: &str) -> Result<Option<Session>, AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        let json: Option<String> = redis::Script::new(REMOVE_SCRIPT)
+            .key(index_key(user_id))
+            .key(jwt_key(jti))
+            .arg(jti)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        Ok(json.as_deref().and_then(parse))
+    }
+    async fn remove_all(&self, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        let mut conn = self.client.get_async_connection().await.map_err(redis_err)?;
+        // The script has to be told every key it touches, so the index is read first
+        let mut all: HashMap<String, String> = conn.hgetall(index_key(user_id)).await.map_err(redis_err)?;
+        if all.is_empty() { return Ok(Vec::new()); }
+        let script = redis::Script::new(REMOVE_ALL_SCRIPT);
+        let mut invocation = script.key(index_key(user_id));
+        for jti in all.keys() { invocation.key(jwt_key(jti)).arg(jti); }
+        let removed: Vec<String> = invocation.invoke_async(&mut conn).await.map_err(redis_err)?;
+        Ok(removed.iter().filter_map(|jti| all.remove(jti)).filter_map(|json| parse(&json)).collect())
+    }
+}
+
+#[derive(Default)]
+struct Sessions {
+    // jti -> access token expiry
+    active: HashMap<String, DateTime<Utc>>,
+    by_user: HashMap<Uuid, HashMap<String, Session>>,
+}
+
+// Fallback when Redis is down; lost on restart, which logs everyone out
+#[derive(Default)]
+pub struct InMemorySessionStore { inner: Mutex<Sessions> }
+impl InMemorySessionStore { pub fn new() -> Self { Self::default() } }
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn add(&self, user_id: Uuid, session: &Session, access_ttl: Duration) -> Result<(), AppError> {
+        let mut inner = self.inner.lock().await;
+        // Sweep expired entries here instead of running a background task
+        let now = Utc::now();
+        inner.active.retain(|_, expires_at| *expires_at > now);
+        inner.by_user.retain(|_, sessions| {
+            sessions.retain(|_, s| s.expires_at > now);
+            !sessions.is_empty()
+        });
+        inner.active.insert(session.jti.clone(), now + access_ttl);
+        inner.by_user.entry(user_id).or_default().insert(session.jti.clone(), session.clone());
+        Ok(())
+    }
+    async fn is_active(&self, jti: &str) -> Result<bool, AppError> {
+        let inner = self.inner.lock().await;
+        Ok(inner.active.get(jti).is_some_and(|expires_at| *expires_at > Utc::now()))
+    }
+    async fn list(&self, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        let inner = self.inner.lock().await;
+        let now = Utc::now();
+        Ok(inner.by_user.get(&user_id).map(|s| s.values().filter(|s| s.expires_at > now).cloned().collect()).unwrap_or_default())
+    }
+    async fn remove(&self, user_id: Uuid, jti: &str) -> Result<Option<Session>, AppError> {
+        let mut inner = self.inner.lock().await;
+        let Some(session) = inner.by_user.get_mut(&user_id).and_then(|s| s.remove(jti)) else { return Ok(None) };
+        inner.active.remove(jti);
+        Ok(Some(session))
+    }
+    async fn remove_all(&self, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        let mut inner = self.inner.lock().await;
+        let sessions = inner.by_user.remove(&user_id).unwrap_or_default();
+        for jti in sessions.keys() { inner.active.remove(jti); }
+        Ok(sessions.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(expires_in: Duration) -> Session {
+        let now = Utc::now();
+        Session { jti: Uuid::new_v4().to_string(), family_id: Uuid::new_v4(), device: Some("laptop".into()), ip: None, issued_at: now, expires_at: now + expires_in }
+    }
+
+    // Redis tests run with `cargo test -- --ignored` and REDIS_URL set; they pass trivially without it
+    fn redis_store() -> Option<RedisSessionStore> {
+        let url = std::env::var("REDIS_URL").ok()?;
+        Some(RedisSessionStore::new(redis::Client::open(url).expect("REDIS_URL")))
+    }
+
+    async fn removing_a_session_un_whitelists_its_jti(store: &dyn SessionStore) {
+        let (owner, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let s = session(Duration::days(1));
+        store.add(owner, &s, Duration::minutes(15)).await.unwrap();
+        assert!(store.is_active(&s.jti).await.unwrap());
+
+        assert!(store.remove(other, &s.jti).await.unwrap().is_none());
+        assert!(store.is_active(&s.jti).await.unwrap());
+        assert_eq!(store.remove(owner, &s.jti).await.unwrap().map(|s| s.family_id), Some(s.family_id));
+        assert!(!store.is_active(&s.jti).await.unwrap());
+        assert!(store.list(owner).await.unwrap().is_empty());
+    }
+
+    async fn remove_all_ends_every_live_session(store: &dyn SessionStore) {
+        let user = Uuid::new_v4();
+        let (live, stale) = (session(Duration::days(1)), session(Duration::seconds(-1)));
+        store.add(user, &stale, Duration::minutes(15)).await.unwrap();
+        store.add(user, &live, Duration::minutes(15)).await.unwrap();
+        assert_eq!(store.list(user).await.unwrap().len(), 1);
+
+        assert_eq!(store.remove_all(user).await.unwrap().len(), 1);
+        assert!(!store.is_active(&live.jti).await.unwrap());
+        assert!(store.list(user).await.unwrap().is_empty());
+        assert!(store.remove_all(user).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn removing_a_session_un_whitelists_its_jti_for_its_owner_only() {
+        removing_a_session_un_whitelists_its_jti(&InMemorySessionStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn remove_all_ends_every_session_and_expired_ones_are_not_listed() {
+        remove_all_ends_every_live_session(&InMemorySessionStore::new()).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn redis_removing_a_session_un_whitelists_its_jti_for_its_owner_only() {
+        let Some(store) = redis_store() else { return };
+        removing_a_session_un_whitelists_its_jti(&store).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn redis_remove_all_ends_every_session_and_expired_ones_are_not_listed() {
+        let Some(store) = redis_store() else { return };
+        remove_all_ends_every_live_session(&store).await;
+    }
+}
@@ -0,0 +1,158 @@
+// Session endpoints against the in-memory repository and stores (no Postgres or Redis needed)
+use std::{net::SocketAddr, sync::Arc};
+use axum::{body::{self, Body}, extract::ConnectInfo, http::{Request, StatusCode}, Router};
+use chrono::Duration;
+use serde_json::{json, Value};
+use tower::util::ServiceExt; // for `oneshot`
+
+use web_server_04::auth::{AuthService, HybridAuthService};
+use web_server_04::handlers::{app, AppState};
+use web_server_04::repository::RepositoryFactory;
+
+fn test_state() -> AppState {
+    let auth = Arc::new(HybridAuthService::new("testsecret-testsecret-testsecret!", Duration::minutes(15), Duration::days(30), None)) as Arc<dyn AuthService>;
+    AppState { repo: RepositoryFactory::in_memory(), auth, max_page_size: 100, batch_limit: 4, trusted_proxies: Arc::from([]), db: None, redis: None }
+}
+
+fn test_app() -> Router { app(test_state()) }
+
+async fn send(app: &Router, method: &str, uri: &str, token: Option<&str>, body: Option<Value>) -> (StatusCode, Value) {
+    let mut req = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+    if let Some(token) = token { req = req.header("authorization", format!("Bearer {}", token)); }
+    let req = req.body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty)).unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = body::to_bytes(resp.into_body(), 1024 * 1024).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn register(app: &Router, email: &str) {
+    let (status, _) = send(app, "POST", "/auth/register", None, Some(json!({ "email": email, "password": "Password1" }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+// Logs in as `device` from `ip`; returns the token pair
+async fn login(app: &Router, email: &str, device: &str, ip: [u8; 4]) -> Value {
+    let req = Request::post("/auth/login")
+        .header("content-type", "application/json")
+        .header("user-agent", device)
+        .extension(ConnectInfo(SocketAddr::from((ip, 40000))))
+        .body(Body::from(json!({ "email": email, "password": "Password1" }).to_string()))
+        .unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = body::to_bytes(resp.into_body(), 1024 * 1024).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+fn token(pair: &Value) -> &str { pair["token"].as_str().unwrap() }
+
+async fn refresh(app: &Router, pair: &Value) -> (StatusCode, Value) {
+    send(app, "POST", "/auth/refresh", None, Some(json!({ "refresh_token": pair["refresh_token"] }))).await
+}
+
+#[tokio::test]
+async fn sessions_list_each_login_and_only_the_owner_can_end_them() {
+    let app = test_app();
+    register(&app, "ada@example.com").await;
+    register(&app, "eve@example.com").await;
+    let laptop = login(&app, "ada@example.com", "laptop", [10, 0, 0, 1]).await;
+    let phone = login(&app, "ada@example.com", "phone", [10, 0, 0, 2]).await;
+    let eve = login(&app, "eve@example.com", "eve", [10, 0, 0, 3]).await;
+
+    let (status, sessions) = send(&app, "GET", "/auth/sessions", Some(token(&laptop)), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let sessions = sessions.as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    let find = |device: &str| sessions.iter().find(|s| s["device"] == device).unwrap().clone();
+    let (laptop_session, phone_session) = (find("laptop"), find("phone"));
+    assert_eq!((laptop_session["ip"].as_str(), laptop_session["current"].as_bool()), (Some("10.0.0.1"), Some(true)));
+    assert_eq!((phone_session["ip"].as_str(), phone_session["current"].as_bool()), (Some("10.0.0.2"), Some(false)));
+    assert!(laptop_session["issued_at"].is_string() && laptop_session["expires_at"].is_string());
+
+    // Another user's jti looks like no session at all
+    let phone_uri = format!("/auth/sessions/{}", phone_session["jti"].as_str().unwrap());
+    let (status, _) = send(&app, "DELETE", &phone_uri, Some(token(&eve)), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, "GET", "/auth/me", Some(token(&phone)), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, "DELETE", &phone_uri, Some(token(&laptop)), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "GET", "/auth/me", Some(token(&phone)), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(refresh(&app, &phone).await.0, StatusCode::UNAUTHORIZED);
+    let (status, _) = send(&app, "DELETE", &phone_uri, Some(token(&laptop)), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn logout_ends_the_session_and_its_refresh_token() {
+    let app = test_app();
+    register(&app, "ada@example.com").await;
+    let laptop = login(&app, "ada@example.com", "laptop", [10, 0, 0, 1]).await;
+    let phone = login(&app, "ada@example.com", "phone", [10, 0, 0, 2]).await;
+
+    let (status, _) = send(&app, "POST", "/auth/logout", Some(token(&phone)), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "GET", "/auth/me", Some(token(&phone)), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(refresh(&app, &phone).await.0, StatusCode::UNAUTHORIZED);
+
+    let (_, sessions) = send(&app, "GET", "/auth/sessions", Some(token(&laptop)), None).await;
+    assert_eq!(sessions.as_array().unwrap().len(), 1);
+    assert_eq!(sessions[0]["device"], "laptop");
+}
+
+#[tokio::test]
+async fn log_out_everywhere_ends_every_session() {
+    let app = test_app();
+    register(&app, "ada@example.com").await;
+    let mut pairs = Vec::new();
+    for device in ["laptop", "phone", "tablet"] { pairs.push(login(&app, "ada@example.com", device, [10, 0, 0, 1]).await); }
+
+    let (status, body) = send(&app, "DELETE", "/auth/sessions", Some(token(&pairs[0])), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["revoked"], 3);
+    for pair in &pairs {
+        let (status, _) = send(&app, "GET", "/auth/me", Some(token(pair)), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(refresh(&app, pair).await.0, StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn refresh_moves_the_session_but_keeps_its_login_time() {
+    let app = test_app();
+    register(&app, "ada@example.com").await;
+    let first = login(&app, "ada@example.com", "laptop", [10, 0, 0, 1]).await;
+    let (_, before) = send(&app, "GET", "/auth/sessions", Some(token(&first)), None).await;
+
+    let (status, second) = refresh(&app, &first).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, after) = send(&app, "GET", "/auth/sessions", Some(token(&second)), None).await;
+    assert_eq!(after.as_array().unwrap().len(), 1);
+    assert_ne!(after[0]["jti"], before[0]["jti"]);
+    assert_eq!(after[0]["issued_at"], before[0]["issued_at"]);
+    assert_eq!(after[0]["expires_at"], before[0]["expires_at"]);
+    assert_eq!((after[0]["device"].as_str(), after[0]["current"].as_bool()), (Some("laptop"), Some(true)));
+    // The access token issued before the refresh is retired with it
+    let (status, _) = send(&app, "GET", "/auth/me", Some(token(&first)), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn a_deleted_user_cannot_refresh_and_loses_the_session() {
+    let state = test_state();
+    let app = app(state.clone());
+    register(&app, "ada@example.com").await;
+    let laptop = login(&app, "ada@example.com", "laptop", [10, 0, 0, 1]).await;
+    let user = state.repo.find_by_email("ada@example.com").await.unwrap();
+    state.repo.delete(user.id).await.unwrap();
+
+    let (status, body) = refresh(&app, &laptop).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body.get("refresh_token").is_none());
+    // The rotation moved the session to a pair nobody received; it must not linger
+    assert!(state.auth.sessions(user.id).await.unwrap().is_empty());
+}